@@ -0,0 +1,1159 @@
+//! Lowering from the AST to the HIR: name collection, type resolution and
+//! expression checking.
+
+use super::*;
+use kql_ast::{Attribute, Database, Decl, Expr, ExprKind, Literal, Type, TypeArg, TypeKind};
+use kql_types::{Diagnostic, KqlError, Result, Severity, Span};
+use std::{collections::HashSet, rc::Rc};
+
+/// Lowers a parsed [Database] into a [HirProgram], accumulating errors as it
+/// goes. Lowering is a two pass process: [Lowerer::collect_names] assigns ids
+/// to every named declaration so forward references resolve, then
+/// [Lowerer::lower_content] lowers bodies.
+#[derive(Debug, Default)]
+pub struct Lowerer {
+    program: HirProgram,
+    errors: Vec<KqlError>,
+    next_id: DeclId,
+    ast_decls: IndexMap<DeclId, (Vec<String>, Rc<Decl>)>,
+    aliases_in_progress: HashSet<DeclId>,
+    /// Monomorphized generic struct instances, keyed by the generic decl and a
+    /// rendering of the applied type arguments.
+    instantiations: IndexMap<(DeclId, String), DeclId>,
+    /// Active generic parameter bindings; only the innermost frame is visible.
+    substitutions: Vec<IndexMap<String, HirType>>,
+    /// `seed` declarations, lowered after every struct is known.
+    seed_decls: Vec<(Vec<String>, kql_ast::SeedDecl)>,
+    /// Spans whose root cause has already been reported; dependent checks
+    /// consult this so one bad declaration doesn't cascade into a diagnostic
+    /// at every use site.
+    errored_spans: HashSet<Span>,
+}
+
+impl Lowerer {
+    /// Lower a complete database into a program, reporting every error found
+    /// rather than stopping at the first bad declaration.
+    pub fn lower_program(db: Database) -> Result<HirProgram, Vec<KqlError>> {
+        let mut lowerer = Self::default();
+        lowerer.collect_names(db.decls, Vec::new());
+        lowerer.lower_content();
+        if !lowerer.errors.is_empty() {
+            return Err(lowerer.errors);
+        }
+        Ok(lowerer.program)
+    }
+
+    /// Like [Self::lower_program], also reporting how long the name-collection
+    /// and content-lowering passes each took.
+    pub fn lower_program_timed(
+        db: Database,
+        name_collection: &mut std::time::Duration,
+        lowering: &mut std::time::Duration,
+    ) -> Result<HirProgram, Vec<KqlError>> {
+        let mut lowerer = Self::default();
+        let start = std::time::Instant::now();
+        lowerer.collect_names(db.decls, Vec::new());
+        *name_collection = start.elapsed();
+        let start = std::time::Instant::now();
+        lowerer.lower_content();
+        *lowering = start.elapsed();
+        if !lowerer.errors.is_empty() {
+            return Err(lowerer.errors);
+        }
+        Ok(lowerer.program)
+    }
+
+    fn collect_names(&mut self, decls: Vec<Decl>, namespace: Vec<String>) {
+        for decl in decls {
+            // Namespaces and imports are flattened away; everything else is
+            // stored once behind an `Rc` so later passes never clone the tree.
+            if let Decl::Namespace(n) = decl {
+                let mut inner = namespace.clone();
+                inner.push(n.name.name);
+                self.collect_names(n.decls, inner);
+                continue;
+            }
+            if let Decl::Import(_) = decl {
+                continue;
+            }
+            if let Decl::Seed(s) = decl {
+                self.seed_decls.push((namespace.clone(), s));
+                continue;
+            }
+            let (name, kind) = match &decl {
+                Decl::Struct(s) => (s.name.clone(), HirDeclKind::Struct),
+                Decl::Enum(e) => (e.name.clone(), HirDeclKind::Enum),
+                Decl::TypeAlias(t) => (t.name.clone(), HirDeclKind::TypeAlias),
+                Decl::Let(l) => (l.name.clone(), HirDeclKind::Let),
+                Decl::Namespace(_) | Decl::Import(_) | Decl::Seed(_) => unreachable!("handled above"),
+            };
+            if PrimitiveType::from_name(&name.name).is_some() || RESERVED_TYPE_NAMES.contains(&name.name.as_str()) {
+                let message = format!("`{}` shadows a built-in type name; rename it to avoid ambiguous resolution", name.name);
+                self.program.warnings.push(Diagnostic { severity: Severity::Warning, message, span: name.span });
+            }
+            let full_name = qualify(&namespace, &name.name);
+            if self.program.name_to_id.contains_key(&full_name) {
+                self.errors.push(KqlError::semantic(format!("duplicate declaration of `{}`", full_name), name.span));
+                continue;
+            }
+            let id = self.next_id;
+            self.next_id += 1;
+            self.program.name_to_id.insert(full_name, id);
+            self.program.id_to_kind.insert(id, kind);
+            self.ast_decls.insert(id, (namespace.clone(), Rc::new(decl)));
+        }
+    }
+
+    fn lower_content(&mut self) {
+        let ids: Vec<DeclId> = self.ast_decls.keys().copied().collect();
+        for id in ids {
+            let (namespace, decl) = {
+                let (namespace, decl) = &self.ast_decls[&id];
+                (namespace.clone(), Rc::clone(decl))
+            };
+            match &*decl {
+                // Generic structs are templates: they only produce HIR structs
+                // when instantiated at a use site.
+                Decl::Struct(s) if !s.generics.is_empty() => {}
+                Decl::Struct(s) => self.lower_struct(id, &namespace, s),
+                Decl::Enum(e) => self.lower_enum(id, &namespace, e),
+                Decl::TypeAlias(t) => {
+                    // A cyclic alias is an error on that alias alone; keep
+                    // lowering the remaining declarations.
+                    if let Err(error) = self.lower_type_alias(id, &namespace, t) {
+                        self.errors.push(error);
+                    }
+                }
+                Decl::Let(l) => self.lower_let(id, &namespace, l),
+                Decl::Namespace(_) | Decl::Import(_) | Decl::Seed(_) => {}
+            }
+        }
+        for (namespace, seed) in std::mem::take(&mut self.seed_decls) {
+            self.lower_seed(&namespace, &seed);
+        }
+        self.check_relation_cycles();
+    }
+
+    /// Warn on cycles made entirely of mandatory (non-nullable) foreign keys:
+    /// no row can be inserted into any table of such a cycle without
+    /// violating a constraint. A self-referencing FK and cycles broken by a
+    /// nullable FK are satisfiable, so they produce nothing.
+    fn check_relation_cycles(&mut self) {
+        let mut edges: IndexMap<DeclId, Vec<DeclId>> = IndexMap::new();
+        for item in self.program.structs.values() {
+            for field in &item.fields {
+                let mut ty = &field.ty;
+                let mut nullable = false;
+                while let HirType::Optional(inner) = ty {
+                    nullable = true;
+                    ty = inner;
+                }
+                let target = match ty {
+                    HirType::ForeignKey { entity, .. } => Some(*entity),
+                    // Cross-entity keys lower to foreign keys as well.
+                    HirType::Key { entity: Some(entity), .. } if *entity != item.id => Some(*entity),
+                    _ => None,
+                };
+                if let Some(target) = target {
+                    if !nullable && target != item.id {
+                        edges.entry(item.id).or_default().push(target);
+                    }
+                }
+            }
+        }
+        for component in strongly_connected(&edges) {
+            if component.len() < 2 {
+                continue;
+            }
+            let names = component.iter().map(|id| format!("`{}`", self.decl_name(*id))).collect::<Vec<_>>().join(", ");
+            let message =
+                format!("mandatory foreign keys form a cycle through {}; make one of them nullable to allow inserts", names);
+            let span = self.program.structs.get(&component[0]).map(|s| s.span).unwrap_or_default();
+            self.program.warnings.push(Diagnostic { severity: Severity::Warning, message, span });
+        }
+    }
+
+    fn lower_struct(&mut self, id: DeclId, namespace: &[String], decl: &kql_ast::StructDecl) {
+        let mut fields = Vec::new();
+        for field in &decl.fields {
+            let ty = self.lower_type(&field.ty, namespace);
+            if ty == HirType::Unknown {
+                // Lowering already reported why the type failed; remember the
+                // field so uses of it don't repeat the diagnostic.
+                self.errored_spans.insert(field.span);
+            }
+            self.check_key_entity(id, &decl.name.name, &field.name.name, &ty, field.span);
+            let attributes = self.lower_attributes(&field.attributes);
+            fields.push(HirField { name: field.name.name.clone(), ty, attributes, docs: field.docs.clone(), span: field.span });
+        }
+        let item = HirStruct {
+            id,
+            name: decl.name.name.clone(),
+            namespace: namespace.to_vec(),
+            full_name: qualify(namespace, &decl.name.name),
+            fields,
+            attributes: self.lower_attributes(&decl.attributes),
+            docs: decl.docs.clone(),
+            span: decl.span,
+        };
+        self.program.structs.insert(id, item);
+    }
+
+    /// Warn when a `Key<Entity, T>` field is keyed to a struct other than the
+    /// one declaring it. Cross-entity keys are allowed — they lower to foreign
+    /// keys — but more often the entity argument is a copy-paste mistake.
+    fn check_key_entity(&mut self, id: DeclId, struct_name: &str, field_name: &str, ty: &HirType, span: Span) {
+        let mut ty = ty;
+        while let HirType::Optional(inner) = ty {
+            ty = inner;
+        }
+        if let HirType::Key { entity: Some(entity), .. } = ty {
+            if *entity != id {
+                let message = format!(
+                    "`{}` is declared as `Key<{}, _>` inside `{}`; use `Key<{}, _>` unless a cross-entity key is intended",
+                    field_name,
+                    self.decl_name(*entity),
+                    struct_name,
+                    struct_name
+                );
+                self.program.warnings.push(Diagnostic { severity: Severity::Warning, message, span });
+            }
+        }
+    }
+
+    /// The generic parameter names of a struct declaration, empty when the
+    /// struct is not generic.
+    fn struct_generics(&self, id: DeclId) -> Vec<String> {
+        match self.ast_decls.get(&id) {
+            Some((_, decl)) => match &**decl {
+                Decl::Struct(s) => s.generics.iter().map(|g| g.name.clone()).collect(),
+                _ => Vec::new(),
+            },
+            None => Vec::new(),
+        }
+    }
+
+    /// Monomorphize a generic struct at a use site, substituting its type
+    /// parameters with the applied arguments. Instances are cached so
+    /// `Paged<User>` lowers to the same struct everywhere.
+    fn instantiate_struct(
+        &mut self,
+        id: DeclId,
+        generics: &[String],
+        args: &[TypeArg],
+        namespace: &[String],
+        span: Span,
+    ) -> HirType {
+        let (decl_namespace, decl) = {
+            let (ns, decl) = &self.ast_decls[&id];
+            (ns.clone(), Rc::clone(decl))
+        };
+        let Decl::Struct(decl) = &*decl else {
+            return HirType::Unknown;
+        };
+        if args.len() != generics.len() {
+            let message = format!("`{}` expects {} type argument(s), found {}", decl.name.name, generics.len(), args.len());
+            self.errors.push(KqlError::semantic(message, span));
+            return HirType::Unknown;
+        }
+        let mut arg_types = Vec::new();
+        for arg in args {
+            match arg {
+                TypeArg::Type(ty) => arg_types.push(self.lower_type(ty, namespace)),
+                TypeArg::Named { name, .. } => {
+                    self.errors.push(KqlError::semantic("generic arguments must be positional", name.span));
+                    return HirType::Unknown;
+                }
+            }
+        }
+        let key = (id, format!("{:?}", arg_types));
+        if let Some(existing) = self.instantiations.get(&key) {
+            return HirType::Struct(*existing);
+        }
+        let new_id = self.next_id;
+        self.next_id += 1;
+        self.instantiations.insert(key, new_id);
+        self.program.id_to_kind.insert(new_id, HirDeclKind::Struct);
+        let arg_names: Vec<String> = arg_types.iter().map(|ty| self.type_display(ty)).collect();
+        let display = format!("{}<{}>", decl.name.name, arg_names.join(", "));
+        let full_name = qualify(&decl_namespace, &display);
+        self.program.name_to_id.insert(full_name.clone(), new_id);
+        let frame: IndexMap<String, HirType> = generics.iter().cloned().zip(arg_types.iter().cloned()).collect();
+        self.substitutions.push(frame);
+        let mut fields = Vec::new();
+        for field in &decl.fields {
+            let ty = self.lower_type(&field.ty, &decl_namespace);
+            let attributes = self.lower_attributes(&field.attributes);
+            fields.push(HirField { name: field.name.name.clone(), ty, attributes, docs: field.docs.clone(), span: field.span });
+        }
+        self.substitutions.pop();
+        let item = HirStruct {
+            id: new_id,
+            // Physical-name friendly: `Paged<User>` reads `PagedUser` in
+            // generated artifacts.
+            name: format!("{}{}", decl.name.name, arg_names.join("")),
+            namespace: decl_namespace.clone(),
+            full_name,
+            fields,
+            attributes: self.lower_attributes(&decl.attributes),
+            docs: decl.docs.clone(),
+            span: decl.span,
+        };
+        self.program.structs.insert(new_id, item);
+        HirType::Struct(new_id)
+    }
+
+    /// Render a lowered type the way it would be written in source.
+    fn type_display(&self, ty: &HirType) -> String {
+        match ty {
+            HirType::Primitive(p) => p.name().to_string(),
+            HirType::Struct(id) => match self.program.structs.get(id) {
+                Some(s) => s.name.clone(),
+                None => self.decl_name(*id),
+            },
+            HirType::Enum(id) => self.decl_name(*id),
+            HirType::List(inner) => format!("[{}]", self.type_display(inner)),
+            HirType::Optional(inner) => format!("{}?", self.type_display(inner)),
+            HirType::Tuple(items) => {
+                format!("({})", items.iter().map(|i| self.type_display(i)).collect::<Vec<_>>().join(", "))
+            }
+            HirType::Key { ty, .. } => format!("Key<{}>", self.type_display(ty)),
+            HirType::ForeignKey { entity, .. } => format!("ForeignKey<{}>", self.decl_name(*entity)),
+            HirType::Unknown => "?".to_string(),
+        }
+    }
+
+    fn decl_name(&self, id: DeclId) -> String {
+        self.ast_decls.get(&id).and_then(|(_, d)| d.name()).map(|ident| ident.name.clone()).unwrap_or_else(|| "?".to_string())
+    }
+
+    fn lower_enum(&mut self, id: DeclId, namespace: &[String], decl: &kql_ast::EnumDecl) {
+        let mut variants = Vec::new();
+        for (index, variant) in decl.variants.iter().enumerate() {
+            let value = match &variant.value {
+                None => index as i64,
+                Some(Expr { kind: ExprKind::Literal(Literal::Int(value, _)), .. }) => *value,
+                Some(expr) => {
+                    self.errors.push(KqlError::semantic("enum variant values must be integer literals", expr.span));
+                    index as i64
+                }
+            };
+            variants.push(HirVariant { name: variant.name.name.clone(), value, span: variant.span });
+        }
+        let attributes = self.lower_attributes(&decl.attributes);
+        let layout = self.enum_layout(&attributes);
+        let item = HirEnum {
+            id,
+            name: decl.name.name.clone(),
+            namespace: namespace.to_vec(),
+            full_name: qualify(namespace, &decl.name.name),
+            variants,
+            layout,
+            attributes,
+            docs: decl.docs.clone(),
+            span: decl.span,
+        };
+        self.program.enums.insert(id, item);
+    }
+
+    fn enum_layout(&mut self, attributes: &[HirAttribute]) -> EnumLayout {
+        let Some(attr) = attributes.iter().find(|a| a.name == "layout") else {
+            return EnumLayout::default();
+        };
+        match attr.first_arg().map(|e| &e.kind) {
+            Some(HirExprKind::Variable(name)) if name == "string" => EnumLayout::String,
+            Some(HirExprKind::Variable(name)) if name == "int" => EnumLayout::Int,
+            _ => {
+                self.errors.push(KqlError::semantic("expected `string` or `int` enum layout", attr.span));
+                EnumLayout::default()
+            }
+        }
+    }
+
+    fn lower_type_alias(&mut self, id: DeclId, namespace: &[String], decl: &kql_ast::TypeAliasDecl) -> Result<()> {
+        if self.program.type_aliases.contains_key(&id) {
+            return Ok(());
+        }
+        if !self.aliases_in_progress.insert(id) {
+            return Err(KqlError::semantic(format!("type alias `{}` refers to itself", decl.name.name), decl.span));
+        }
+        let ty = self.lower_type(&decl.ty, namespace);
+        self.aliases_in_progress.remove(&id);
+        let full_name = qualify(namespace, &decl.name.name);
+        self.program.type_aliases.insert(id, HirTypeAlias { id, full_name, ty, span: decl.span });
+        Ok(())
+    }
+
+    fn lower_let(&mut self, id: DeclId, namespace: &[String], decl: &kql_ast::LetDecl) {
+        let query = self.process_query_expr(&decl.value, namespace);
+        let full_name = qualify(namespace, &decl.name.name);
+        self.program.lets.insert(id, HirLet { id, full_name, query, span: decl.span });
+    }
+
+    /// Resolve a possibly qualified name, preferring the current namespace.
+    fn resolve_name(&self, name: &str, namespace: &[String]) -> Option<DeclId> {
+        if !namespace.is_empty() {
+            let qualified = qualify(namespace, name);
+            if let Some(id) = self.program.name_to_id.get(&qualified) {
+                return Some(*id);
+            }
+        }
+        self.program.name_to_id.get(name).copied()
+    }
+
+    fn lower_type(&mut self, ty: &Type, namespace: &[String]) -> HirType {
+        match &ty.kind {
+            TypeKind::List(inner) => HirType::List(Box::new(self.lower_type(inner, namespace))),
+            TypeKind::Optional(inner) => HirType::Optional(Box::new(self.lower_type(inner, namespace))),
+            TypeKind::Tuple(items) => HirType::Tuple(items.iter().map(|item| self.lower_type(item, namespace)).collect()),
+            TypeKind::Named { path, args } => self.lower_named_type(ty, path, args, namespace),
+        }
+    }
+
+    fn lower_named_type(&mut self, ty: &Type, path: &[kql_ast::Ident], args: &[TypeArg], namespace: &[String]) -> HirType {
+        let name = path.iter().map(|i| i.name.as_str()).collect::<Vec<_>>().join("::");
+        if path.len() == 1 && args.is_empty() {
+            if let Some(bound) = self.substitutions.last().and_then(|frame| frame.get(&name)) {
+                return bound.clone();
+            }
+        }
+        if name == "Key" {
+            return self.lower_key_type(ty, args, namespace);
+        }
+        if name == "ForeignKey" {
+            return self.lower_foreign_key_type(ty, args, namespace);
+        }
+        if let Some(id) = self.resolve_name(&name, namespace) {
+            self.record_deprecation(id, &name, ty.span);
+            return match self.program.id_to_kind[&id] {
+                HirDeclKind::Struct => match self.struct_generics(id) {
+                    generics if generics.is_empty() => HirType::Struct(id),
+                    generics => self.instantiate_struct(id, &generics, args, namespace, ty.span),
+                },
+                HirDeclKind::Enum => HirType::Enum(id),
+                HirDeclKind::TypeAlias => self.resolve_alias(id),
+                HirDeclKind::Let => {
+                    self.errors.push(KqlError::semantic(format!("`{}` is a query, not a type", name), ty.span));
+                    HirType::Unknown
+                }
+            };
+        }
+        if let Some(primitive) = PrimitiveType::from_name(&name) {
+            return HirType::Primitive(primitive);
+        }
+        self.errors.push(KqlError::semantic(format!("unknown type `{}`", name), ty.span));
+        HirType::Unknown
+    }
+
+    /// Lower a `seed` block, checking the row against the target struct.
+    fn lower_seed(&mut self, namespace: &[String], decl: &kql_ast::SeedDecl) {
+        let Some(id) = self.resolve_name(&decl.target.name, namespace) else {
+            self.errors.push(KqlError::semantic(format!("unknown struct `{}`", decl.target.name), decl.target.span));
+            return;
+        };
+        let Some(item) = self.program.structs.get(&id).cloned() else {
+            self.errors.push(KqlError::semantic(format!("`{}` is not a struct", decl.target.name), decl.target.span));
+            return;
+        };
+        let mut values = Vec::new();
+        for entry in &decl.values {
+            let Some(field) = item.field(&entry.name.name) else {
+                let message = format!("`{}` has no field `{}`", item.name, entry.name.name);
+                self.errors.push(KqlError::semantic(message, entry.name.span));
+                continue;
+            };
+            let value = self.lower_expr(&entry.value, None);
+            self.check_seed_value(field, &value);
+            values.push((entry.name.name.clone(), value));
+        }
+        self.program.seeds.push(HirSeed { struct_id: id, values, span: decl.span });
+    }
+
+    /// Check a seed value against the declared field type, looking through
+    /// `Option` and `Key` wrappers.
+    fn check_seed_value(&mut self, field: &HirField, value: &HirExpr) {
+        use PrimitiveType as P;
+        let mut expected = &field.ty;
+        while let HirType::Optional(inner) | HirType::Key { ty: inner, .. } = expected {
+            expected = inner;
+        }
+        let ok = match (&value.kind, expected) {
+            (HirExprKind::Literal(HirLiteral::Int(_)), HirType::Primitive(p)) => {
+                matches!(p, P::I8 | P::I16 | P::I32 | P::I64 | P::U8 | P::U16 | P::U32 | P::U64 | P::F32 | P::F64 | P::D128)
+            }
+            (HirExprKind::Literal(HirLiteral::Float(_)), HirType::Primitive(p)) => matches!(p, P::F32 | P::F64 | P::D128),
+            (HirExprKind::Literal(HirLiteral::String(_)), HirType::Primitive(p)) => {
+                matches!(p, P::String | P::DateTime | P::Date | P::Time | P::Uuid | P::Json)
+            }
+            (HirExprKind::Literal(HirLiteral::Bool(_)), HirType::Primitive(P::Bool)) => true,
+            (HirExprKind::Literal(_), HirType::ForeignKey { .. }) => true,
+            (HirExprKind::Variable(name), HirType::Enum(id)) => {
+                self.program.enums.get(id).is_some_and(|e| e.variant(name).is_some())
+            }
+            (_, HirType::Unknown) => true,
+            _ => false,
+        };
+        if !ok {
+            let message = format!("seed value for `{}` does not match its declared type", field.name);
+            self.errors.push(KqlError::semantic(message, value.span));
+        }
+    }
+
+    /// Record a use of `id` when its declaration is marked `@deprecated`.
+    fn record_deprecation(&mut self, id: DeclId, name: &str, span: Span) {
+        let Some((_, decl)) = self.ast_decls.get(&id) else {
+            return;
+        };
+        let attributes = match &**decl {
+            Decl::Struct(s) => &s.attributes,
+            Decl::Enum(e) => &e.attributes,
+            _ => return,
+        };
+        let Some(attr) = attributes.iter().find(|a| a.name.name == "deprecated") else {
+            return;
+        };
+        let note = match attr.first_arg().map(|e| &e.kind) {
+            Some(ExprKind::Literal(Literal::String(note))) => Some(note.clone()),
+            _ => None,
+        };
+        self.program.deprecations.push(HirDeprecation { symbol: name.to_string(), note, span });
+    }
+
+    fn lower_key_type(&mut self, ty: &Type, args: &[TypeArg], namespace: &[String]) -> HirType {
+        match args {
+            [TypeArg::Type(inner)] => HirType::Key { entity: None, ty: Box::new(self.lower_type(inner, namespace)) },
+            [TypeArg::Type(entity), TypeArg::Type(inner)] => {
+                let entity = self.expect_struct_ref(entity, namespace);
+                HirType::Key { entity, ty: Box::new(self.lower_type(inner, namespace)) }
+            }
+            _ => {
+                self.errors.push(KqlError::semantic("`Key` expects `Key<T>` or `Key<Entity, T>`", ty.span));
+                HirType::Unknown
+            }
+        }
+    }
+
+    fn lower_foreign_key_type(&mut self, ty: &Type, args: &[TypeArg], namespace: &[String]) -> HirType {
+        match args {
+            [TypeArg::Type(entity)] => match self.expect_struct_ref(entity, namespace) {
+                Some(entity) => HirType::ForeignKey { name: None, entity },
+                None => HirType::Unknown,
+            },
+            [TypeArg::Named { name, ty: entity }] => match self.expect_struct_ref(entity, namespace) {
+                Some(entity) => HirType::ForeignKey { name: Some(name.name.clone()), entity },
+                None => HirType::Unknown,
+            },
+            _ => {
+                self.errors.push(KqlError::semantic("`ForeignKey` expects a single entity argument", ty.span));
+                HirType::Unknown
+            }
+        }
+    }
+
+    /// Resolve a type argument that must name a struct, reporting an error otherwise.
+    fn expect_struct_ref(&mut self, ty: &Type, namespace: &[String]) -> Option<DeclId> {
+        if let TypeKind::Named { path, args } = &ty.kind {
+            if args.is_empty() {
+                let name = path.iter().map(|i| i.name.as_str()).collect::<Vec<_>>().join("::");
+                if let Some(id) = self.resolve_name(&name, namespace) {
+                    if self.program.id_to_kind[&id] == HirDeclKind::Struct {
+                        return Some(id);
+                    }
+                }
+            }
+        }
+        self.errors.push(KqlError::semantic("expected a struct name", ty.span));
+        None
+    }
+
+    fn resolve_alias(&mut self, id: DeclId) -> HirType {
+        if let Some(alias) = self.program.type_aliases.get(&id) {
+            return alias.ty.clone();
+        }
+        // Aliases are lowered lazily so that an alias used before its
+        // declaration still resolves.
+        let Some((namespace, decl)) = self.ast_decls.get(&id).map(|(ns, d)| (ns.clone(), Rc::clone(d))) else {
+            return HirType::Unknown;
+        };
+        let Decl::TypeAlias(decl) = &*decl else {
+            return HirType::Unknown;
+        };
+        match self.lower_type_alias(id, &namespace, decl) {
+            Ok(()) => self.program.type_aliases[&id].ty.clone(),
+            Err(error) => {
+                self.errors.push(error);
+                HirType::Unknown
+            }
+        }
+    }
+
+    fn lower_attributes(&mut self, attributes: &[Attribute]) -> Vec<HirAttribute> {
+        attributes
+            .iter()
+            .map(|attr| HirAttribute {
+                name: attr.name.name.clone(),
+                args: attr
+                    .args
+                    .iter()
+                    .map(|arg| HirAttrArg {
+                        name: arg.name.as_ref().map(|n| n.name.clone()),
+                        value: self.lower_attr_value(&arg.value),
+                    })
+                    .collect(),
+                span: attr.span,
+            })
+            .collect()
+    }
+
+    /// Lower an attribute argument structurally, without type checking.
+    fn lower_attr_value(&mut self, expr: &Expr) -> HirExpr {
+        let span = expr.span;
+        match &expr.kind {
+            ExprKind::Literal(lit) => self.lower_literal(lit, span),
+            ExprKind::Variable(ident) => {
+                HirExpr { kind: HirExprKind::Variable(ident.name.clone()), ty: HirType::Unknown, span }
+            }
+            ExprKind::Unary { op, expr } => {
+                let inner = self.lower_attr_value(expr);
+                HirExpr { kind: HirExprKind::Unary { op: *op, expr: Box::new(inner) }, ty: HirType::Unknown, span }
+            }
+            ExprKind::List(items) => {
+                let items = items.iter().map(|item| self.lower_attr_value(item)).collect();
+                HirExpr { kind: HirExprKind::List(items), ty: HirType::Unknown, span }
+            }
+            ExprKind::Call { func, args } => {
+                let args = args.iter().map(|arg| self.lower_attr_value(arg)).collect();
+                HirExpr { kind: HirExprKind::Call { func: func.name.clone(), args }, ty: HirType::Unknown, span }
+            }
+            ExprKind::MethodCall { base, method, args, .. } => {
+                let mut call_args = vec![self.lower_attr_value(base)];
+                call_args.extend(args.iter().map(|arg| self.lower_attr_value(arg)));
+                HirExpr { kind: HirExprKind::Call { func: method.name.clone(), args: call_args }, ty: HirType::Unknown, span }
+            }
+            _ => {
+                self.errors.push(KqlError::semantic("unsupported attribute argument", span));
+                HirExpr { kind: HirExprKind::Variable(String::new()), ty: HirType::Unknown, span }
+            }
+        }
+    }
+
+    fn process_query_expr(&mut self, expr: &Expr, namespace: &[String]) -> HirQuery {
+        let mut chain = Vec::new();
+        let mut head = expr;
+        while let ExprKind::MethodCall { base, method, args, closure } = &head.kind {
+            chain.push((method, args, closure, head.span));
+            head = base;
+        }
+        chain.reverse();
+        let source = match &head.kind {
+            ExprKind::Variable(ident) => {
+                let id =
+                    self.resolve_name(&ident.name, namespace).filter(|id| self.program.id_to_kind[id] == HirDeclKind::Struct);
+                if id.is_none() {
+                    let message = format!("`{}` is not a known struct; a query must start with one", ident.name);
+                    self.errors.push(KqlError::semantic(message, head.span));
+                }
+                id
+            }
+            _ => {
+                self.errors.push(KqlError::semantic("a query must start with a struct name", head.span));
+                None
+            }
+        };
+        let mut query = HirQuery { source, ops: Vec::new() };
+        for (method, args, closure, span) in chain {
+            match method.name.as_str() {
+                "filter" => match closure {
+                    Some(body) => {
+                        let predicate = self.lower_expr(body, source);
+                        query.ops.push(HirQueryOp::Filter(predicate));
+                    }
+                    None => self.errors.push(KqlError::semantic("`filter` expects a `{ predicate }` closure", span)),
+                },
+                "sort" => match closure {
+                    Some(body) => {
+                        if let Some(key) = self.lower_sort_key(body, source) {
+                            query.ops.push(HirQueryOp::Sort(vec![key]));
+                        }
+                    }
+                    None => self.errors.push(KqlError::semantic("`sort` expects a `{ key }` closure", span)),
+                },
+                "map" => match closure {
+                    Some(body) => {
+                        let projections = self.lower_projections(body, source);
+                        if !projections.is_empty() {
+                            query.ops.push(HirQueryOp::Map(projections));
+                        }
+                    }
+                    None => self.errors.push(KqlError::semantic("`map` expects a `{ projection }` closure", span)),
+                },
+                "limit" | "offset" => {
+                    let value = match args.first().map(|a| &a.kind) {
+                        Some(ExprKind::Literal(Literal::Int(value, _))) if *value >= 0 => *value as u64,
+                        _ => {
+                            self.errors
+                                .push(KqlError::semantic(format!("`{}` expects a non-negative integer", method.name), span));
+                            0
+                        }
+                    };
+                    let op = if method.name == "limit" { HirQueryOp::Limit(value) } else { HirQueryOp::Offset(value) };
+                    query.ops.push(op);
+                }
+                other => self.errors.push(KqlError::semantic(format!("unknown query method `{}`", other), method.span)),
+            }
+        }
+        query
+    }
+
+    fn lower_sort_key(&mut self, expr: &Expr, source: Option<DeclId>) -> Option<HirSortKey> {
+        let span = expr.span;
+        // Peel a trailing `.nulls_first()` / `.nulls_last()` before the
+        // direction, so `$.field.desc().nulls_last()` parses as one key.
+        let mut expr = expr;
+        let mut nulls = None;
+        if let ExprKind::MethodCall { base, method, .. } = &expr.kind {
+            match method.name.as_str() {
+                "nulls_first" => {
+                    nulls = Some(NullsOrder::First);
+                    expr = base;
+                }
+                "nulls_last" => {
+                    nulls = Some(NullsOrder::Last);
+                    expr = base;
+                }
+                _ => {}
+            }
+        }
+        match &expr.kind {
+            ExprKind::MethodCall { base, method, .. } if method.name == "asc" || method.name == "desc" => {
+                let field = self.context_field_name(base, source)?;
+                Some(HirSortKey { field, desc: method.name == "desc", nulls, span })
+            }
+            _ => {
+                let field = self.context_field_name(expr, source)?;
+                Some(HirSortKey { field, desc: false, nulls, span })
+            }
+        }
+    }
+
+    /// Lower a `.map { ... }` body: a `$.{ alias: field, ... }` record
+    /// projects several renamed fields, anything else a single plain one.
+    fn lower_projections(&mut self, expr: &Expr, source: Option<DeclId>) -> Vec<HirProjection> {
+        if let ExprKind::Record(entries) = &expr.kind {
+            return entries
+                .iter()
+                .filter_map(|entry| {
+                    let field = self.record_field_name(&entry.value, source)?;
+                    Some(HirProjection { field, alias: Some(entry.name.name.clone()), span: entry.value.span })
+                })
+                .collect();
+        }
+        self.lower_projection(expr, source).into_iter().collect()
+    }
+
+    fn lower_projection(&mut self, expr: &Expr, source: Option<DeclId>) -> Option<HirProjection> {
+        let field = self.context_field_name(expr, source)?;
+        Some(HirProjection { field, alias: None, span: expr.span })
+    }
+
+    /// Extract the source field of one record projection entry: inside
+    /// `$.{ ... }` the row is implied, so a bare `id` and `$.id` both work.
+    fn record_field_name(&mut self, expr: &Expr, source: Option<DeclId>) -> Option<String> {
+        if let ExprKind::Variable(name) = &expr.kind {
+            if let Some(id) = source {
+                if self.program.structs[&id].field(&name.name).is_none() {
+                    let struct_name = self.program.structs[&id].name.clone();
+                    self.errors
+                        .push(KqlError::semantic(format!("no field `{}` on struct `{}`", name.name, struct_name), name.span));
+                    return None;
+                }
+            }
+            return Some(name.name.clone());
+        }
+        self.context_field_name(expr, source)
+    }
+
+    /// Extract the field name from a `$.field` expression, checking it exists on the source struct.
+    fn context_field_name(&mut self, expr: &Expr, source: Option<DeclId>) -> Option<String> {
+        match &expr.kind {
+            ExprKind::Member { base, name } if base.kind == ExprKind::Context => {
+                if let Some(id) = source {
+                    if self.program.structs[&id].field(&name.name).is_none() {
+                        let struct_name = self.program.structs[&id].name.clone();
+                        self.errors.push(KqlError::semantic(
+                            format!("no field `{}` on struct `{}`", name.name, struct_name),
+                            name.span,
+                        ));
+                        return None;
+                    }
+                }
+                Some(name.name.clone())
+            }
+            _ => {
+                self.errors.push(KqlError::semantic("expected a `$.field` expression", expr.span));
+                None
+            }
+        }
+    }
+
+    fn lower_expr(&mut self, expr: &Expr, context: Option<DeclId>) -> HirExpr {
+        let span = expr.span;
+        match &expr.kind {
+            ExprKind::Literal(lit) => self.lower_literal(lit, span),
+            ExprKind::Context => {
+                let ty = match context {
+                    Some(id) => HirType::Struct(id),
+                    None => HirType::Unknown,
+                };
+                HirExpr { kind: HirExprKind::Context, ty, span }
+            }
+            ExprKind::Variable(ident) => {
+                HirExpr { kind: HirExprKind::Variable(ident.name.clone()), ty: HirType::Unknown, span }
+            }
+            ExprKind::Member { base, name } if base.kind == ExprKind::Context => {
+                let ty = self.context_field_type(name, context);
+                HirExpr { kind: HirExprKind::ContextField(name.name.clone()), ty, span }
+            }
+            ExprKind::Member { base, name } => {
+                let base = self.lower_expr(base, context);
+                HirExpr {
+                    kind: HirExprKind::Member { base: Box::new(base), name: name.name.clone() },
+                    ty: HirType::Unknown,
+                    span,
+                }
+            }
+            ExprKind::Unary { op, expr } => {
+                let inner = self.lower_expr(expr, context);
+                let ty = self.check_unary_op(*op, &inner, span);
+                HirExpr { kind: HirExprKind::Unary { op: *op, expr: Box::new(inner) }, ty, span }
+            }
+            ExprKind::Binary { op, lhs, rhs } => {
+                let lhs = self.lower_expr(lhs, context);
+                let rhs = self.lower_expr(rhs, context);
+                let ty = self.check_binary_op(*op, &lhs, &rhs, span);
+                HirExpr { kind: HirExprKind::Binary { op: *op, lhs: Box::new(lhs), rhs: Box::new(rhs) }, ty, span }
+            }
+            ExprKind::Call { func, args } => {
+                let args: Vec<_> = args.iter().map(|arg| self.lower_expr(arg, context)).collect();
+                let ty = self.check_builtin_function(&func.name, &args, span);
+                HirExpr { kind: HirExprKind::Call { func: func.name.clone(), args }, ty, span }
+            }
+            ExprKind::MethodCall { base, method, args, closure } => {
+                if closure.is_some() {
+                    self.errors.push(KqlError::semantic("closures are only valid on query operators", span));
+                }
+                let mut call_args = vec![self.lower_expr(base, context)];
+                call_args.extend(args.iter().map(|arg| self.lower_expr(arg, context)));
+                let ty = self.check_builtin_function(&method.name, &call_args, span);
+                HirExpr { kind: HirExprKind::Call { func: method.name.clone(), args: call_args }, ty, span }
+            }
+            ExprKind::If { cond, then_branch, else_branch } => {
+                let cond = self.lower_expr(cond, context);
+                if !matches!(cond.ty, HirType::Primitive(PrimitiveType::Bool) | HirType::Unknown) {
+                    self.errors.push(KqlError::semantic("`if` requires a boolean condition", cond.span));
+                }
+                let then_branch = self.lower_expr(then_branch, context);
+                let else_branch = self.lower_expr(else_branch, context);
+                let ty = if comparable(&then_branch.ty, &else_branch.ty) {
+                    if then_branch.ty == HirType::Unknown { else_branch.ty.clone() } else { then_branch.ty.clone() }
+                } else {
+                    self.errors.push(KqlError::semantic("`if` branches must have the same type", span));
+                    HirType::Unknown
+                };
+                HirExpr {
+                    kind: HirExprKind::If {
+                        cond: Box::new(cond),
+                        then_branch: Box::new(then_branch),
+                        else_branch: Box::new(else_branch),
+                    },
+                    ty,
+                    span,
+                }
+            }
+            ExprKind::List(items) => {
+                let items: Vec<_> = items.iter().map(|item| self.lower_expr(item, context)).collect();
+                // The element type is the first resolved one; every other
+                // element must be promotable to it, as in a comparison.
+                let mut elem_ty = HirType::Unknown;
+                for item in &items {
+                    if elem_ty == HirType::Unknown {
+                        elem_ty = item.ty.clone();
+                    } else if !comparable(&elem_ty, &item.ty) {
+                        self.errors.push(KqlError::semantic("list elements must all have the same type", item.span));
+                        break;
+                    }
+                }
+                HirExpr { kind: HirExprKind::List(items), ty: HirType::List(Box::new(elem_ty)), span }
+            }
+            ExprKind::Tuple(items) => {
+                let items: Vec<_> = items.iter().map(|item| self.lower_expr(item, context)).collect();
+                let ty = HirType::Tuple(items.iter().map(|item| item.ty.clone()).collect());
+                HirExpr { kind: HirExprKind::Tuple(items), ty, span }
+            }
+            ExprKind::Record(_) => {
+                self.errors.push(KqlError::semantic("a `$.{ ... }` projection is only valid in `.map { ... }`", span));
+                HirExpr { kind: HirExprKind::Tuple(Vec::new()), ty: HirType::Unknown, span }
+            }
+        }
+    }
+
+    fn context_field_type(&mut self, name: &kql_ast::Ident, context: Option<DeclId>) -> HirType {
+        let Some(id) = context else {
+            return HirType::Unknown;
+        };
+        let item = &self.program.structs[&id];
+        match item.field(&name.name) {
+            Some(field) => {
+                let ty = column_type(&field.ty);
+                if ty == HirType::Unknown && !self.errored_spans.contains(&field.span) {
+                    let message = format!("cannot use field `{}` whose type failed to resolve", name.name);
+                    self.errors.push(KqlError::semantic(message, name.span));
+                }
+                ty
+            }
+            None => {
+                let message = format!("no field `{}` on struct `{}`", name.name, item.name);
+                self.errors.push(KqlError::semantic(message, name.span));
+                HirType::Unknown
+            }
+        }
+    }
+
+    fn check_unary_op(&mut self, op: UnaryOpKind, operand: &HirExpr, span: Span) -> HirType {
+        let numeric = operand.ty.is_numeric();
+        match op {
+            UnaryOpKind::Neg if numeric || operand.ty == HirType::Unknown => operand.ty.clone(),
+            UnaryOpKind::Neg => {
+                self.errors.push(KqlError::semantic("`-` requires a numeric operand", span));
+                HirType::Unknown
+            }
+            UnaryOpKind::Not
+                if matches!(operand.ty, HirType::Primitive(PrimitiveType::Bool)) || operand.ty == HirType::Unknown =>
+            {
+                HirType::Primitive(PrimitiveType::Bool)
+            }
+            UnaryOpKind::Not => {
+                self.errors.push(KqlError::semantic("`!` requires a boolean operand", span));
+                HirType::Unknown
+            }
+        }
+    }
+
+    fn check_binary_op(&mut self, op: BinaryOpKind, lhs: &HirExpr, rhs: &HirExpr, span: Span) -> HirType {
+        use BinaryOpKind::*;
+        let numeric = |ty: &HirType| ty.is_numeric() || *ty == HirType::Unknown;
+        match op {
+            Add | Sub | Mul | Div | Mod => {
+                if numeric(&lhs.ty) && numeric(&rhs.ty) {
+                    lhs.ty.clone()
+                } else {
+                    self.errors.push(KqlError::semantic(format!("`{:?}` requires numeric operands", op), span));
+                    HirType::Unknown
+                }
+            }
+            Eq | NotEq | Lt | Le | Gt | Ge => {
+                if !comparable(&lhs.ty, &rhs.ty) {
+                    self.errors.push(KqlError::semantic("cannot compare values of different types", span));
+                }
+                HirType::Primitive(PrimitiveType::Bool)
+            }
+            And | Or => {
+                for side in [lhs, rhs] {
+                    if !matches!(side.ty, HirType::Primitive(PrimitiveType::Bool) | HirType::Unknown) {
+                        self.errors.push(KqlError::semantic("logical operators require boolean operands", side.span));
+                    }
+                }
+                HirType::Primitive(PrimitiveType::Bool)
+            }
+        }
+    }
+
+    fn check_builtin_function(&mut self, name: &str, args: &[HirExpr], span: Span) -> HirType {
+        let numeric = |ty: &HirType| ty.is_numeric();
+        let stringy = |ty: &HirType| ty.is_string() || *ty == HirType::Unknown;
+        match name {
+            "count" => {
+                // `count(*)` and `count(field)` are both fine; counting does
+                // not care about the argument's type.
+                if args.len() != 1 {
+                    self.errors.push(KqlError::semantic("`count` takes exactly one argument, `*` or a field", span));
+                }
+                HirType::Primitive(PrimitiveType::I64)
+            }
+            "sum" | "min" | "max" | "avg" => match args.first() {
+                Some(arg) if matches!(arg.kind, HirExprKind::Literal(HirLiteral::Star)) => {
+                    self.errors.push(KqlError::semantic(format!("`{}` cannot aggregate `*`; name a field", name), span));
+                    HirType::Unknown
+                }
+                Some(arg) if numeric(&arg.ty) => {
+                    if name == "avg" {
+                        HirType::Primitive(PrimitiveType::F64)
+                    } else {
+                        arg.ty.clone()
+                    }
+                }
+                _ => {
+                    self.errors.push(KqlError::semantic(format!("`{}` requires a numeric argument", name), span));
+                    HirType::Unknown
+                }
+            },
+            "coalesce" | "ifnull" => {
+                if args.len() < 2 {
+                    self.errors.push(KqlError::semantic(format!("`{}` requires at least two arguments", name), span));
+                }
+                args.first().map(|a| unwrap_optional(&a.ty)).unwrap_or(HirType::Unknown)
+            }
+            "nullif" => {
+                if args.len() != 2 {
+                    self.errors.push(KqlError::semantic("`nullif` requires exactly two arguments", span));
+                }
+                args.first().map(|a| HirType::Optional(Box::new(unwrap_optional(&a.ty)))).unwrap_or(HirType::Unknown)
+            }
+            "lower" | "upper" => match args.first() {
+                Some(arg) if stringy(&arg.ty) => HirType::Primitive(PrimitiveType::String),
+                _ => {
+                    self.errors.push(KqlError::semantic(format!("`{}` requires a string argument", name), span));
+                    HirType::Unknown
+                }
+            },
+            "length" => match args.first() {
+                Some(arg) if stringy(&arg.ty) => HirType::Primitive(PrimitiveType::I64),
+                _ => {
+                    self.errors.push(KqlError::semantic("`length` requires a string argument", span));
+                    HirType::Unknown
+                }
+            },
+            "now" => HirType::Primitive(PrimitiveType::DateTime),
+            "uuid" => HirType::Primitive(PrimitiveType::Uuid),
+            other => {
+                self.errors.push(KqlError::semantic(format!("unknown function `{}`", other), span));
+                HirType::Unknown
+            }
+        }
+    }
+
+    fn lower_literal(&mut self, lit: &Literal, span: Span) -> HirExpr {
+        let (kind, ty) = match lit {
+            Literal::Int(value, suffix) => {
+                let ty = match suffix {
+                    None => HirType::Primitive(PrimitiveType::I32),
+                    Some(suffix) => self.suffix_type(suffix, false, span),
+                };
+                (HirLiteral::Int(*value), ty)
+            }
+            Literal::Float(value, suffix) => {
+                let ty = match suffix {
+                    None => HirType::Primitive(PrimitiveType::F32),
+                    Some(suffix) => self.suffix_type(suffix, true, span),
+                };
+                (HirLiteral::Float(*value), ty)
+            }
+            Literal::String(value) => (HirLiteral::String(value.clone()), HirType::Primitive(PrimitiveType::String)),
+            Literal::Bool(value) => (HirLiteral::Bool(*value), HirType::Primitive(PrimitiveType::Bool)),
+            Literal::Star => (HirLiteral::Star, HirType::Unknown),
+        };
+        HirExpr { kind: HirExprKind::Literal(kind), ty, span }
+    }
+
+    /// Resolve a numeric literal suffix like `i64` to its primitive type. An
+    /// integer literal accepts any numeric suffix (`10f64` is a float), but a
+    /// float literal only accepts floating point suffixes.
+    fn suffix_type(&mut self, suffix: &str, float: bool, span: Span) -> HirType {
+        use PrimitiveType as P;
+        let primitive = match PrimitiveType::from_name(suffix) {
+            Some(p @ (P::F32 | P::F64 | P::D128)) => Some(p),
+            Some(p @ (P::I8 | P::I16 | P::I32 | P::I64 | P::U8 | P::U16 | P::U32 | P::U64)) if !float => Some(p),
+            _ => None,
+        };
+        match primitive {
+            Some(p) => HirType::Primitive(p),
+            None => {
+                self.errors.push(KqlError::semantic(format!("`{}` is not a valid numeric literal suffix", suffix), span));
+                HirType::Unknown
+            }
+        }
+    }
+}
+
+fn qualify(namespace: &[String], name: &str) -> String {
+    if namespace.is_empty() { name.to_string() } else { format!("{}::{}", namespace.join("::"), name) }
+}
+
+/// The strongly connected components of a relation graph, via Kosaraju's two
+/// depth-first passes. Components come out in a deterministic order so
+/// diagnostics derived from them are stable.
+fn strongly_connected(edges: &IndexMap<DeclId, Vec<DeclId>>) -> Vec<Vec<DeclId>> {
+    fn visit(node: DeclId, edges: &IndexMap<DeclId, Vec<DeclId>>, seen: &mut HashSet<DeclId>, finish: &mut Vec<DeclId>) {
+        if !seen.insert(node) {
+            return;
+        }
+        for &next in edges.get(&node).into_iter().flatten() {
+            visit(next, edges, seen, finish);
+        }
+        finish.push(node);
+    }
+    let mut reversed: IndexMap<DeclId, Vec<DeclId>> = IndexMap::new();
+    for (&from, targets) in edges {
+        for &to in targets {
+            reversed.entry(to).or_default().push(from);
+        }
+    }
+    let mut seen = HashSet::new();
+    let mut finish = Vec::new();
+    for &node in edges.keys() {
+        visit(node, edges, &mut seen, &mut finish);
+    }
+    let mut assigned = HashSet::new();
+    let mut components = Vec::new();
+    for &node in finish.iter().rev() {
+        if assigned.contains(&node) {
+            continue;
+        }
+        let mut component = Vec::new();
+        let mut stack = vec![node];
+        while let Some(next) = stack.pop() {
+            if !assigned.insert(next) {
+                continue;
+            }
+            component.push(next);
+            stack.extend(reversed.get(&next).into_iter().flatten());
+        }
+        components.push(component);
+    }
+    components
+}
+
+/// Built-in type constructors that are not primitives but are still matched
+/// by name during type resolution.
+const RESERVED_TYPE_NAMES: &[&str] = &["Key", "ForeignKey", "List", "Option"];
+
+fn comparable(lhs: &HirType, rhs: &HirType) -> bool {
+    if *lhs == HirType::Unknown || *rhs == HirType::Unknown {
+        return true;
+    }
+    column_base(lhs) == column_base(rhs)
+}
+
+fn column_base(ty: &HirType) -> HirType {
+    let ty = unwrap_optional(ty);
+    match ty {
+        HirType::Primitive(p) if p.is_numeric() => HirType::Primitive(PrimitiveType::F64),
+        other => other,
+    }
+}
+
+fn unwrap_optional(ty: &HirType) -> HirType {
+    match ty {
+        HirType::Optional(inner) => unwrap_optional(inner),
+        other => other.clone(),
+    }
+}
+
+/// The type a field has when referenced as a column in a query.
+pub fn column_type(ty: &HirType) -> HirType {
+    match ty {
+        HirType::Key { ty, .. } => column_type(ty),
+        HirType::ForeignKey { .. } => HirType::Primitive(PrimitiveType::I64),
+        other => other.clone(),
+    }
+}
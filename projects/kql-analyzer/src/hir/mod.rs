@@ -0,0 +1,610 @@
+//! The high-level intermediate representation: a name-resolved, type-checked
+//! view of a parsed schema, produced by [lower::Lowerer].
+
+pub mod lower;
+pub mod visit;
+
+pub use kql_ast::{BinaryOpKind, UnaryOpKind};
+use kql_types::{Diagnostic, KqlError, Span};
+
+use indexmap::IndexMap;
+
+/// A unique id assigned to every named declaration during lowering.
+pub type DeclId = usize;
+
+/// A complete lowered program.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HirProgram {
+    /// Fully qualified declaration name to id.
+    pub name_to_id: IndexMap<String, DeclId>,
+    /// Declaration id to the kind of declaration it names.
+    pub id_to_kind: IndexMap<DeclId, HirDeclKind>,
+    /// All struct declarations.
+    pub structs: IndexMap<DeclId, HirStruct>,
+    /// All enum declarations.
+    pub enums: IndexMap<DeclId, HirEnum>,
+    /// All type alias declarations, resolved to their target type.
+    pub type_aliases: IndexMap<DeclId, HirTypeAlias>,
+    /// All query declarations.
+    pub lets: IndexMap<DeclId, HirLet>,
+    /// Every use of a `@deprecated` symbol found while lowering.
+    pub deprecations: Vec<HirDeprecation>,
+    /// Non-blocking findings, such as a `Key<Entity, T>` whose entity is not
+    /// the declaring struct.
+    pub warnings: Vec<Diagnostic>,
+    /// Fixture rows declared with `seed`, in source order.
+    pub seeds: Vec<HirSeed>,
+}
+
+impl HirProgram {
+    /// Look up a struct by its fully qualified name.
+    pub fn struct_by_name(&self, name: &str) -> Option<&HirStruct> {
+        self.structs.get(self.name_to_id.get(name)?)
+    }
+    /// Look up an enum by its fully qualified name.
+    pub fn enum_by_name(&self, name: &str) -> Option<&HirEnum> {
+        self.enums.get(self.name_to_id.get(name)?)
+    }
+
+    /// Check the internal invariants lowering is supposed to uphold: every
+    /// [Self::name_to_id] entry has a kind and lives in the matching map, and
+    /// every type that references a declaration points at one that exists.
+    ///
+    /// A program produced by [lower::Lowerer] always passes; a non-empty
+    /// result indicates a lowering bug, not a problem with the source.
+    pub fn validate(&self) -> Vec<KqlError> {
+        let mut errors = Vec::new();
+        for (name, id) in &self.name_to_id {
+            let Some(kind) = self.id_to_kind.get(id) else {
+                errors.push(KqlError::semantic(format!("`{}` has an id with no declaration kind", name), Span::default()));
+                continue;
+            };
+            let present = match kind {
+                HirDeclKind::Struct => self.structs.contains_key(id),
+                HirDeclKind::Enum => self.enums.contains_key(id),
+                HirDeclKind::TypeAlias => self.type_aliases.contains_key(id),
+                HirDeclKind::Let => self.lets.contains_key(id),
+            };
+            if !present {
+                errors.push(KqlError::semantic(
+                    format!("`{}` is registered as a {:?} but is missing from that map", name, kind),
+                    Span::default(),
+                ));
+            }
+        }
+        for strukt in self.structs.values() {
+            for field in &strukt.fields {
+                let owner = format!("{}.{}", strukt.full_name, field.name);
+                self.validate_type(&field.ty, &owner, field.span, &mut errors);
+            }
+        }
+        for alias in self.type_aliases.values() {
+            self.validate_type(&alias.ty, &alias.full_name, alias.span, &mut errors);
+        }
+        errors
+    }
+
+    fn validate_type(&self, ty: &HirType, owner: &str, span: Span, errors: &mut Vec<KqlError>) {
+        match ty {
+            HirType::Struct(id) => {
+                if !self.structs.contains_key(id) {
+                    errors.push(KqlError::semantic(format!("`{}` references a struct that does not exist", owner), span));
+                }
+            }
+            HirType::Enum(id) => {
+                if !self.enums.contains_key(id) {
+                    errors.push(KqlError::semantic(format!("`{}` references an enum that does not exist", owner), span));
+                }
+            }
+            HirType::ForeignKey { entity, .. } => {
+                if !self.structs.contains_key(entity) {
+                    errors.push(KqlError::semantic(
+                        format!("`{}` is a foreign key to a struct that does not exist", owner),
+                        span,
+                    ));
+                }
+            }
+            HirType::Key { entity, ty } => {
+                if let Some(entity) = entity {
+                    if !self.structs.contains_key(entity) {
+                        errors.push(KqlError::semantic(format!("`{}` is a key of a struct that does not exist", owner), span));
+                    }
+                }
+                self.validate_type(ty, owner, span, errors);
+            }
+            HirType::List(inner) | HirType::Optional(inner) => self.validate_type(inner, owner, span, errors),
+            HirType::Tuple(items) => {
+                for item in items {
+                    self.validate_type(item, owner, span, errors);
+                }
+            }
+            HirType::Primitive(_) | HirType::Unknown => {}
+        }
+    }
+}
+
+/// A lowered `seed` declaration: one checked fixture row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HirSeed {
+    /// The struct the row belongs to.
+    pub struct_id: DeclId,
+    /// `(field name, value)` pairs in source order.
+    pub values: Vec<(String, HirExpr)>,
+    /// Span of the declaration.
+    pub span: Span,
+}
+
+/// A recorded use of a symbol whose declaration carries `@deprecated`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HirDeprecation {
+    /// The name of the deprecated symbol as written at the use site.
+    pub symbol: String,
+    /// The note given as `@deprecated("...")`, if any.
+    pub note: Option<String>,
+    /// Span of the use, not of the declaration.
+    pub span: Span,
+}
+
+/// Which kind of declaration an id refers to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HirDeclKind {
+    /// A struct declaration.
+    Struct,
+    /// An enum declaration.
+    Enum,
+    /// A type alias declaration.
+    TypeAlias,
+    /// A query declaration.
+    Let,
+}
+
+/// A lowered struct declaration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HirStruct {
+    /// The id assigned to this declaration.
+    pub id: DeclId,
+    /// The unqualified name.
+    pub name: String,
+    /// The namespace path this struct was declared in.
+    pub namespace: Vec<String>,
+    /// The fully qualified `::`-joined name.
+    pub full_name: String,
+    /// The lowered fields in source order.
+    pub fields: Vec<HirField>,
+    /// The lowered attributes.
+    pub attributes: Vec<HirAttribute>,
+    /// `///` doc comments.
+    pub docs: Vec<String>,
+    /// Span of the declaration.
+    pub span: Span,
+}
+
+impl HirStruct {
+    /// Look up a field by name.
+    pub fn field(&self, name: &str) -> Option<&HirField> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+    /// Look up an attribute by name.
+    pub fn attribute(&self, name: &str) -> Option<&HirAttribute> {
+        self.attributes.iter().find(|a| a.name == name)
+    }
+}
+
+/// A lowered struct field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HirField {
+    /// The field name.
+    pub name: String,
+    /// The lowered type.
+    pub ty: HirType,
+    /// The lowered attributes.
+    pub attributes: Vec<HirAttribute>,
+    /// `///` doc comments.
+    pub docs: Vec<String>,
+    /// Span of the field.
+    pub span: Span,
+}
+
+impl HirField {
+    /// Look up an attribute by name.
+    pub fn attribute(&self, name: &str) -> Option<&HirAttribute> {
+        self.attributes.iter().find(|a| a.name == name)
+    }
+    /// Whether an attribute with the given name is present.
+    pub fn has_attribute(&self, name: &str) -> bool {
+        self.attribute(name).is_some()
+    }
+}
+
+/// A lowered enum declaration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HirEnum {
+    /// The id assigned to this declaration.
+    pub id: DeclId,
+    /// The unqualified name.
+    pub name: String,
+    /// The namespace path this enum was declared in.
+    pub namespace: Vec<String>,
+    /// The fully qualified `::`-joined name.
+    pub full_name: String,
+    /// The variants in source order.
+    pub variants: Vec<HirVariant>,
+    /// How values of this enum are stored in a column.
+    pub layout: EnumLayout,
+    /// The lowered attributes.
+    pub attributes: Vec<HirAttribute>,
+    /// `///` doc comments.
+    pub docs: Vec<String>,
+    /// Span of the declaration.
+    pub span: Span,
+}
+
+impl HirEnum {
+    /// Look up a variant by name.
+    pub fn variant(&self, name: &str) -> Option<&HirVariant> {
+        self.variants.iter().find(|v| v.name == name)
+    }
+}
+
+/// A lowered enum variant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HirVariant {
+    /// The variant name.
+    pub name: String,
+    /// The stored integer value, explicit or assigned by position.
+    pub value: i64,
+    /// Span of the variant.
+    pub span: Span,
+}
+
+/// How an enum is stored in the database.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum EnumLayout {
+    /// Stored as the variant name, using a native enum type where available.
+    #[default]
+    String,
+    /// Stored as the variant's integer value.
+    Int,
+}
+
+/// A lowered type alias.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HirTypeAlias {
+    /// The id assigned to this declaration.
+    pub id: DeclId,
+    /// The fully qualified `::`-joined name.
+    pub full_name: String,
+    /// The resolved target type.
+    pub ty: HirType,
+    /// Span of the declaration.
+    pub span: Span,
+}
+
+/// A lowered query declaration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HirLet {
+    /// The id assigned to this declaration.
+    pub id: DeclId,
+    /// The fully qualified `::`-joined name.
+    pub full_name: String,
+    /// The processed query chain.
+    pub query: HirQuery,
+    /// Span of the declaration.
+    pub span: Span,
+}
+
+/// A processed query chain: a source table and a sequence of operators.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HirQuery {
+    /// The struct the chain starts from, if it resolved.
+    pub source: Option<DeclId>,
+    /// The operators in application order.
+    pub ops: Vec<HirQueryOp>,
+}
+
+/// A single query operator.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HirQueryOp {
+    /// `.filter { predicate }`
+    Filter(HirExpr),
+    /// `.sort { keys }`
+    Sort(Vec<HirSortKey>),
+    /// `.map { projection }`
+    Map(Vec<HirProjection>),
+    /// `.limit(n)`
+    Limit(u64),
+    /// `.offset(n)`
+    Offset(u64),
+}
+
+/// A single sort key inside `.sort { ... }`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HirSortKey {
+    /// The field to sort by.
+    pub field: String,
+    /// Whether to sort descending.
+    pub desc: bool,
+    /// Where NULLs sort, from `.nulls_first()` / `.nulls_last()`.
+    pub nulls: Option<NullsOrder>,
+    /// Span of the key expression.
+    pub span: Span,
+}
+
+/// An explicit NULL placement on a sort key.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NullsOrder {
+    /// `NULLS FIRST`
+    First,
+    /// `NULLS LAST`
+    Last,
+}
+
+/// A single projected field inside `.map { ... }`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HirProjection {
+    /// The field to project.
+    pub field: String,
+    /// The output name from a `$.{ alias: field }` projection, if renamed.
+    pub alias: Option<String>,
+    /// Span of the projection expression.
+    pub span: Span,
+}
+
+/// A lowered attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HirAttribute {
+    /// The attribute name, without the leading `@`.
+    pub name: String,
+    /// The lowered arguments.
+    pub args: Vec<HirAttrArg>,
+    /// Span of the attribute.
+    pub span: Span,
+}
+
+impl HirAttribute {
+    /// The first positional argument, if any.
+    pub fn first_arg(&self) -> Option<&HirExpr> {
+        self.args.first().map(|a| &a.value)
+    }
+    /// The value of the named argument `name`, if present.
+    pub fn named_arg(&self, name: &str) -> Option<&HirExpr> {
+        self.args.iter().find(|a| a.name.as_deref() == Some(name)).map(|a| &a.value)
+    }
+}
+
+/// A lowered attribute argument.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HirAttrArg {
+    /// The argument name for named arguments.
+    pub name: Option<String>,
+    /// The argument value.
+    pub value: HirExpr,
+}
+
+/// A lowered, typed expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HirExpr {
+    /// Which form of expression this is.
+    pub kind: HirExprKind,
+    /// The inferred type.
+    pub ty: HirType,
+    /// Span of the expression.
+    pub span: Span,
+}
+
+/// The shape of a [HirExpr].
+#[derive(Debug, Clone, PartialEq)]
+pub enum HirExprKind {
+    /// A literal value.
+    Literal(HirLiteral),
+    /// An unresolved symbol, e.g. an enum variant or column name in an attribute.
+    Variable(String),
+    /// The `$` row variable inside a query closure.
+    Context,
+    /// An access to a field of the context struct, `$.name`.
+    ContextField(String),
+    /// A member access that is not a context field.
+    Member {
+        /// The expression before the dot.
+        base: Box<HirExpr>,
+        /// The member name.
+        name: String,
+    },
+    /// A prefix unary operation.
+    Unary {
+        /// The operator.
+        op: UnaryOpKind,
+        /// The operand.
+        expr: Box<HirExpr>,
+    },
+    /// An infix binary operation.
+    Binary {
+        /// The operator.
+        op: BinaryOpKind,
+        /// Left operand.
+        lhs: Box<HirExpr>,
+        /// Right operand.
+        rhs: Box<HirExpr>,
+    },
+    /// A function call.
+    Call {
+        /// The function name.
+        func: String,
+        /// The arguments.
+        args: Vec<HirExpr>,
+    },
+    /// A conditional `if cond then a else b`.
+    If {
+        /// The condition.
+        cond: Box<HirExpr>,
+        /// The value when the condition holds.
+        then_branch: Box<HirExpr>,
+        /// The value otherwise.
+        else_branch: Box<HirExpr>,
+    },
+    /// A list literal.
+    List(Vec<HirExpr>),
+    /// A tuple literal `(a, b)`.
+    Tuple(Vec<HirExpr>),
+}
+
+/// A literal value in the HIR.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HirLiteral {
+    /// An integer literal.
+    Int(i64),
+    /// A floating point literal.
+    Float(f64),
+    /// A string literal.
+    String(String),
+    /// A boolean literal.
+    Bool(bool),
+    /// The `*` wildcard used as an aggregate argument.
+    Star,
+}
+
+/// A lowered type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HirType {
+    /// A built-in scalar type.
+    Primitive(PrimitiveType),
+    /// A reference to a struct declaration.
+    Struct(DeclId),
+    /// A reference to an enum declaration.
+    Enum(DeclId),
+    /// A list type `[T]`.
+    List(Box<HirType>),
+    /// An optional type `T?`.
+    Optional(Box<HirType>),
+    /// An anonymous tuple type `(A, B)`.
+    Tuple(Vec<HirType>),
+    /// A primary or foreign key column, `Key<Entity, T>` or `Key<T>`.
+    Key {
+        /// The entity the key belongs to, when written explicitly.
+        entity: Option<DeclId>,
+        /// The underlying column type.
+        ty: Box<HirType>,
+    },
+    /// A foreign key relation, `ForeignKey<Entity>` or `ForeignKey<name: Entity>`.
+    ForeignKey {
+        /// The relation name, when written explicitly.
+        name: Option<String>,
+        /// The referenced struct.
+        entity: DeclId,
+    },
+    /// A type that failed to resolve; an error has already been reported.
+    Unknown,
+}
+
+/// The built-in scalar types of KQL.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum PrimitiveType {
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    F32,
+    F64,
+    D128,
+    Bool,
+    String,
+    DateTime,
+    Date,
+    Time,
+    Uuid,
+    Json,
+}
+
+impl HirType {
+    /// Whether this is a numeric primitive, integer or floating.
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Self::Primitive(p) if p.is_numeric())
+    }
+
+    /// Whether this is an integer primitive, signed or unsigned.
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Self::Primitive(p) if p.is_integer())
+    }
+
+    /// Whether this is a floating point or decimal primitive.
+    pub fn is_float(&self) -> bool {
+        matches!(self, Self::Primitive(p) if p.is_float())
+    }
+
+    /// Whether this is the `String` primitive.
+    pub fn is_string(&self) -> bool {
+        matches!(self, Self::Primitive(PrimitiveType::String))
+    }
+}
+
+impl PrimitiveType {
+    /// Parse a primitive type name as written in KQL source.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let ty = match name {
+            "i8" => Self::I8,
+            "i16" => Self::I16,
+            "i32" => Self::I32,
+            "i64" => Self::I64,
+            "u8" => Self::U8,
+            "u16" => Self::U16,
+            "u32" => Self::U32,
+            "u64" => Self::U64,
+            "f32" => Self::F32,
+            "f64" => Self::F64,
+            "d128" => Self::D128,
+            "bool" | "Bool" => Self::Bool,
+            "String" => Self::String,
+            "DateTime" => Self::DateTime,
+            "Date" => Self::Date,
+            "Time" => Self::Time,
+            "Uuid" => Self::Uuid,
+            "Json" => Self::Json,
+            _ => return None,
+        };
+        Some(ty)
+    }
+
+    /// The name of this type as written in KQL source.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::I8 => "i8",
+            Self::I16 => "i16",
+            Self::I32 => "i32",
+            Self::I64 => "i64",
+            Self::U8 => "u8",
+            Self::U16 => "u16",
+            Self::U32 => "u32",
+            Self::U64 => "u64",
+            Self::F32 => "f32",
+            Self::F64 => "f64",
+            Self::D128 => "d128",
+            Self::Bool => "bool",
+            Self::String => "String",
+            Self::DateTime => "DateTime",
+            Self::Date => "Date",
+            Self::Time => "Time",
+            Self::Uuid => "Uuid",
+            Self::Json => "Json",
+        }
+    }
+
+    /// Whether this is an integer type, signed or unsigned.
+    pub fn is_integer(self) -> bool {
+        matches!(self, Self::I8 | Self::I16 | Self::I32 | Self::I64 | Self::U8 | Self::U16 | Self::U32 | Self::U64)
+    }
+
+    /// Whether this is a floating point or decimal type.
+    pub fn is_float(self) -> bool {
+        matches!(self, Self::F32 | Self::F64 | Self::D128)
+    }
+
+    /// Whether this is any numeric type.
+    pub fn is_numeric(self) -> bool {
+        self.is_integer() || self.is_float()
+    }
+}
@@ -0,0 +1,173 @@
+//! A read-only visitor over the HIR, mirroring [kql_ast::visit] for the
+//! lowered program.
+//!
+//! Every [HirVisitor] method defaults to walking the node's children via the
+//! matching `walk_*` function, so an implementation overrides only the nodes
+//! it cares about and calls the `walk_*` function itself when it still wants
+//! to descend.
+
+use super::{
+    HirAttribute, HirEnum, HirExpr, HirExprKind, HirField, HirLet, HirProgram, HirQuery, HirQueryOp, HirSeed, HirStruct,
+    HirType, HirTypeAlias, HirVariant,
+};
+
+/// A traversal over a lowered [HirProgram] and everything nested inside it.
+pub trait HirVisitor {
+    fn visit_program(&mut self, program: &HirProgram) {
+        walk_program(self, program);
+    }
+    fn visit_struct(&mut self, decl: &HirStruct) {
+        walk_struct(self, decl);
+    }
+    fn visit_field(&mut self, field: &HirField) {
+        walk_field(self, field);
+    }
+    fn visit_enum(&mut self, decl: &HirEnum) {
+        walk_enum(self, decl);
+    }
+    fn visit_variant(&mut self, variant: &HirVariant) {
+        let _ = variant;
+    }
+    fn visit_type_alias(&mut self, decl: &HirTypeAlias) {
+        self.visit_type(&decl.ty);
+    }
+    fn visit_let(&mut self, decl: &HirLet) {
+        self.visit_query(&decl.query);
+    }
+    fn visit_query(&mut self, query: &HirQuery) {
+        walk_query(self, query);
+    }
+    fn visit_query_op(&mut self, op: &HirQueryOp) {
+        walk_query_op(self, op);
+    }
+    fn visit_seed(&mut self, seed: &HirSeed) {
+        walk_seed(self, seed);
+    }
+    fn visit_attribute(&mut self, attribute: &HirAttribute) {
+        walk_attribute(self, attribute);
+    }
+    fn visit_type(&mut self, ty: &HirType) {
+        walk_type(self, ty);
+    }
+    fn visit_expr(&mut self, expr: &HirExpr) {
+        walk_expr(self, expr);
+    }
+}
+
+/// Visit every declaration and seed row of the program.
+pub fn walk_program<V: HirVisitor + ?Sized>(visitor: &mut V, program: &HirProgram) {
+    for strukt in program.structs.values() {
+        visitor.visit_struct(strukt);
+    }
+    for item in program.enums.values() {
+        visitor.visit_enum(item);
+    }
+    for alias in program.type_aliases.values() {
+        visitor.visit_type_alias(alias);
+    }
+    for decl in program.lets.values() {
+        visitor.visit_let(decl);
+    }
+    for seed in &program.seeds {
+        visitor.visit_seed(seed);
+    }
+}
+
+/// Visit the attributes and fields of a struct.
+pub fn walk_struct<V: HirVisitor + ?Sized>(visitor: &mut V, decl: &HirStruct) {
+    for attribute in &decl.attributes {
+        visitor.visit_attribute(attribute);
+    }
+    for field in &decl.fields {
+        visitor.visit_field(field);
+    }
+}
+
+/// Visit the type and attributes of a field.
+pub fn walk_field<V: HirVisitor + ?Sized>(visitor: &mut V, field: &HirField) {
+    visitor.visit_type(&field.ty);
+    for attribute in &field.attributes {
+        visitor.visit_attribute(attribute);
+    }
+}
+
+/// Visit the attributes and variants of an enum.
+pub fn walk_enum<V: HirVisitor + ?Sized>(visitor: &mut V, decl: &HirEnum) {
+    for attribute in &decl.attributes {
+        visitor.visit_attribute(attribute);
+    }
+    for variant in &decl.variants {
+        visitor.visit_variant(variant);
+    }
+}
+
+/// Visit every operator of a query chain.
+pub fn walk_query<V: HirVisitor + ?Sized>(visitor: &mut V, query: &HirQuery) {
+    for op in &query.ops {
+        visitor.visit_query_op(op);
+    }
+}
+
+/// Visit the expressions inside a query operator.
+pub fn walk_query_op<V: HirVisitor + ?Sized>(visitor: &mut V, op: &HirQueryOp) {
+    match op {
+        HirQueryOp::Filter(predicate) => visitor.visit_expr(predicate),
+        HirQueryOp::Sort(_) | HirQueryOp::Map(_) | HirQueryOp::Limit(_) | HirQueryOp::Offset(_) => {}
+    }
+}
+
+/// Visit the value expressions of a seed row.
+pub fn walk_seed<V: HirVisitor + ?Sized>(visitor: &mut V, seed: &HirSeed) {
+    for (_, value) in &seed.values {
+        visitor.visit_expr(value);
+    }
+}
+
+/// Visit the argument expressions of an attribute.
+pub fn walk_attribute<V: HirVisitor + ?Sized>(visitor: &mut V, attribute: &HirAttribute) {
+    for arg in &attribute.args {
+        visitor.visit_expr(&arg.value);
+    }
+}
+
+/// Visit the nested types of a type.
+pub fn walk_type<V: HirVisitor + ?Sized>(visitor: &mut V, ty: &HirType) {
+    match ty {
+        HirType::Key { ty, .. } => visitor.visit_type(ty),
+        HirType::List(inner) | HirType::Optional(inner) => visitor.visit_type(inner),
+        HirType::Tuple(items) => {
+            for item in items {
+                visitor.visit_type(item);
+            }
+        }
+        HirType::Primitive(_) | HirType::Struct(_) | HirType::Enum(_) | HirType::ForeignKey { .. } | HirType::Unknown => {}
+    }
+}
+
+/// Visit every sub-expression of an expression.
+pub fn walk_expr<V: HirVisitor + ?Sized>(visitor: &mut V, expr: &HirExpr) {
+    match &expr.kind {
+        HirExprKind::Literal(_) | HirExprKind::Variable(_) | HirExprKind::Context | HirExprKind::ContextField(_) => {}
+        HirExprKind::Member { base, .. } => visitor.visit_expr(base),
+        HirExprKind::Unary { expr, .. } => visitor.visit_expr(expr),
+        HirExprKind::Binary { lhs, rhs, .. } => {
+            visitor.visit_expr(lhs);
+            visitor.visit_expr(rhs);
+        }
+        HirExprKind::Call { args, .. } => {
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+        }
+        HirExprKind::If { cond, then_branch, else_branch } => {
+            visitor.visit_expr(cond);
+            visitor.visit_expr(then_branch);
+            visitor.visit_expr(else_branch);
+        }
+        HirExprKind::List(items) | HirExprKind::Tuple(items) => {
+            for item in items {
+                visitor.visit_expr(item);
+            }
+        }
+    }
+}
@@ -0,0 +1,14 @@
+#![deny(missing_debug_implementations)]
+#![warn(rustdoc::missing_crate_level_docs)]
+#![doc = include_str!("../readme.md")]
+
+mod compiler;
+pub mod hir;
+pub mod lir;
+pub mod mir;
+mod pipeline;
+
+pub use crate::{
+    compiler::{CompileTimings, Compiler},
+    pipeline::Pipeline,
+};
@@ -0,0 +1,295 @@
+//! The low-level intermediate representation: a dialect-neutral statement
+//! model rendered to concrete SQL text by [sql_gen::SqlGenerator].
+
+pub mod sql_gen;
+
+use crate::mir::RefAction;
+use kql_types::{KqlError, Span};
+use std::{fmt::Display, str::FromStr};
+
+/// A supported SQL backend.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Dialect {
+    /// PostgreSQL.
+    #[default]
+    Postgres,
+    /// MySQL 8+.
+    MySql,
+    /// SQLite 3.
+    Sqlite,
+}
+
+impl Dialect {
+    /// All supported dialects.
+    pub fn all() -> [Dialect; 3] {
+        [Self::Postgres, Self::MySql, Self::Sqlite]
+    }
+}
+
+/// How `u64` columns are stored on sqlite, whose native integers are signed
+/// 64-bit and overflow above `i64::MAX`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum SqliteU64 {
+    /// `NUMERIC` affinity: keeps ordering and arithmetic, but values above
+    /// 2^63 round through floating point.
+    #[default]
+    Numeric,
+    /// `TEXT`: lossless storage, at the cost of lexicographic comparison.
+    Text,
+}
+
+impl FromStr for Dialect {
+    type Err = KqlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "postgres" | "postgresql" | "pg" => Ok(Self::Postgres),
+            "mysql" => Ok(Self::MySql),
+            "sqlite" | "sqlite3" => Ok(Self::Sqlite),
+            other => Err(KqlError::IoError { message: format!("unknown dialect `{}`", other) }),
+        }
+    }
+}
+
+impl Display for Dialect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Postgres => write!(f, "postgres"),
+            Self::MySql => write!(f, "mysql"),
+            Self::Sqlite => write!(f, "sqlite"),
+        }
+    }
+}
+
+/// A schema feature that does not translate cleanly to some dialect, found by
+/// [sql_gen::SqlGenerator::portability_report].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortabilityWarning {
+    /// The dialect the feature does not translate to.
+    pub dialect: Dialect,
+    /// What the generator does instead on that dialect.
+    pub message: String,
+    /// Span of the originating declaration.
+    pub span: Span,
+}
+
+/// A single DDL statement.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    /// `CREATE SCHEMA`, for dialects with schema support.
+    CreateSchema {
+        /// The schema name.
+        name: String,
+    },
+    /// `CREATE TABLE`.
+    CreateTable(CreateTable),
+    /// `CREATE [UNIQUE] INDEX`.
+    CreateIndex(CreateIndex),
+    /// `CREATE TYPE ... AS ENUM`, Postgres only.
+    CreateEnum {
+        /// The type name.
+        name: String,
+        /// The variant labels in order.
+        variants: Vec<String>,
+    },
+    /// `ALTER TABLE`.
+    AlterTable {
+        /// The table to alter.
+        table: String,
+        /// The alteration to apply.
+        op: AlterTableOp,
+    },
+    /// `DROP TABLE`.
+    DropTable {
+        /// The table to drop.
+        name: String,
+        /// Whether to add `IF EXISTS`.
+        if_exists: bool,
+    },
+    /// `DROP INDEX`.
+    DropIndex {
+        /// The index to drop.
+        name: String,
+        /// The table the index belongs to, needed by MySQL.
+        table: String,
+        /// Whether to add `IF EXISTS`.
+        if_exists: bool,
+    },
+    /// `DROP TYPE`, Postgres only.
+    DropType {
+        /// The type to drop.
+        name: String,
+        /// Whether to add `IF EXISTS`.
+        if_exists: bool,
+    },
+}
+
+/// The body of a `CREATE TABLE` statement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateTable {
+    /// The table name.
+    pub name: String,
+    /// Whether to add `IF NOT EXISTS`.
+    pub if_not_exists: bool,
+    /// The column definitions.
+    pub columns: Vec<ColumnDef>,
+    /// Table-level constraints.
+    pub constraints: Vec<TableConstraint>,
+    /// Whether to emit system-versioning DDL on dialects that support it.
+    pub temporal: bool,
+}
+
+/// The body of a `CREATE INDEX` statement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateIndex {
+    /// The index name.
+    pub name: String,
+    /// The table the index is on.
+    pub table: String,
+    /// The indexed columns in order.
+    pub columns: Vec<String>,
+    /// Whether this is a unique index.
+    pub unique: bool,
+    /// Whether to add `IF NOT EXISTS`.
+    pub if_not_exists: bool,
+    /// The index method for `USING`, e.g. `gin`. Postgres only.
+    pub using: Option<String>,
+}
+
+/// A single column definition inside `CREATE TABLE` or `ADD COLUMN`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnDef {
+    /// The column name.
+    pub name: String,
+    /// The rendered data type.
+    pub data_type: DataType,
+    /// Column options in render order.
+    pub options: Vec<ColumnOption>,
+}
+
+/// A concrete SQL data type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataType {
+    /// `TINYINT`
+    TinyInt,
+    /// `SMALLINT`
+    SmallInt,
+    /// `INTEGER`
+    Integer,
+    /// `BIGINT`
+    BigInt,
+    /// `REAL`
+    Real,
+    /// `DOUBLE PRECISION`
+    DoublePrecision,
+    /// `DECIMAL[(p, s)]`
+    Decimal(Option<(u32, u32)>),
+    /// `BOOLEAN`
+    Boolean,
+    /// `TEXT`
+    Text,
+    /// `TIMESTAMP`
+    Timestamp,
+    /// `DATE`
+    Date,
+    /// `TIME`
+    Time,
+    /// `UUID`
+    Uuid,
+    /// `JSON` / `JSONB`
+    Json,
+    /// `SERIAL`, Postgres only.
+    Serial,
+    /// `BIGSERIAL`, Postgres only.
+    BigSerial,
+    /// A type emitted verbatim.
+    Custom(String),
+}
+
+impl Display for DataType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TinyInt => write!(f, "TINYINT"),
+            Self::SmallInt => write!(f, "SMALLINT"),
+            Self::Integer => write!(f, "INTEGER"),
+            Self::BigInt => write!(f, "BIGINT"),
+            Self::Real => write!(f, "REAL"),
+            Self::DoublePrecision => write!(f, "DOUBLE PRECISION"),
+            Self::Decimal(None) => write!(f, "DECIMAL"),
+            Self::Decimal(Some((p, s))) => write!(f, "DECIMAL({}, {})", p, s),
+            Self::Boolean => write!(f, "BOOLEAN"),
+            Self::Text => write!(f, "TEXT"),
+            Self::Timestamp => write!(f, "TIMESTAMP"),
+            Self::Date => write!(f, "DATE"),
+            Self::Time => write!(f, "TIME"),
+            Self::Uuid => write!(f, "UUID"),
+            Self::Json => write!(f, "JSON"),
+            Self::Serial => write!(f, "SERIAL"),
+            Self::BigSerial => write!(f, "BIGSERIAL"),
+            Self::Custom(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// An option rendered after the data type in a column definition.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnOption {
+    /// `NOT NULL`
+    NotNull,
+    /// `PRIMARY KEY`
+    PrimaryKey,
+    /// `UNIQUE`
+    Unique,
+    /// `UNIQUE NULLS NOT DISTINCT` (Postgres 15+).
+    UniqueNullsNotDistinct,
+    /// `AUTO_INCREMENT` / `AUTOINCREMENT`, dialect dependent.
+    AutoIncrement,
+    /// `CHECK (<expr>)`, the expression already rendered.
+    Check(String),
+    /// `DEFAULT <expr>`, already rendered.
+    Default(String),
+    /// `GENERATED ALWAYS AS (<expr>) STORED`
+    Generated(String),
+    /// `COLLATE <name>`, already quoted for the dialect.
+    Collate(String),
+}
+
+/// A table-level constraint inside `CREATE TABLE`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableConstraint {
+    /// `PRIMARY KEY (a, b)`
+    PrimaryKey(Vec<String>),
+    /// A foreign key constraint.
+    ForeignKey {
+        /// The constraint name.
+        name: String,
+        /// The referencing columns.
+        columns: Vec<String>,
+        /// The referenced table.
+        ref_table: String,
+        /// The referenced columns.
+        ref_columns: Vec<String>,
+        /// `ON DELETE` action.
+        on_delete: Option<RefAction>,
+        /// `ON UPDATE` action.
+        on_update: Option<RefAction>,
+        /// Whether to emit `DEFERRABLE INITIALLY DEFERRED`. Postgres only.
+        deferrable: bool,
+    },
+}
+
+/// A single operation inside `ALTER TABLE`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlterTableOp {
+    /// `ADD COLUMN`.
+    AddColumn(ColumnDef),
+    /// `DROP COLUMN`.
+    DropColumn(String),
+    /// Change a column's data type.
+    AlterColumnType {
+        /// The column to change.
+        name: String,
+        /// The new data type.
+        data_type: DataType,
+    },
+}
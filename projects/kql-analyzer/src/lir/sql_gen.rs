@@ -0,0 +1,958 @@
+//! Rendering the MIR schema model to SQL text for a concrete dialect.
+
+use super::*;
+use crate::mir::{
+    BinaryOpKind, Column, Index, IndexOrder, MirExpr, MirProgram, MirQuery, MirType, MirValue, NullsOrder, Table, UnaryOpKind,
+};
+
+use kql_types::{KqlError, Result};
+use std::collections::HashSet;
+
+/// Words every supported dialect reserves, which cannot appear bare as a
+/// table or column name. Quoting is only applied on a collision (or for a
+/// name that is not a plain identifier), so ordinary schemas render unquoted.
+const RESERVED_SQL_WORDS: &[&str] = &[
+    "all",
+    "and",
+    "as",
+    "asc",
+    "between",
+    "by",
+    "case",
+    "check",
+    "column",
+    "constraint",
+    "create",
+    "cross",
+    "default",
+    "delete",
+    "desc",
+    "distinct",
+    "drop",
+    "else",
+    "end",
+    "exists",
+    "foreign",
+    "from",
+    "group",
+    "having",
+    "in",
+    "index",
+    "inner",
+    "insert",
+    "into",
+    "is",
+    "join",
+    "left",
+    "like",
+    "limit",
+    "not",
+    "null",
+    "on",
+    "or",
+    "order",
+    "outer",
+    "primary",
+    "references",
+    "right",
+    "select",
+    "set",
+    "table",
+    "then",
+    "to",
+    "union",
+    "unique",
+    "update",
+    "values",
+    "when",
+    "where",
+];
+
+/// The collations sqlite ships with; any other name needs a runtime
+/// registration the generated DDL cannot assume.
+const SQLITE_COLLATIONS: &[&str] = &["binary", "nocase", "rtrim"];
+
+/// Renders a [MirProgram] as DDL and query SQL for one [Dialect].
+#[derive(Debug)]
+pub struct SqlGenerator<'a> {
+    mir: &'a MirProgram,
+    /// The dialect SQL is rendered for.
+    pub dialect: Dialect,
+    sqlite_u64: SqliteU64,
+    enum_checks: bool,
+}
+
+impl<'a> SqlGenerator<'a> {
+    /// Create a generator over a lowered program.
+    pub fn new(mir: &'a MirProgram, dialect: Dialect) -> Self {
+        Self { mir, dialect, sqlite_u64: SqliteU64::default(), enum_checks: false }
+    }
+
+    /// Choose how `u64` columns are stored when rendering for sqlite.
+    pub fn with_sqlite_u64(mut self, mode: SqliteU64) -> Self {
+        self.sqlite_u64 = mode;
+        self
+    }
+
+    /// Enforce string-layout enum columns with a `CHECK (col IN (...))`
+    /// constraint instead of a native enum type. Sqlite has no native enums,
+    /// so this is the only validation available there; on Postgres and MySQL
+    /// it replaces `CREATE TYPE` / `ENUM(...)` with constrained TEXT.
+    pub fn with_enum_checks(mut self, enabled: bool) -> Self {
+        self.enum_checks = enabled;
+        self
+    }
+
+    /// The program this generator renders.
+    pub fn program(&self) -> &MirProgram {
+        self.mir
+    }
+
+    /// Reject schema features the target dialect cannot express at all.
+    /// Rendering itself is infallible, so callers producing final artifacts
+    /// should run this first.
+    pub fn validate(&self) -> Result<()> {
+        if self.dialect != Dialect::Postgres {
+            for table in self.mir.tables.values() {
+                for column in &table.columns {
+                    if column.unique_nulls_not_distinct {
+                        let message =
+                            format!("`nulls_not_distinct` on `{}.{}` requires the postgres dialect", table.name, column.name);
+                        return Err(KqlError::semantic(message, column.span));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Generate the full schema DDL: schemas, enum types, tables and indexes.
+    pub fn generate_ddl(&self) -> Vec<Statement> {
+        let mut statements = Vec::new();
+        if self.dialect == Dialect::Postgres {
+            let mut schemas = HashSet::new();
+            for table in self.mir.tables.values() {
+                if let Some(schema) = &table.schema {
+                    if schemas.insert(schema.clone()) {
+                        statements.push(Statement::CreateSchema { name: schema.clone() });
+                    }
+                }
+            }
+            for item in self.mir.enums.values() {
+                if item.string_layout && !self.enum_checks {
+                    statements.push(Statement::CreateEnum {
+                        name: item.name.clone(),
+                        variants: item.variants.iter().map(|(name, _)| name.clone()).collect(),
+                    });
+                }
+            }
+        }
+        for table in self.tables_in_dependency_order() {
+            statements.push(Statement::CreateTable(self.generate_create_table(table)));
+            for index in &table.indexes {
+                statements.push(Statement::CreateIndex(self.generate_create_index(table, index)));
+            }
+        }
+        statements
+    }
+
+    /// Generate a teardown script for the schema: `DROP TABLE IF EXISTS` for
+    /// every table in reverse dependency order, so referencing tables go
+    /// before their targets, followed by `DROP TYPE` for native enum types.
+    pub fn generate_drop_all(&self) -> Vec<Statement> {
+        let mut statements = Vec::new();
+        for table in self.tables_in_dependency_order().into_iter().rev() {
+            statements.push(Statement::DropTable { name: self.table_ident(table), if_exists: true });
+        }
+        if self.dialect == Dialect::Postgres {
+            for item in self.mir.enums.values() {
+                if item.string_layout && !self.enum_checks {
+                    statements.push(Statement::DropType { name: item.name.clone(), if_exists: true });
+                }
+            }
+        }
+        statements
+    }
+
+    fn generate_create_index(&self, table: &Table, index: &Index) -> CreateIndex {
+        CreateIndex {
+            name: index.name.clone(),
+            table: self.table_ident(table),
+            columns: index
+                .columns
+                .iter()
+                .map(|c| {
+                    let rendered = if c.expr {
+                        // MySQL only accepts a functional key part inside its
+                        // own parentheses; the others take the bare expression.
+                        match self.dialect {
+                            Dialect::MySql => format!("({})", c.name),
+                            Dialect::Postgres | Dialect::Sqlite => c.name.clone(),
+                        }
+                    } else {
+                        self.ident(&c.name)
+                    };
+                    match c.order {
+                        None => rendered,
+                        Some(IndexOrder::Asc) => format!("{} ASC", rendered),
+                        Some(IndexOrder::Desc) => format!("{} DESC", rendered),
+                    }
+                })
+                .collect(),
+            unique: index.unique,
+            if_not_exists: false,
+            using: index.method.clone(),
+        }
+    }
+
+    /// Render the full schema, including named queries, as a single SQL script.
+    pub fn generate_sql(&self) -> String {
+        let mut out = String::new();
+        for statement in self.generate_ddl() {
+            out.push_str(&self.render(&statement));
+            out.push_str(";\n\n");
+        }
+        for query in &self.mir.queries {
+            out.push_str(&format!("-- let {}\n{};\n\n", query.name, self.generate_select(query, &[])));
+        }
+        out.trim_end().to_string()
+    }
+
+    /// Flag schema features that will not translate cleanly to every dialect,
+    /// regardless of the dialect this generator renders for.
+    pub fn portability_report(&self) -> Vec<PortabilityWarning> {
+        let mut warnings = Vec::new();
+        for table in self.mir.tables.values() {
+            if table.schema.is_some() {
+                warnings.push(PortabilityWarning {
+                    dialect: Dialect::Sqlite,
+                    message: format!("table `{}`: sqlite has no schemas, the namespace is dropped", table.name),
+                    span: table.span,
+                });
+            }
+            for column in &table.columns {
+                let place = format!("column `{}.{}`", table.name, column.name);
+                match &column.ty {
+                    MirType::Enum(name) if self.mir.enums.get(name).is_some_and(|e| e.string_layout) => {
+                        warnings.push(PortabilityWarning {
+                            dialect: Dialect::Sqlite,
+                            message: format!("{}: sqlite has no native enum types, values are stored as unchecked TEXT", place),
+                            span: column.span,
+                        });
+                    }
+                    MirType::DateTime => warnings.push(PortabilityWarning {
+                        dialect: Dialect::Sqlite,
+                        message: format!("{}: sqlite has no timestamp type, values rely on column affinity", place),
+                        span: column.span,
+                    }),
+                    MirType::U8 | MirType::U16 | MirType::U32 | MirType::U64 => warnings.push(PortabilityWarning {
+                        dialect: Dialect::Postgres,
+                        message: format!("{}: postgres has no unsigned integer types, a wider signed type is used", place),
+                        span: column.span,
+                    }),
+                    MirType::Uuid => warnings.push(PortabilityWarning {
+                        dialect: Dialect::MySql,
+                        message: format!("{}: mysql has no UUID type, CHAR(36) is used", place),
+                        span: column.span,
+                    }),
+                    _ => {}
+                }
+            }
+        }
+        warnings
+    }
+
+    /// Render an `INSERT` statement for every `seed` row in the program.
+    pub fn generate_seed(&self) -> String {
+        let mut out = String::new();
+        for seed in &self.mir.seeds {
+            let Some(table) = self.mir.tables.get(&seed.table) else {
+                continue;
+            };
+            let values: Vec<String> = seed.values.iter().map(|v| self.render_value(v)).collect();
+            let insert = format!(
+                "INSERT INTO {} ({}) VALUES ({});\n",
+                self.table_ident(table),
+                seed.columns.join(", "),
+                values.join(", ")
+            );
+            out.push_str(&insert);
+        }
+        out
+    }
+
+    /// Render one SQL script per table: its `CREATE TABLE` followed by its
+    /// indexes. The pairs come back as `(table name, script)` in the same
+    /// dependency order as [Self::generate_sql], so writing them out in order
+    /// preserves creation order across files.
+    pub fn generate_split_sql(&self) -> Vec<(String, String)> {
+        let mut scripts = Vec::new();
+        for table in self.tables_in_dependency_order() {
+            let mut script = String::new();
+            script.push_str(&self.render(&Statement::CreateTable(self.generate_create_table(table))));
+            script.push_str(";\n");
+            for index in &table.indexes {
+                script.push('\n');
+                script.push_str(&self.render(&Statement::CreateIndex(self.generate_create_index(table, index))));
+                script.push_str(";\n");
+            }
+            scripts.push((table.name.clone(), script));
+        }
+        scripts
+    }
+
+    /// Order tables so that referenced tables are created before referencing ones.
+    pub fn tables_in_dependency_order(&self) -> Vec<&Table> {
+        let mut ordered: Vec<&Table> = Vec::new();
+        let mut placed: HashSet<&str> = HashSet::new();
+        let mut remaining: Vec<&Table> = self.mir.tables.values().collect();
+        while !remaining.is_empty() {
+            let before = remaining.len();
+            remaining.retain(|table| {
+                let ready =
+                    table.foreign_keys.iter().all(|fk| fk.ref_table == table.name || placed.contains(fk.ref_table.as_str()));
+                if ready {
+                    placed.insert(table.name.as_str());
+                    ordered.push(table);
+                }
+                !ready
+            });
+            if remaining.len() == before {
+                // A reference cycle; emit the rest in declaration order.
+                ordered.append(&mut remaining);
+            }
+        }
+        ordered
+    }
+
+    fn generate_create_table(&self, table: &Table) -> CreateTable {
+        let single_pk = table.primary_key.len() == 1;
+        let mut columns = Vec::new();
+        for column in &table.columns {
+            let mut def = self.generate_column_def(column);
+            if single_pk && table.primary_key[0] == column.name {
+                def.options.push(ColumnOption::PrimaryKey);
+            }
+            columns.push(def);
+        }
+        let mut constraints = Vec::new();
+        if table.primary_key.len() > 1 {
+            constraints.push(TableConstraint::PrimaryKey(table.primary_key.clone()));
+        }
+        for fk in &table.foreign_keys {
+            let ref_table =
+                self.mir.table_by_name(&fk.ref_table).map(|t| self.table_ident(t)).unwrap_or_else(|| fk.ref_table.clone());
+            constraints.push(TableConstraint::ForeignKey {
+                name: fk.name.clone(),
+                columns: fk.columns.clone(),
+                ref_table,
+                ref_columns: fk.ref_columns.clone(),
+                on_delete: fk.on_delete,
+                on_update: fk.on_update,
+                deferrable: fk.deferrable,
+            });
+        }
+        CreateTable { name: self.table_ident(table), if_not_exists: false, columns, constraints, temporal: table.temporal }
+    }
+
+    /// Render a single column definition for this dialect.
+    pub fn generate_column_def(&self, column: &Column) -> ColumnDef {
+        let mut data_type = match self.db_type_override(column) {
+            Some(raw) => DataType::Custom(raw.to_string()),
+            None => self.data_type(&column.ty),
+        };
+        let mut options = Vec::new();
+        if column.auto_increment {
+            match self.dialect {
+                Dialect::Postgres => {
+                    data_type = if data_type == DataType::BigInt { DataType::BigSerial } else { DataType::Serial };
+                }
+                Dialect::MySql | Dialect::Sqlite => options.push(ColumnOption::AutoIncrement),
+            }
+        }
+        if !column.nullable {
+            options.push(ColumnOption::NotNull);
+        }
+        if column.unique {
+            if column.unique_nulls_not_distinct && self.dialect == Dialect::Postgres {
+                options.push(ColumnOption::UniqueNullsNotDistinct);
+            } else {
+                options.push(ColumnOption::Unique);
+            }
+        }
+        if self.enum_checks {
+            if let MirType::Enum(name) = &column.ty {
+                if let Some(item) = self.mir.enums.get(name).filter(|e| e.string_layout) {
+                    let variants = item.variants.iter().map(|(name, _)| format!("'{}'", name)).collect::<Vec<_>>().join(", ");
+                    options.push(ColumnOption::Check(format!("{} IN ({})", self.ident(&column.name), variants)));
+                }
+            }
+        }
+        if let Some(default) = &column.default {
+            options.push(ColumnOption::Default(self.render_value(default)));
+        }
+        if let Some(expr) = &column.generated {
+            // Every supported dialect (SQLite since 3.31) takes the stored
+            // generated-column form.
+            options.push(ColumnOption::Generated(expr.clone()));
+        }
+        if let Some(collation) = &column.collation {
+            match self.dialect {
+                Dialect::Postgres => options.push(ColumnOption::Collate(format!("\"{}\"", collation))),
+                Dialect::MySql => options.push(ColumnOption::Collate(collation.clone())),
+                // Sqlite only ships BINARY, NOCASE and RTRIM; anything else
+                // would fail at CREATE TABLE time, so it is dropped.
+                Dialect::Sqlite => {
+                    if SQLITE_COLLATIONS.iter().any(|c| c.eq_ignore_ascii_case(collation)) {
+                        options.push(ColumnOption::Collate(collation.clone()));
+                    }
+                }
+            }
+        }
+        ColumnDef { name: column.name.clone(), data_type, options }
+    }
+
+    /// The raw `@db_type` override applying to this generator's dialect.
+    fn db_type_override<'c>(&self, column: &'c Column) -> Option<&'c str> {
+        let raw = column.db_type.as_deref()?;
+        match &column.db_type_dialect {
+            Some(name) => name.parse::<Dialect>().is_ok_and(|d| d == self.dialect).then_some(raw),
+            None => Some(raw),
+        }
+    }
+
+    fn data_type(&self, ty: &MirType) -> DataType {
+        match self.dialect {
+            Dialect::Postgres => match ty {
+                MirType::I8 | MirType::I16 | MirType::U8 => DataType::SmallInt,
+                MirType::I32 | MirType::U16 => DataType::Integer,
+                MirType::I64 | MirType::U32 => DataType::BigInt,
+                MirType::U64 => DataType::Custom("NUMERIC(20)".to_string()),
+                MirType::F32 => DataType::Real,
+                MirType::F64 => DataType::DoublePrecision,
+                MirType::Decimal { precision } => DataType::Decimal(*precision),
+                MirType::Bool => DataType::Boolean,
+                MirType::Text => DataType::Text,
+                MirType::DateTime => DataType::Timestamp,
+                MirType::Date => DataType::Date,
+                MirType::Time => DataType::Time,
+                MirType::Uuid => DataType::Uuid,
+                MirType::Json => DataType::Custom("JSONB".to_string()),
+                MirType::Enum(name) => self.enum_type(name, DataType::Text),
+            },
+            Dialect::MySql => match ty {
+                MirType::I8 => DataType::TinyInt,
+                MirType::I16 => DataType::SmallInt,
+                MirType::I32 => DataType::Integer,
+                MirType::I64 => DataType::BigInt,
+                MirType::U8 => DataType::Custom("TINYINT UNSIGNED".to_string()),
+                MirType::U16 => DataType::Custom("SMALLINT UNSIGNED".to_string()),
+                MirType::U32 => DataType::Custom("INT UNSIGNED".to_string()),
+                MirType::U64 => DataType::Custom("BIGINT UNSIGNED".to_string()),
+                MirType::F32 => DataType::Custom("FLOAT".to_string()),
+                MirType::F64 => DataType::Custom("DOUBLE".to_string()),
+                MirType::Decimal { precision } => DataType::Decimal(*precision),
+                // MySQL's BOOLEAN is only an alias; the real type is spelled out.
+                MirType::Bool => DataType::Custom("TINYINT(1)".to_string()),
+                MirType::Text => DataType::Text,
+                MirType::DateTime => DataType::Custom("DATETIME".to_string()),
+                MirType::Date => DataType::Date,
+                MirType::Time => DataType::Time,
+                MirType::Uuid => DataType::Custom("CHAR(36)".to_string()),
+                MirType::Json => DataType::Json,
+                MirType::Enum(name) => self.mysql_enum_type(name),
+            },
+            Dialect::Sqlite => match ty {
+                MirType::I8 | MirType::I16 | MirType::I32 => DataType::Integer,
+                MirType::I64 => DataType::BigInt,
+                // Sqlite integers are signed 64-bit, so everything up to u32
+                // fits; u64 does not and is stored per [SqliteU64].
+                MirType::U8 | MirType::U16 => DataType::Integer,
+                MirType::U32 => DataType::BigInt,
+                MirType::U64 => match self.sqlite_u64 {
+                    SqliteU64::Numeric => DataType::Custom("NUMERIC".to_string()),
+                    SqliteU64::Text => DataType::Text,
+                },
+                MirType::F32 | MirType::F64 => DataType::Real,
+                MirType::Decimal { .. } => DataType::Custom("NUMERIC".to_string()),
+                // Sqlite stores booleans with integer affinity.
+                MirType::Bool => DataType::Integer,
+                MirType::Text | MirType::Uuid | MirType::Json => DataType::Text,
+                MirType::DateTime => DataType::Timestamp,
+                MirType::Date => DataType::Date,
+                MirType::Time => DataType::Time,
+                MirType::Enum(name) => match self.mir.enums.get(name) {
+                    Some(item) if item.string_layout => DataType::Text,
+                    _ => DataType::Integer,
+                },
+            },
+        }
+    }
+
+    fn enum_type(&self, full_name: &str, int_fallback: DataType) -> DataType {
+        match self.mir.enums.get(full_name) {
+            Some(item) if item.string_layout => {
+                if self.enum_checks {
+                    DataType::Text
+                } else {
+                    DataType::Custom(item.name.clone())
+                }
+            }
+            _ => int_fallback,
+        }
+    }
+
+    fn mysql_enum_type(&self, full_name: &str) -> DataType {
+        match self.mir.enums.get(full_name) {
+            Some(item) if item.string_layout && self.enum_checks => DataType::Text,
+            Some(item) if item.string_layout => {
+                let variants = item.variants.iter().map(|(name, _)| format!("'{}'", name)).collect::<Vec<_>>().join(", ");
+                DataType::Custom(format!("ENUM({})", variants))
+            }
+            _ => DataType::Integer,
+        }
+    }
+
+    /// The identifier a table is referred to by in this dialect.
+    pub fn table_ident(&self, table: &Table) -> String {
+        match (&table.schema, self.dialect) {
+            (Some(schema), Dialect::Postgres | Dialect::MySql) => {
+                format!("{}.{}", self.ident(schema), self.ident(&table.name))
+            }
+            _ => self.ident(&table.name),
+        }
+    }
+
+    /// Quote `name` when it cannot appear bare: a reserved word, or anything
+    /// other than a plain identifier. MySQL quotes with backticks, the other
+    /// dialects with standard double quotes.
+    pub fn ident(&self, name: &str) -> String {
+        let plain = !name.is_empty()
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && !RESERVED_SQL_WORDS.contains(&name.to_ascii_lowercase().as_str());
+        if plain {
+            return name.to_string();
+        }
+        match self.dialect {
+            Dialect::MySql => format!("`{}`", name.replace('`', "``")),
+            _ => format!("\"{}\"", name.replace('"', "\"\"")),
+        }
+    }
+
+    fn ident_list(&self, names: &[String]) -> String {
+        names.iter().map(|name| self.ident(name)).collect::<Vec<_>>().join(", ")
+    }
+
+    /// Map a builtin function name to what this dialect spells it.
+    /// `ifnull` is a mysql/sqlite name; postgres only has the standard
+    /// `COALESCE`, which every dialect accepts.
+    fn function_name<'f>(&self, func: &'f str) -> &'f str {
+        match func {
+            "ifnull" if self.dialect == Dialect::Postgres => "coalesce",
+            other => other,
+        }
+    }
+
+    /// Render a literal value as SQL.
+    pub fn render_value(&self, value: &MirValue) -> String {
+        match value {
+            MirValue::Int(v) => v.to_string(),
+            MirValue::Float(v) => v.to_string(),
+            MirValue::Text(v) => format!("'{}'", v.replace('\'', "''")),
+            MirValue::Bool(v) => match self.dialect {
+                Dialect::Postgres => if *v { "TRUE" } else { "FALSE" }.to_string(),
+                // The boolean columns are integers on these dialects.
+                Dialect::MySql | Dialect::Sqlite => if *v { "1" } else { "0" }.to_string(),
+            },
+            MirValue::Function(name) => self.render_function_value(name),
+        }
+    }
+
+    /// The SQL expression a builtin default function renders to, unquoted.
+    fn render_function_value(&self, name: &str) -> String {
+        match (name, self.dialect) {
+            ("now", _) => "CURRENT_TIMESTAMP".to_string(),
+            ("uuid", Dialect::Postgres) => "gen_random_uuid()".to_string(),
+            ("uuid", Dialect::MySql) => "(UUID())".to_string(),
+            // Sqlite has no UUID builtin; a random 128-bit hex string is the
+            // usual stand-in.
+            ("uuid", Dialect::Sqlite) => "(lower(hex(randomblob(16))))".to_string(),
+            (other, _) => format!("{}()", other),
+        }
+    }
+
+    /// Render a statement as SQL text, without the trailing semicolon.
+    pub fn render(&self, statement: &Statement) -> String {
+        match statement {
+            Statement::CreateSchema { name } => format!("CREATE SCHEMA IF NOT EXISTS {}", name),
+            Statement::CreateTable(create) => self.render_create_table(create),
+            Statement::CreateIndex(create) => self.render_create_index(create),
+            Statement::CreateEnum { name, variants } => {
+                let variants = variants.iter().map(|v| format!("'{}'", v)).collect::<Vec<_>>().join(", ");
+                format!("CREATE TYPE {} AS ENUM ({})", name, variants)
+            }
+            Statement::AlterTable { table, op } => self.render_alter_table(table, op),
+            Statement::DropTable { name, if_exists } => {
+                format!("DROP TABLE {}{}", if *if_exists { "IF EXISTS " } else { "" }, name)
+            }
+            Statement::DropIndex { name, table, if_exists } => match self.dialect {
+                Dialect::MySql => format!("DROP INDEX {} ON {}", name, table),
+                _ => format!("DROP INDEX {}{}", if *if_exists { "IF EXISTS " } else { "" }, name),
+            },
+            Statement::DropType { name, if_exists } => {
+                format!("DROP TYPE {}{}", if *if_exists { "IF EXISTS " } else { "" }, name)
+            }
+        }
+    }
+
+    fn render_create_table(&self, create: &CreateTable) -> String {
+        let mut lines = Vec::new();
+        for column in &create.columns {
+            lines.push(format!("    {}", self.render_column_def(column)));
+        }
+        for constraint in &create.constraints {
+            lines.push(format!("    {}", self.render_constraint(constraint)));
+        }
+        let if_not_exists = if create.if_not_exists { "IF NOT EXISTS " } else { "" };
+        // No supported backend implements SQL:2011 system versioning in its
+        // stock build (`WITH SYSTEM VERSIONING` is MariaDB-only), so temporal
+        // tables always use the plain `valid_from`/`valid_to` columns.
+        format!("CREATE TABLE {}{} (\n{}\n)", if_not_exists, create.name, lines.join(",\n"))
+    }
+
+    /// Render a column definition as it appears inside `CREATE TABLE`.
+    pub fn render_column_def(&self, column: &ColumnDef) -> String {
+        let mut out = format!("{} {}", self.ident(&column.name), column.data_type);
+        for option in &column.options {
+            let rendered = match option {
+                ColumnOption::NotNull => "NOT NULL".to_string(),
+                ColumnOption::PrimaryKey => "PRIMARY KEY".to_string(),
+                ColumnOption::Unique => "UNIQUE".to_string(),
+                ColumnOption::UniqueNullsNotDistinct => "UNIQUE NULLS NOT DISTINCT".to_string(),
+                ColumnOption::AutoIncrement => match self.dialect {
+                    Dialect::MySql => "AUTO_INCREMENT".to_string(),
+                    _ => "AUTOINCREMENT".to_string(),
+                },
+                ColumnOption::Check(expr) => format!("CHECK ({})", expr),
+                ColumnOption::Default(value) => format!("DEFAULT {}", value),
+                ColumnOption::Generated(expr) => format!("GENERATED ALWAYS AS ({}) STORED", expr),
+                ColumnOption::Collate(name) => format!("COLLATE {}", name),
+            };
+            out.push(' ');
+            out.push_str(&rendered);
+        }
+        out
+    }
+
+    fn render_constraint(&self, constraint: &TableConstraint) -> String {
+        match constraint {
+            TableConstraint::PrimaryKey(columns) => format!("PRIMARY KEY ({})", self.ident_list(columns)),
+            TableConstraint::ForeignKey { name, columns, ref_table, ref_columns, on_delete, on_update, deferrable } => {
+                let mut out = format!(
+                    "CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({})",
+                    name,
+                    self.ident_list(columns),
+                    ref_table,
+                    self.ident_list(ref_columns)
+                );
+                if let Some(action) = on_delete {
+                    out.push_str(&format!(" ON DELETE {}", action.as_sql()));
+                }
+                if let Some(action) = on_update {
+                    out.push_str(&format!(" ON UPDATE {}", action.as_sql()));
+                }
+                // Only Postgres can defer constraint checks to commit time;
+                // the other dialects enforce the constraint immediately.
+                if *deferrable && self.dialect == Dialect::Postgres {
+                    out.push_str(" DEFERRABLE INITIALLY DEFERRED");
+                }
+                out
+            }
+        }
+    }
+
+    fn render_create_index(&self, create: &CreateIndex) -> String {
+        let unique = if create.unique { "UNIQUE " } else { "" };
+        // MySQL rejects `IF NOT EXISTS` on indexes, so the flag only renders
+        // for the other dialects.
+        let if_not_exists = if create.if_not_exists && self.dialect != Dialect::MySql { "IF NOT EXISTS " } else { "" };
+        // Only Postgres has pluggable index methods; the other dialects fall
+        // back to their default index type.
+        let using = match (&create.using, self.dialect) {
+            (Some(method), Dialect::Postgres) => format!(" USING {}", method),
+            _ => String::new(),
+        };
+        format!(
+            "CREATE {}INDEX {}{} ON {}{} ({})",
+            unique,
+            if_not_exists,
+            create.name,
+            create.table,
+            using,
+            create.columns.join(", ")
+        )
+    }
+
+    fn render_alter_table(&self, table: &str, op: &AlterTableOp) -> String {
+        match op {
+            AlterTableOp::AddColumn(column) => format!("ALTER TABLE {} ADD COLUMN {}", table, self.render_column_def(column)),
+            AlterTableOp::DropColumn(name) => format!("ALTER TABLE {} DROP COLUMN {}", table, name),
+            AlterTableOp::AlterColumnType { name, data_type } => match self.dialect {
+                Dialect::MySql => format!("ALTER TABLE {} MODIFY COLUMN {} {}", table, name, data_type),
+                _ => format!("ALTER TABLE {} ALTER COLUMN {} SET DATA TYPE {}", table, name, data_type),
+            },
+        }
+    }
+
+    /// Render a named query as a `SELECT`, optionally joining the named relations.
+    pub fn generate_select(&self, query: &MirQuery, relations: &[&str]) -> String {
+        let table = self.mir.table_by_name(&query.table);
+        let projections = if query.projections.is_empty() {
+            "*".to_string()
+        } else {
+            query
+                .projections
+                .iter()
+                .map(|p| match p {
+                    crate::mir::MirProjection::Column(name) => name.clone(),
+                    crate::mir::MirProjection::Alias(name, expr) => {
+                        format!("{} AS {}", self.generate_expr(expr), name)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut out = format!("SELECT {} FROM {}", projections, query.table);
+        for relation_name in relations {
+            let Some(relation) = table.and_then(|t| t.relations.iter().find(|r| r.name == *relation_name)) else {
+                continue;
+            };
+            if let Some(fk_column) = &relation.fk_column {
+                let ref_pk = self
+                    .mir
+                    .table_by_name(&relation.to_table)
+                    .and_then(|t| t.primary_key.first().cloned())
+                    .unwrap_or_else(|| "id".to_string());
+                let join = if relation.required { "INNER" } else { "LEFT OUTER" };
+                out.push_str(&format!(
+                    " {} JOIN {} ON {}.{} = {}.{}",
+                    join, relation.to_table, query.table, fk_column, relation.to_table, ref_pk
+                ));
+            }
+        }
+        if let Some(filter) = &query.filter {
+            out.push_str(&format!(" WHERE {}", self.generate_expr(filter)));
+        }
+        if !query.order_by.is_empty() {
+            let mut keys = Vec::new();
+            for key in &query.order_by {
+                let rendered = if key.desc { format!("{} DESC", key.column) } else { key.column.clone() };
+                match (key.nulls, self.dialect) {
+                    (None, _) => keys.push(rendered),
+                    (Some(NullsOrder::First), Dialect::Postgres) => keys.push(format!("{} NULLS FIRST", rendered)),
+                    (Some(NullsOrder::Last), Dialect::Postgres) => keys.push(format!("{} NULLS LAST", rendered)),
+                    // MySQL and SQLite have no NULLS clause; a boolean
+                    // `IS NULL` key in front gets the same placement.
+                    (Some(NullsOrder::First), _) => {
+                        keys.push(format!("{} IS NULL DESC", key.column));
+                        keys.push(rendered);
+                    }
+                    (Some(NullsOrder::Last), _) => {
+                        keys.push(format!("{} IS NULL", key.column));
+                        keys.push(rendered);
+                    }
+                }
+            }
+            out.push_str(&format!(" ORDER BY {}", keys.join(", ")));
+        }
+        if let Some(limit) = query.limit {
+            out.push_str(&format!(" LIMIT {}", limit));
+        }
+        if let Some(offset) = query.offset {
+            out.push_str(&format!(" OFFSET {}", offset));
+        }
+        out
+    }
+
+    /// Render a scalar expression as SQL.
+    pub fn generate_expr(&self, expr: &MirExpr) -> String {
+        match expr {
+            MirExpr::Column(name) => name.clone(),
+            MirExpr::Value(value) => self.render_value(value),
+            MirExpr::Star => "*".to_string(),
+            MirExpr::Unary { op, expr } => {
+                let operand = match expr.as_ref() {
+                    MirExpr::Binary { .. } => format!("({})", self.generate_expr(expr)),
+                    _ => self.generate_expr(expr),
+                };
+                match op {
+                    UnaryOpKind::Neg => format!("-{}", operand),
+                    UnaryOpKind::Not => format!("NOT {}", operand),
+                }
+            }
+            MirExpr::Binary { op, lhs, rhs } => {
+                let precedence = sql_precedence(*op);
+                format!(
+                    "{} {} {}",
+                    self.binary_operand(lhs, precedence, false),
+                    binary_op_sql(*op),
+                    self.binary_operand(rhs, precedence, true)
+                )
+            }
+            MirExpr::Call { func, args } => {
+                let args = args.iter().map(|arg| self.generate_expr(arg)).collect::<Vec<_>>().join(", ");
+                format!("{}({})", self.function_name(func), args)
+            }
+            MirExpr::Case { when, then, otherwise } => format!(
+                "CASE WHEN {} THEN {} ELSE {} END",
+                self.generate_expr(when),
+                self.generate_expr(then),
+                self.generate_expr(otherwise)
+            ),
+            MirExpr::JsonAccess { column, path } => match self.dialect {
+                // `->` walks intermediate objects, `->>` extracts the leaf as text.
+                Dialect::Postgres => {
+                    let mut out = column.clone();
+                    for (position, key) in path.iter().enumerate() {
+                        let op = if position + 1 == path.len() { "->>" } else { "->" };
+                        out.push_str(&format!("{}'{}'", op, key));
+                    }
+                    out
+                }
+                Dialect::MySql | Dialect::Sqlite => format!("JSON_EXTRACT({}, '$.{}')", column, path.join(".")),
+            },
+        }
+    }
+
+    /// Render one side of a binary expression, re-inserting the parentheses
+    /// the parser discarded: a child that binds looser than its parent — or
+    /// equally tight on the right-hand side — must be grouped to keep the
+    /// tree's meaning.
+    fn binary_operand(&self, expr: &MirExpr, parent: u8, is_rhs: bool) -> String {
+        let rendered = self.generate_expr(expr);
+        if let MirExpr::Binary { op, .. } = expr {
+            let child = sql_precedence(*op);
+            if child < parent || (child == parent && is_rhs) {
+                return format!("({})", rendered);
+            }
+        }
+        rendered
+    }
+
+    /// Render a parameterized `INSERT` for a table, skipping auto-increment
+    /// and generated columns.
+    pub fn generate_insert(&self, table: &Table) -> String {
+        let columns: Vec<&Column> = table.columns.iter().filter(|c| !c.auto_increment && c.generated.is_none()).collect();
+        let names = columns.iter().map(|c| self.ident(&c.name)).collect::<Vec<_>>().join(", ");
+        let values = (1..=columns.len()).map(|i| self.placeholder(i)).collect::<Vec<_>>().join(", ");
+        format!("INSERT INTO {} ({}) VALUES ({})", self.table_ident(table), names, values)
+    }
+
+    /// Render a keyset (cursor) pagination query: rows strictly after the
+    /// cursor bound over `order_cols`, ordered by those same columns. Unlike
+    /// `OFFSET`, the cursor predicate stays cheap on large tables.
+    pub fn generate_keyset_page(&self, table: &Table, order_cols: &[&str], limit: u64) -> String {
+        let predicate = match order_cols {
+            [single] => format!("{} > {}", single, self.placeholder(1)),
+            _ => {
+                let placeholders = (1..=order_cols.len()).map(|i| self.placeholder(i)).collect::<Vec<_>>().join(", ");
+                format!("({}) > ({})", order_cols.join(", "), placeholders)
+            }
+        };
+        format!(
+            "SELECT * FROM {} WHERE {} ORDER BY {} LIMIT {}",
+            self.table_ident(table),
+            predicate,
+            order_cols.join(", "),
+            limit
+        )
+    }
+
+    /// Render a parameterized `UPDATE ... WHERE <pk>` for a table. With
+    /// `returning`, the statement hands the updated row back via
+    /// `RETURNING *` on dialects that support it.
+    pub fn generate_update_by_pk(&self, table: &Table, returning: bool) -> String {
+        let non_pk: Vec<&Column> = table.columns.iter().filter(|c| !table.primary_key.contains(&c.name)).collect();
+        let assignments = non_pk
+            .iter()
+            .enumerate()
+            .map(|(i, c)| format!("{} = {}", c.name, self.placeholder(i + 1)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let predicate = self.pk_predicate(table, non_pk.len());
+        let mut sql = format!("UPDATE {} SET {} WHERE {}", self.table_ident(table), assignments, predicate);
+        if returning {
+            sql.push_str(self.returning_clause());
+        }
+        sql
+    }
+
+    /// Render a parameterized `DELETE ... WHERE <pk>` for a table. With
+    /// `returning`, the deleted row comes back via `RETURNING *` on dialects
+    /// that support it.
+    pub fn generate_delete_by_pk(&self, table: &Table, returning: bool) -> String {
+        let mut sql = format!("DELETE FROM {} WHERE {}", self.table_ident(table), self.pk_predicate(table, 0));
+        if returning {
+            sql.push_str(self.returning_clause());
+        }
+        sql
+    }
+
+    /// The `RETURNING *` suffix; MySQL has no `RETURNING`, so callers there
+    /// fall back to a follow-up SELECT.
+    fn returning_clause(&self) -> &'static str {
+        match self.dialect {
+            Dialect::Postgres | Dialect::Sqlite => " RETURNING *",
+            Dialect::MySql => "",
+        }
+    }
+
+    fn pk_predicate(&self, table: &Table, offset: usize) -> String {
+        table
+            .primary_key
+            .iter()
+            .enumerate()
+            .map(|(i, pk)| format!("{} = {}", pk, self.placeholder(offset + i + 1)))
+            .collect::<Vec<_>>()
+            .join(" AND ")
+    }
+
+    /// The parameter placeholder for the `n`-th (1-based) bind parameter.
+    pub fn placeholder(&self, n: usize) -> String {
+        match self.dialect {
+            Dialect::Postgres => format!("${}", n),
+            Dialect::MySql | Dialect::Sqlite => "?".to_string(),
+        }
+    }
+}
+
+/// Binding power of a binary operator in the rendered SQL, loosest first.
+fn sql_precedence(op: BinaryOpKind) -> u8 {
+    match op {
+        BinaryOpKind::Or => 1,
+        BinaryOpKind::And => 2,
+        BinaryOpKind::Eq | BinaryOpKind::NotEq => 3,
+        BinaryOpKind::Lt | BinaryOpKind::Le | BinaryOpKind::Gt | BinaryOpKind::Ge => 4,
+        BinaryOpKind::Add | BinaryOpKind::Sub => 5,
+        BinaryOpKind::Mul | BinaryOpKind::Div | BinaryOpKind::Mod => 6,
+    }
+}
+
+fn binary_op_sql(op: BinaryOpKind) -> &'static str {
+    match op {
+        BinaryOpKind::Add => "+",
+        BinaryOpKind::Sub => "-",
+        BinaryOpKind::Mul => "*",
+        BinaryOpKind::Div => "/",
+        BinaryOpKind::Mod => "%",
+        BinaryOpKind::Eq => "=",
+        BinaryOpKind::NotEq => "<>",
+        BinaryOpKind::Lt => "<",
+        BinaryOpKind::Le => "<=",
+        BinaryOpKind::Gt => ">",
+        BinaryOpKind::Ge => ">=",
+        BinaryOpKind::And => "AND",
+        BinaryOpKind::Or => "OR",
+    }
+}
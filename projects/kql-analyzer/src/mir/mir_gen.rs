@@ -0,0 +1,973 @@
+//! Lowering from the HIR to the MIR: structs become tables, fields become
+//! columns, keys become constraints and `let` queries become [MirQuery]s.
+
+use super::*;
+use crate::hir::{
+    DeclId, EnumLayout, HirAttribute, HirEnum, HirExpr, HirExprKind, HirField, HirLiteral, HirProgram, HirQuery, HirQueryOp,
+    HirSeed, HirStruct, HirType, PrimitiveType,
+};
+use kql_types::{KqlError, RelationsConfig, Result};
+
+/// Lowers a [HirProgram] into a [MirProgram].
+#[derive(Debug)]
+pub struct MirLowerer {
+    hir: HirProgram,
+    mir: MirProgram,
+    errors: Vec<KqlError>,
+    default_on_delete: Option<RefAction>,
+    default_on_update: Option<RefAction>,
+    table_prefix: Option<String>,
+}
+
+impl MirLowerer {
+    /// Create a lowerer over a checked program.
+    pub fn new(hir: HirProgram) -> Self {
+        Self {
+            hir,
+            mir: MirProgram::default(),
+            errors: Vec::new(),
+            default_on_delete: None,
+            default_on_update: None,
+            table_prefix: None,
+        }
+    }
+
+    /// Apply the `[relations]` config defaults to every foreign key that does
+    /// not declare its own referential action. Unknown action keywords are
+    /// ignored, matching how `@relation` treats them.
+    pub fn with_relation_defaults(mut self, config: &RelationsConfig) -> Self {
+        self.default_on_delete = config.default_on_delete.as_deref().and_then(RefAction::from_keyword);
+        self.default_on_update = config.default_on_update.as_deref().and_then(RefAction::from_keyword);
+        self
+    }
+
+    /// Prepend `prefix` to every derived table name, for multi-tenant setups
+    /// that keep tenants apart by name. The prefix is used verbatim — pass
+    /// `app_` for `app_users` — and an explicit `@table` name is left alone.
+    pub fn with_table_prefix(mut self, prefix: &str) -> Self {
+        self.table_prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// Lower the whole program.
+    pub fn lower(mut self) -> Result<MirProgram> {
+        let struct_ids: Vec<DeclId> = self.hir.structs.keys().copied().collect();
+        for id in struct_ids {
+            let item = self.hir.structs[&id].clone();
+            if has_json_layout(&item) {
+                continue;
+            }
+            let table = self.lower_table(&item);
+            self.mir.tables.insert(item.full_name.clone(), table);
+        }
+        self.check_table_name_collisions();
+        let let_ids: Vec<DeclId> = self.hir.lets.keys().copied().collect();
+        for id in let_ids {
+            let item = self.hir.lets[&id].clone();
+            if let Some(query) = self.process_query_expr(&item) {
+                self.mir.queries.push(query);
+            }
+        }
+        for seed in self.hir.seeds.clone() {
+            if let Some(lowered) = self.lower_seed(&seed) {
+                self.mir.seeds.push(lowered);
+            }
+        }
+        if !self.errors.is_empty() {
+            return Err(self.errors[0].clone());
+        }
+        Ok(self.mir)
+    }
+
+    /// Tables are keyed by struct full name, so two structs can still end up
+    /// mapped to one physical `(schema, table)` — via `@table` or matching
+    /// snake-cased names — and the later one would silently win.
+    fn check_table_name_collisions(&mut self) {
+        let mut seen: std::collections::HashMap<(Option<String>, String), String> = std::collections::HashMap::new();
+        for table in self.mir.tables.values() {
+            let key = (table.schema.clone(), table.name.clone());
+            if let Some(existing) = seen.insert(key, table.struct_name.clone()) {
+                let message = format!("`{}` and `{}` both map to table `{}`", existing, table.struct_name, table.name);
+                self.errors.push(KqlError::semantic(message, table.span));
+            }
+        }
+    }
+
+    fn lower_table(&mut self, item: &HirStruct) -> Table {
+        let name = self.table_name(item);
+        let schema = if item.namespace.is_empty() { None } else { Some(to_snake_case(&item.namespace.join("_"))) };
+        let mut table = Table {
+            schema,
+            name,
+            struct_name: item.full_name.clone(),
+            columns: Vec::new(),
+            primary_key: Vec::new(),
+            indexes: Vec::new(),
+            foreign_keys: Vec::new(),
+            relations: Vec::new(),
+            deprecated: deprecation_note(item.attribute("deprecated")),
+            temporal: false,
+            span: item.span,
+        };
+        let mut field_primary_keys = Vec::new();
+        for field in &item.fields {
+            self.lower_field(item, field, &mut table, &mut field_primary_keys);
+        }
+        for attr in &item.attributes {
+            match attr.name.as_str() {
+                "audit" => {
+                    table.columns.push(plain_column("created_at", MirType::DateTime, item.span));
+                    table.columns.push(plain_column("updated_at", MirType::DateTime, item.span));
+                }
+                "soft_delete" => {
+                    let mut column = plain_column("deleted_at", MirType::DateTime, item.span);
+                    column.nullable = true;
+                    table.columns.push(column);
+                }
+                "temporal" => {
+                    table.columns.push(plain_column("valid_from", MirType::DateTime, item.span));
+                    let mut valid_to = plain_column("valid_to", MirType::DateTime, item.span);
+                    valid_to.nullable = true;
+                    table.columns.push(valid_to);
+                    table.temporal = true;
+                }
+                "index" => {
+                    if let Some(index) = self.lower_index_attribute(&table, item, attr) {
+                        table.indexes.push(index);
+                    }
+                }
+                _ => {}
+            }
+        }
+        let auto: Vec<&Column> = table.columns.iter().filter(|c| c.auto_increment).collect();
+        for column in &auto {
+            if !is_integer_type(&column.ty) {
+                let message = format!("`@auto_increment` on `{}` requires an integer column type", column.name);
+                self.errors.push(KqlError::semantic(message, column.span));
+            }
+        }
+        if auto.len() > 1 {
+            // MySQL allows a single auto-increment column per table.
+            let message = format!("table `{}` has more than one `@auto_increment` column", table.name);
+            self.errors.push(KqlError::semantic(message, auto[1].span));
+        }
+        // An explicit struct-level `@primary_key(a, b)` takes precedence over
+        // field-level markers.
+        let struct_pk = self.struct_primary_key(item);
+        table.primary_key = if struct_pk.is_empty() { field_primary_keys } else { struct_pk };
+        table
+    }
+
+    fn table_name(&mut self, item: &HirStruct) -> String {
+        match item.attribute("table").and_then(|a| a.first_arg()) {
+            Some(HirExpr { kind: HirExprKind::Literal(HirLiteral::String(name)), .. }) => name.clone(),
+            Some(expr) => {
+                self.errors.push(KqlError::semantic("`@table` expects a string literal", expr.span));
+                self.prefixed(to_snake_case(&item.name))
+            }
+            None => self.prefixed(to_snake_case(&item.name)),
+        }
+    }
+
+    fn prefixed(&self, name: String) -> String {
+        match &self.table_prefix {
+            Some(prefix) => format!("{}{}", prefix, name),
+            None => name,
+        }
+    }
+
+    fn struct_primary_key(&mut self, item: &HirStruct) -> Vec<String> {
+        let Some(attr) = item.attribute("primary_key") else {
+            return Vec::new();
+        };
+        let mut columns = Vec::new();
+        for arg in &attr.args {
+            match &arg.value.kind {
+                HirExprKind::Variable(name) => columns.push(name.clone()),
+                _ => self.errors.push(KqlError::semantic("`@primary_key` expects column names", arg.value.span)),
+            }
+        }
+        columns
+    }
+
+    /// Lower a fixture row into concrete columns and values, checking that
+    /// every omitted column can legally be absent.
+    fn lower_seed(&mut self, seed: &HirSeed) -> Option<MirSeed> {
+        let item = self.hir.structs.get(&seed.struct_id)?.clone();
+        let Some(table) = self.mir.tables.get(&item.full_name).cloned() else {
+            self.errors.push(KqlError::semantic(format!("`{}` has no table to seed", item.name), seed.span));
+            return None;
+        };
+        let mut columns = Vec::new();
+        let mut values = Vec::new();
+        for (name, value) in &seed.values {
+            // Foreign key fields store into their `_id` column.
+            let column_name = if table.column(name).is_some() { name.clone() } else { format!("{}_id", name) };
+            if table.column(&column_name).is_none() {
+                self.errors.push(KqlError::semantic(format!("field `{}` has no column to seed", name), seed.span));
+                continue;
+            }
+            let Some(value) = self.seed_value(&item, name, value) else {
+                continue;
+            };
+            columns.push(column_name);
+            values.push(value);
+        }
+        for column in &table.columns {
+            if columns.contains(&column.name)
+                || column.nullable
+                || column.auto_increment
+                || column.default.is_some()
+                || column.generated.is_some()
+            {
+                continue;
+            }
+            let message = format!("seed for `{}` is missing a value for column `{}`", item.name, column.name);
+            self.errors.push(KqlError::semantic(message, seed.span));
+        }
+        Some(MirSeed { table: item.full_name.clone(), columns, values })
+    }
+
+    /// Convert a checked seed expression into a stored value.
+    fn seed_value(&mut self, item: &HirStruct, field_name: &str, value: &HirExpr) -> Option<MirValue> {
+        match &value.kind {
+            HirExprKind::Literal(HirLiteral::Int(v)) => return Some(MirValue::Int(*v)),
+            HirExprKind::Literal(HirLiteral::Float(v)) => return Some(MirValue::Float(*v)),
+            HirExprKind::Literal(HirLiteral::String(v)) => return Some(MirValue::Text(v.clone())),
+            HirExprKind::Literal(HirLiteral::Bool(v)) => return Some(MirValue::Bool(*v)),
+            HirExprKind::Variable(variant) => {
+                // An enum variant, stored according to the enum's layout.
+                let mut ty = item.field(field_name).map(|f| &f.ty);
+                while let Some(HirType::Optional(inner) | HirType::Key { ty: inner, .. }) = ty {
+                    ty = Some(inner);
+                }
+                if let Some(HirType::Enum(id)) = ty {
+                    let item = &self.hir.enums[id];
+                    if let Some(found) = item.variant(variant) {
+                        return Some(match item.layout {
+                            EnumLayout::String => MirValue::Text(found.name.clone()),
+                            EnumLayout::Int => MirValue::Int(found.value),
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+        self.errors.push(KqlError::semantic(format!("seed value for `{}` must be a literal", field_name), value.span));
+        None
+    }
+
+    fn lower_field(&mut self, item: &HirStruct, field: &HirField, table: &mut Table, field_primary_keys: &mut Vec<String>) {
+        // `@virtual` fields are computed in application code: they stay in the
+        // HIR for type checking and codegen but never become a column, key,
+        // constraint, or relation.
+        if field.has_attribute("virtual") {
+            return;
+        }
+        match &field.ty {
+            HirType::Key { entity, ty } => {
+                let column_name = field.name.clone();
+                let Some(ty) = self.mir_type(field, ty) else {
+                    return;
+                };
+                let mut column = self.build_column(field, column_name.clone(), ty, false);
+                column.nullable = false;
+                table.columns.push(column);
+                match entity {
+                    Some(entity) if *entity != item.id => {
+                        // The `ref_id` path: a key into another entity becomes a
+                        // foreign key constraint on this table.
+                        let (ref_table, ref_column) = self.referenced_key(*entity);
+                        let (on_delete, on_update) = self.relation_actions(field);
+                        table.foreign_keys.push(ForeignKey {
+                            name: shorten_identifier(format!("{}_{}_fkey", table.name, column_name)),
+                            columns: vec![column_name],
+                            ref_table,
+                            ref_columns: vec![ref_column],
+                            on_delete,
+                            on_update,
+                            deferrable: relation_deferrable(field),
+                        });
+                    }
+                    _ => field_primary_keys.push(column_name),
+                }
+            }
+            HirType::ForeignKey { name, entity } => {
+                self.lower_foreign_key_field(field, name.clone(), *entity, table, false);
+            }
+            HirType::List(inner) => match inner.as_ref() {
+                HirType::ForeignKey { name, entity } => {
+                    let (ref_table, _) = self.referenced_key(*entity);
+                    // A list relation mirrored by a list on the other side is
+                    // a many-to-many pairing; one-sided lists stay one-to-many.
+                    let paired = self.hir.structs.get(entity).is_some_and(|target| {
+                        target.fields.iter().any(|f| match &f.ty {
+                            HirType::List(inner) => {
+                                matches!(inner.as_ref(), HirType::ForeignKey { entity: back, .. } if *back == item.id)
+                            }
+                            _ => false,
+                        })
+                    });
+                    let cardinality = if paired { Cardinality::ManyToMany } else { Cardinality::OneToMany };
+                    table.relations.push(Relation {
+                        name: name.clone().unwrap_or_else(|| field.name.clone()),
+                        from_table: table.name.clone(),
+                        to_table: ref_table,
+                        fk_column: None,
+                        is_list: true,
+                        // A list side never guarantees a row on the other end.
+                        required: false,
+                        cardinality,
+                    });
+                }
+                _ => {
+                    let column = self.build_column(field, field.name.clone(), MirType::Json, false);
+                    table.columns.push(column);
+                }
+            },
+            HirType::Optional(inner) => match inner.as_ref() {
+                // `ForeignKey<T>?`: the column becomes nullable but the FK
+                // constraint and relation are generated like the required form.
+                HirType::ForeignKey { name, entity } => {
+                    self.lower_foreign_key_field(field, name.clone(), *entity, table, true);
+                }
+                _ => {
+                    if let Some(ty) = self.mir_type(field, inner) {
+                        let column = self.build_column(field, field.name.clone(), ty, true);
+                        table.columns.push(column);
+                    }
+                }
+            },
+            HirType::Unknown => {}
+            other => {
+                if let Some(ty) = self.mir_type(field, other) {
+                    let column = self.build_column(field, field.name.clone(), ty, false);
+                    table.columns.push(column);
+                    if field.has_attribute("primary_key") {
+                        field_primary_keys.push(field.name.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Lower a `ForeignKey<T>` field into its `_id` column, constraint and
+    /// relation. `ForeignKey<author: User>` names the relation and column
+    /// after `author`; otherwise the field name is used.
+    fn lower_foreign_key_field(
+        &mut self,
+        field: &HirField,
+        name: Option<String>,
+        entity: DeclId,
+        table: &mut Table,
+        nullable: bool,
+    ) {
+        let relation_name = name.unwrap_or_else(|| field.name.clone());
+        let column_name = format!("{}_id", relation_name);
+        let (ref_table, ref_column) = self.referenced_key(entity);
+        let ty = self.referenced_key_type(entity);
+        let column = self.build_column(field, column_name.clone(), ty, nullable);
+        table.columns.push(column);
+        let (on_delete, on_update) = self.relation_actions(field);
+        table.foreign_keys.push(ForeignKey {
+            name: shorten_identifier(format!("{}_{}_fkey", table.name, column_name)),
+            columns: vec![column_name.clone()],
+            ref_table: ref_table.clone(),
+            ref_columns: vec![ref_column],
+            on_delete,
+            on_update,
+            deferrable: relation_deferrable(field),
+        });
+        // A unique FK column admits at most one owning row per target.
+        let cardinality = if field.has_attribute("unique") { Cardinality::OneToOne } else { Cardinality::OneToMany };
+        let required = matches!(
+            field.attribute("relation").and_then(|a| a.named_arg("required")).map(|e| &e.kind),
+            Some(HirExprKind::Literal(HirLiteral::Bool(true)))
+        );
+        table.relations.push(Relation {
+            name: relation_name,
+            from_table: table.name.clone(),
+            to_table: ref_table,
+            fk_column: Some(column_name),
+            is_list: false,
+            required,
+            cardinality,
+        });
+    }
+
+    /// Map a HIR type to a column type, reporting an error when the type
+    /// cannot be stored in a single column.
+    fn mir_type(&mut self, field: &HirField, ty: &HirType) -> Option<MirType> {
+        let mapped = match ty {
+            HirType::Primitive(p) => primitive_type(*p, field),
+            HirType::Enum(id) => {
+                let item = self.hir.enums[id].clone();
+                self.register_enum(&item);
+                MirType::Enum(item.full_name)
+            }
+            HirType::Struct(id) => {
+                let target = &self.hir.structs[id];
+                if has_json_layout(target) {
+                    MirType::Json
+                } else {
+                    let message = format!("struct `{}` must be referenced through `ForeignKey`", target.name);
+                    self.errors.push(KqlError::semantic(message, field.span));
+                    return None;
+                }
+            }
+            HirType::List(_) | HirType::Tuple(_) => MirType::Json,
+            _ => {
+                self.errors
+                    .push(KqlError::semantic(format!("field `{}` is not representable as a column", field.name), field.span));
+                return None;
+            }
+        };
+        Some(mapped)
+    }
+
+    fn register_enum(&mut self, item: &HirEnum) {
+        if self.mir.enums.contains_key(&item.full_name) {
+            return;
+        }
+        let lowered = MirEnum {
+            name: to_snake_case(&item.name),
+            variants: item.variants.iter().map(|v| (v.name.clone(), v.value)).collect(),
+            string_layout: item.layout == EnumLayout::String,
+        };
+        self.mir.enums.insert(item.full_name.clone(), lowered);
+    }
+
+    fn build_column(&mut self, field: &HirField, name: String, ty: MirType, nullable: bool) -> Column {
+        let mut column = Column {
+            name,
+            ty,
+            nullable,
+            default: None,
+            auto_increment: field.has_attribute("auto_increment"),
+            unique: field.has_attribute("unique"),
+            unique_nulls_not_distinct: false,
+            db_type: None,
+            db_type_dialect: None,
+            generated: None,
+            collation: None,
+            comment: if field.docs.is_empty() { None } else { Some(field.docs.join("\n")) },
+            deprecated: deprecation_note(field.attribute("deprecated")),
+            span: field.span,
+        };
+        if let Some(attr) = field.attribute("default") {
+            column.default = attr.first_arg().and_then(|expr| self.default_value(field, expr));
+        }
+        if let Some(attr) = field.attribute("generated") {
+            match attr.first_arg().map(|e| &e.kind) {
+                Some(HirExprKind::Literal(HirLiteral::String(expr))) => column.generated = Some(expr.clone()),
+                _ => self.errors.push(KqlError::semantic("`@generated` expects a SQL expression string", attr.span)),
+            }
+        }
+        if let Some(attr) = field.attribute("unique") {
+            match attr.named_arg("nulls_not_distinct").map(|e| &e.kind) {
+                Some(HirExprKind::Literal(HirLiteral::Bool(value))) => column.unique_nulls_not_distinct = *value,
+                Some(_) => self.errors.push(KqlError::semantic("`nulls_not_distinct:` expects `true` or `false`", attr.span)),
+                None => {}
+            }
+        }
+        if let Some(attr) = field.attribute("db_type") {
+            match attr.first_arg().map(|e| &e.kind) {
+                Some(HirExprKind::Literal(HirLiteral::String(raw))) => column.db_type = Some(raw.clone()),
+                _ => self.errors.push(KqlError::semantic("`@db_type` expects a SQL type string", attr.span)),
+            }
+            match attr.named_arg("dialect").map(|e| (&e.kind, e.span)) {
+                Some((HirExprKind::Literal(HirLiteral::String(name)), span)) => {
+                    if name.parse::<crate::lir::Dialect>().is_ok() {
+                        column.db_type_dialect = Some(name.clone());
+                    } else {
+                        self.errors.push(KqlError::semantic(format!("unknown dialect `{}`", name), span));
+                    }
+                }
+                Some((_, span)) => {
+                    self.errors.push(KqlError::semantic("`dialect:` expects a dialect name string", span));
+                }
+                None => {}
+            }
+        }
+        if let Some(attr) = field.attribute("collate") {
+            match attr.first_arg().map(|e| &e.kind) {
+                Some(HirExprKind::Literal(HirLiteral::String(name))) => {
+                    if column.ty == MirType::Text {
+                        column.collation = Some(name.clone());
+                    } else {
+                        self.errors.push(KqlError::semantic("`@collate` only applies to string columns", attr.span));
+                    }
+                }
+                _ => self.errors.push(KqlError::semantic("`@collate` expects a collation name string", attr.span)),
+            }
+        }
+        if let Some(attr) = field.attribute("precision") {
+            if let MirType::Decimal { precision } = &mut column.ty {
+                *precision = extract_precision(attr);
+            }
+        }
+        column
+    }
+
+    /// Resolve a `@default(..)` argument: a literal, or a variant name when
+    /// the field has an enum type, stored according to the enum's layout.
+    fn default_value(&mut self, field: &HirField, expr: &HirExpr) -> Option<MirValue> {
+        if let Some(value) = extract_default(expr) {
+            return Some(value);
+        }
+        if let HirExprKind::Call { func, args } = &expr.kind {
+            // `gen_random_uuid()` is the Postgres spelling of the same
+            // generator; both normalize to the dialect-rendered `uuid`.
+            if matches!(func.as_str(), "uuid" | "gen_random_uuid") && args.is_empty() {
+                if !field_has_primitive(field, PrimitiveType::Uuid) {
+                    let message = format!("`{}()` returns a UUID; the column must have type `Uuid`", func);
+                    self.errors.push(KqlError::semantic(message, expr.span));
+                    return None;
+                }
+                return Some(MirValue::Function("uuid".to_string()));
+            }
+            if func == "now" && args.is_empty() {
+                return Some(MirValue::Function(func.clone()));
+            }
+            self.errors.push(KqlError::semantic(format!("`{}` cannot be used as a column default", func), expr.span));
+            return None;
+        }
+        let HirExprKind::Variable(variant) = &expr.kind else {
+            return None;
+        };
+        let mut ty = &field.ty;
+        while let HirType::Optional(inner) | HirType::Key { ty: inner, .. } = ty {
+            ty = inner;
+        }
+        let HirType::Enum(id) = ty else {
+            return None;
+        };
+        let item = self.hir.enums[id].clone();
+        match item.variant(variant) {
+            Some(found) => Some(match item.layout {
+                EnumLayout::String => MirValue::Text(found.name.clone()),
+                EnumLayout::Int => MirValue::Int(found.value),
+            }),
+            None => {
+                self.errors.push(KqlError::semantic(format!("`{}` is not a variant of `{}`", variant, item.name), expr.span));
+                None
+            }
+        }
+    }
+
+    fn relation_actions(&mut self, field: &HirField) -> (Option<RefAction>, Option<RefAction>) {
+        let action = |expr: Option<&HirExpr>| match expr.map(|e| &e.kind) {
+            Some(HirExprKind::Variable(keyword)) => RefAction::from_keyword(keyword),
+            _ => None,
+        };
+        // Actions come from `@relation(on_delete: ..)` or the standalone
+        // `@on_delete(..)` / `@on_update(..)` forms; standalone attributes win.
+        let (mut on_delete, mut on_update) = match field.attribute("relation") {
+            Some(attr) => (action(attr.named_arg("on_delete")), action(attr.named_arg("on_update"))),
+            None => (None, None),
+        };
+        if let Some(attr) = field.attribute("on_delete") {
+            on_delete = action(attr.first_arg());
+        }
+        if let Some(attr) = field.attribute("on_update") {
+            on_update = action(attr.first_arg());
+        }
+        (on_delete.or(self.default_on_delete), on_update.or(self.default_on_update))
+    }
+
+    /// The physical table and primary key column a struct id is referenced by.
+    fn referenced_key(&mut self, entity: DeclId) -> (String, String) {
+        let item = self.hir.structs[&entity].clone();
+        let table = self.table_name(&item);
+        let column = item
+            .fields
+            .iter()
+            .find(|f| matches!(f.ty, HirType::Key { .. }))
+            .map(|f| f.name.clone())
+            .unwrap_or_else(|| "id".to_string());
+        (table, column)
+    }
+
+    fn referenced_key_type(&mut self, entity: DeclId) -> MirType {
+        let item = self.hir.structs[&entity].clone();
+        for field in &item.fields {
+            if let HirType::Key { ty, .. } = &field.ty {
+                if let Some(ty) = self.mir_type(field, ty) {
+                    return ty;
+                }
+            }
+        }
+        MirType::I64
+    }
+
+    fn lower_index_attribute(&mut self, table: &Table, item: &HirStruct, attr: &HirAttribute) -> Option<Index> {
+        let Some(arg) = attr.named_arg("columns").or_else(|| attr.first_arg()) else {
+            self.errors.push(KqlError::semantic("`@index` expects a `columns:` list", attr.span));
+            return None;
+        };
+        let mut columns = Vec::new();
+        match &arg.kind {
+            HirExprKind::List(items) => {
+                for item in items {
+                    match self.index_column(item) {
+                        Some(column) => columns.push(column),
+                        None => self.errors.push(KqlError::semantic(
+                            "`@index` expects column names or expressions, optionally with `.asc()` or `.desc()`",
+                            item.span,
+                        )),
+                    }
+                }
+            }
+            _ => match self.index_column(arg) {
+                Some(column) => columns.push(column),
+                None => {
+                    self.errors.push(KqlError::semantic("`@index` expects a `columns:` list", attr.span));
+                    return None;
+                }
+            },
+        }
+        for column in &columns {
+            let is_virtual = |f: &HirField| f.name == column.name && f.has_attribute("virtual");
+            if !column.expr && item.fields.iter().any(is_virtual) {
+                let message = format!("`@index` cannot use `{}`: `@virtual` fields have no column", column.name);
+                self.errors.push(KqlError::semantic(message, attr.span));
+            }
+        }
+        let unique = matches!(attr.named_arg("unique").map(|e| &e.kind), Some(HirExprKind::Literal(HirLiteral::Bool(true))));
+        let method = match attr.named_arg("using").map(|e| (&e.kind, e.span)) {
+            Some((HirExprKind::Literal(HirLiteral::String(name)), span)) => {
+                if INDEX_METHODS.contains(&name.as_str()) {
+                    Some(name.clone())
+                } else {
+                    let message = format!("unknown index method `{}`; expected one of {}", name, INDEX_METHODS.join(", "));
+                    self.errors.push(KqlError::semantic(message, span));
+                    None
+                }
+            }
+            Some((_, span)) => {
+                self.errors.push(KqlError::semantic("`using:` expects an index method string", span));
+                None
+            }
+            None => None,
+        };
+        let name = shorten_identifier(format!(
+            "{}_{}_idx",
+            table.name,
+            columns.iter().map(index_name_fragment).collect::<Vec<_>>().join("_")
+        ));
+        Some(Index { name, columns, unique, method })
+    }
+
+    /// Parse one `@index` column entry: a bare name, an expression like
+    /// `lower(email)`, either optionally wrapped in `.asc()` / `.desc()`.
+    fn index_column(&self, expr: &HirExpr) -> Option<IndexColumn> {
+        match &expr.kind {
+            HirExprKind::Variable(name) => Some(IndexColumn { name: name.clone(), expr: false, order: None }),
+            HirExprKind::Call { func, args } => {
+                if let order @ Some(_) = match func.as_str() {
+                    "asc" => Some(IndexOrder::Asc),
+                    "desc" => Some(IndexOrder::Desc),
+                    _ => None,
+                } {
+                    return match args.as_slice() {
+                        [inner] => self.index_column(inner).map(|column| IndexColumn { order, ..column }),
+                        _ => None,
+                    };
+                }
+                Some(IndexColumn { name: render_index_expr(expr)?, expr: true, order: None })
+            }
+            _ => None,
+        }
+    }
+
+    fn process_query_expr(&mut self, item: &crate::hir::HirLet) -> Option<MirQuery> {
+        let HirQuery { source, ops } = &item.query;
+        let source = (*source)?;
+        let table = self.table_name(&self.hir.structs[&source].clone());
+        let mut query = MirQuery {
+            name: short_name(&item.full_name),
+            table,
+            filter: None,
+            order_by: Vec::new(),
+            projections: Vec::new(),
+            limit: None,
+            offset: None,
+        };
+        for op in ops {
+            match op {
+                HirQueryOp::Filter(predicate) => {
+                    let predicate = self.lower_mir_expr(predicate);
+                    query.filter = Some(match query.filter.take() {
+                        Some(existing) => {
+                            MirExpr::Binary { op: BinaryOpKind::And, lhs: Box::new(existing), rhs: Box::new(predicate) }
+                        }
+                        None => predicate,
+                    });
+                }
+                HirQueryOp::Sort(keys) => {
+                    query.order_by.extend(keys.iter().map(|k| MirOrderBy {
+                        column: k.field.clone(),
+                        desc: k.desc,
+                        nulls: k.nulls,
+                    }));
+                }
+                HirQueryOp::Map(projections) => {
+                    query.projections.extend(projections.iter().map(|p| match &p.alias {
+                        Some(alias) => MirProjection::Alias(alias.clone(), MirExpr::Column(p.field.clone())),
+                        None => MirProjection::Column(p.field.clone()),
+                    }));
+                }
+                HirQueryOp::Limit(value) => query.limit = Some(*value),
+                HirQueryOp::Offset(value) => query.offset = Some(*value),
+            }
+        }
+        Some(query)
+    }
+
+    fn lower_mir_expr(&mut self, expr: &HirExpr) -> MirExpr {
+        match &expr.kind {
+            HirExprKind::Literal(HirLiteral::Int(value)) => MirExpr::Value(MirValue::Int(*value)),
+            HirExprKind::Literal(HirLiteral::Float(value)) => MirExpr::Value(MirValue::Float(*value)),
+            HirExprKind::Literal(HirLiteral::String(value)) => MirExpr::Value(MirValue::Text(value.clone())),
+            HirExprKind::Literal(HirLiteral::Bool(value)) => MirExpr::Value(MirValue::Bool(*value)),
+            HirExprKind::Literal(HirLiteral::Star) => MirExpr::Star,
+            HirExprKind::ContextField(name) => MirExpr::Column(name.clone()),
+            HirExprKind::Variable(name) => MirExpr::Value(MirValue::Text(name.clone())),
+            HirExprKind::Unary { op, expr } => MirExpr::Unary { op: *op, expr: Box::new(self.lower_mir_expr(expr)) },
+            HirExprKind::Binary { op, lhs, rhs } => {
+                MirExpr::Binary { op: *op, lhs: Box::new(self.lower_mir_expr(lhs)), rhs: Box::new(self.lower_mir_expr(rhs)) }
+            }
+            HirExprKind::Call { func, args } => {
+                MirExpr::Call { func: func.clone(), args: args.iter().map(|a| self.lower_mir_expr(a)).collect() }
+            }
+            HirExprKind::If { cond, then_branch, else_branch } => MirExpr::Case {
+                when: Box::new(self.lower_mir_expr(cond)),
+                then: Box::new(self.lower_mir_expr(then_branch)),
+                otherwise: Box::new(self.lower_mir_expr(else_branch)),
+            },
+            HirExprKind::Member { .. } => match self.json_access(expr) {
+                Some(access) => access,
+                None => {
+                    self.errors
+                        .push(KqlError::semantic("member access is only supported through `@layout(json)` fields", expr.span));
+                    MirExpr::Star
+                }
+            },
+            _ => {
+                self.errors.push(KqlError::semantic("expression cannot be translated to SQL", expr.span));
+                MirExpr::Star
+            }
+        }
+    }
+
+    /// Fold `$.field.a.b` into a JSON path access when `field` is a column
+    /// holding a `@layout(json)` struct.
+    fn json_access(&self, expr: &HirExpr) -> Option<MirExpr> {
+        let mut path = Vec::new();
+        let mut cursor = expr;
+        while let HirExprKind::Member { base, name } = &cursor.kind {
+            path.push(name.clone());
+            cursor = base;
+        }
+        path.reverse();
+        let HirExprKind::ContextField(column) = &cursor.kind else {
+            return None;
+        };
+        let HirType::Struct(id) = &cursor.ty else {
+            return None;
+        };
+        if !has_json_layout(self.hir.structs.get(id)?) {
+            return None;
+        }
+        Some(MirExpr::JsonAccess { column: column.clone(), path })
+    }
+}
+
+fn has_json_layout(item: &HirStruct) -> bool {
+    item.attribute("layout")
+        .and_then(|a| a.first_arg())
+        .is_some_and(|e| matches!(&e.kind, HirExprKind::Variable(n) if n == "json"))
+}
+
+fn plain_column(name: &str, ty: MirType, span: kql_types::Span) -> Column {
+    Column {
+        name: name.to_string(),
+        ty,
+        nullable: false,
+        default: None,
+        auto_increment: false,
+        unique: false,
+        unique_nulls_not_distinct: false,
+        db_type: None,
+        db_type_dialect: None,
+        generated: None,
+        collation: None,
+        comment: None,
+        deprecated: None,
+        span,
+    }
+}
+
+/// Render an `@index` expression entry as raw SQL text: nested calls over
+/// column names and literals, nothing else.
+fn render_index_expr(expr: &HirExpr) -> Option<String> {
+    match &expr.kind {
+        HirExprKind::Variable(name) => Some(name.clone()),
+        HirExprKind::Literal(HirLiteral::Int(value)) => Some(value.to_string()),
+        HirExprKind::Literal(HirLiteral::String(value)) => Some(format!("'{}'", value.replace('\'', "''"))),
+        HirExprKind::Call { func, args } => {
+            let args: Option<Vec<String>> = args.iter().map(render_index_expr).collect();
+            Some(format!("{}({})", func, args?.join(", ")))
+        }
+        _ => None,
+    }
+}
+
+/// The piece an index column contributes to the generated index name: the
+/// column itself, or the identifier characters of an expression, so
+/// `lower(email)` yields `lower_email`.
+fn index_name_fragment(column: &IndexColumn) -> String {
+    if !column.expr {
+        return column.name.clone();
+    }
+    column.name.split(|c: char| !(c.is_alphanumeric() || c == '_')).filter(|s| !s.is_empty()).collect::<Vec<_>>().join("_")
+}
+
+/// The note carried by a `@deprecated` attribute; empty when the attribute has
+/// no string argument, `None` when the attribute is absent.
+fn deprecation_note(attr: Option<&HirAttribute>) -> Option<String> {
+    let attr = attr?;
+    match attr.first_arg().map(|e| &e.kind) {
+        Some(HirExprKind::Literal(HirLiteral::String(note))) => Some(note.clone()),
+        _ => Some(String::new()),
+    }
+}
+
+fn primitive_type(p: PrimitiveType, _field: &HirField) -> MirType {
+    match p {
+        PrimitiveType::I8 => MirType::I8,
+        PrimitiveType::I16 => MirType::I16,
+        PrimitiveType::I32 => MirType::I32,
+        PrimitiveType::I64 => MirType::I64,
+        PrimitiveType::U8 => MirType::U8,
+        PrimitiveType::U16 => MirType::U16,
+        PrimitiveType::U32 => MirType::U32,
+        PrimitiveType::U64 => MirType::U64,
+        PrimitiveType::F32 => MirType::F32,
+        PrimitiveType::F64 => MirType::F64,
+        PrimitiveType::D128 => MirType::Decimal { precision: None },
+        PrimitiveType::Bool => MirType::Bool,
+        PrimitiveType::String => MirType::Text,
+        PrimitiveType::DateTime => MirType::DateTime,
+        PrimitiveType::Date => MirType::Date,
+        PrimitiveType::Time => MirType::Time,
+        PrimitiveType::Uuid => MirType::Uuid,
+        PrimitiveType::Json => MirType::Json,
+    }
+}
+
+fn extract_default(expr: &HirExpr) -> Option<MirValue> {
+    match &expr.kind {
+        HirExprKind::Literal(HirLiteral::Int(value)) => Some(MirValue::Int(*value)),
+        HirExprKind::Literal(HirLiteral::Float(value)) => Some(MirValue::Float(*value)),
+        HirExprKind::Literal(HirLiteral::String(value)) => Some(MirValue::Text(value.clone())),
+        HirExprKind::Literal(HirLiteral::Bool(value)) => Some(MirValue::Bool(*value)),
+        // `@default(-1)` parses as a unary negation of a literal.
+        HirExprKind::Unary { op: UnaryOpKind::Neg, expr } => match extract_default(expr)? {
+            MirValue::Int(value) => Some(MirValue::Int(-value)),
+            MirValue::Float(value) => Some(MirValue::Float(-value)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn extract_precision(attr: &HirAttribute) -> Option<(u32, u32)> {
+    let int_arg = |index: usize| match attr.args.get(index).map(|a| &a.value.kind) {
+        Some(HirExprKind::Literal(HirLiteral::Int(value))) if *value >= 0 => Some(*value as u32),
+        _ => None,
+    };
+    Some((int_arg(0)?, int_arg(1).unwrap_or(0)))
+}
+
+/// The portable identifier budget: Postgres truncates identifiers at 63
+/// bytes and MySQL rejects names over 64, so generated names stay within the
+/// stricter bound.
+const MAX_IDENTIFIER_LEN: usize = 63;
+
+/// Whether the field asks for a deferrable constraint via
+/// `@relation(deferrable: true)`.
+fn relation_deferrable(field: &HirField) -> bool {
+    matches!(
+        field.attribute("relation").and_then(|a| a.named_arg("deferrable")).map(|e| &e.kind),
+        Some(HirExprKind::Literal(HirLiteral::Bool(true)))
+    )
+}
+
+/// Whether the field's underlying type, unwrapping `Key` and `?`, is the
+/// given primitive.
+fn field_has_primitive(field: &HirField, primitive: PrimitiveType) -> bool {
+    let mut ty = &field.ty;
+    while let HirType::Optional(inner) | HirType::Key { ty: inner, .. } = ty {
+        ty = inner;
+    }
+    matches!(ty, HirType::Primitive(p) if *p == primitive)
+}
+
+/// Whether a column type can legally carry `@auto_increment`.
+fn is_integer_type(ty: &MirType) -> bool {
+    matches!(
+        ty,
+        MirType::I8 | MirType::I16 | MirType::I32 | MirType::I64 | MirType::U8 | MirType::U16 | MirType::U32 | MirType::U64
+    )
+}
+
+/// The index methods `@index(using: ...)` accepts, as Postgres names them.
+const INDEX_METHODS: &[&str] = &["btree", "hash", "gin", "gist", "spgist", "brin"];
+
+/// Deterministically shorten a generated name that exceeds
+/// [MAX_IDENTIFIER_LEN]: truncate and append an FNV-1a hash of the full name,
+/// so shortened names stay unique and stable across runs.
+fn shorten_identifier(name: String) -> String {
+    if name.len() <= MAX_IDENTIFIER_LEN {
+        return name;
+    }
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in name.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    let keep = MAX_IDENTIFIER_LEN - 9;
+    format!("{}_{:08x}", &name[..keep], hash as u32)
+}
+
+fn short_name(full_name: &str) -> String {
+    full_name.rsplit("::").next().unwrap_or(full_name).to_string()
+}
+
+/// Convert a PascalCase struct name to the snake_case table name. Acronym
+/// runs stay together (`HTTPServer` becomes `http_server`, not
+/// `h_t_t_p_server`) and a capital after a digit starts a new word
+/// (`User2FA` becomes `user2_fa`).
+pub fn to_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() {
+            let prev = i.checked_sub(1).map(|p| chars[p]);
+            // A capital after a lowercase letter or digit starts a word, as
+            // does the last capital of an acronym run (the `S` in
+            // `HTTPServer`).
+            let after_word = prev.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit());
+            let ends_acronym =
+                prev.is_some_and(|p| p.is_ascii_uppercase()) && chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            if after_word || ends_acronym {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
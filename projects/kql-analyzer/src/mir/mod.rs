@@ -0,0 +1,485 @@
+//! The mid-level intermediate representation: a flat relational model of the
+//! schema, produced from the HIR by [mir_gen::MirLowerer].
+
+pub mod mir_gen;
+
+pub use crate::hir::NullsOrder;
+pub use kql_ast::{BinaryOpKind, UnaryOpKind};
+use kql_types::Span;
+
+use indexmap::IndexMap;
+
+/// A complete lowered schema.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MirProgram {
+    /// All tables, keyed by the fully qualified name of the struct they came from.
+    pub tables: IndexMap<String, Table>,
+    /// All enums referenced by a column, keyed by fully qualified name.
+    pub enums: IndexMap<String, MirEnum>,
+    /// All named queries.
+    pub queries: Vec<MirQuery>,
+    /// Fixture rows declared with `seed`, in source order.
+    pub seeds: Vec<MirSeed>,
+}
+
+impl MirProgram {
+    /// Look up a table by its physical name.
+    pub fn table_by_name(&self, name: &str) -> Option<&Table> {
+        self.tables.values().find(|t| t.name == name)
+    }
+
+    /// A stable digest of the schema's semantic content, used by `kql migrate`
+    /// to skip work when nothing changed. Tables, columns, indexes and foreign
+    /// keys are serialized in sorted order, so declaration order does not
+    /// change the hash; orderings with meaning — composite primary keys, index
+    /// column order — are kept as declared.
+    pub fn fingerprint(&self) -> String {
+        use std::fmt::Write;
+        let mut canon = String::new();
+        let mut table_names: Vec<&String> = self.tables.keys().collect();
+        table_names.sort();
+        for name in table_names {
+            let table = &self.tables[name];
+            let _ = writeln!(canon, "table {} pk={:?} temporal={}", table.qualified_name(), table.primary_key, table.temporal);
+            let mut columns: Vec<&Column> = table.columns.iter().collect();
+            columns.sort_by_key(|c| &c.name);
+            for c in columns {
+                let _ = writeln!(
+                    canon,
+                    "  column {} {:?} nullable={} default={:?} auto={} unique={} nnd={} db_type={:?}/{:?} generated={:?} collation={:?}",
+                    c.name,
+                    c.ty,
+                    c.nullable,
+                    c.default,
+                    c.auto_increment,
+                    c.unique,
+                    c.unique_nulls_not_distinct,
+                    c.db_type,
+                    c.db_type_dialect,
+                    c.generated,
+                    c.collation
+                );
+            }
+            let mut indexes: Vec<&Index> = table.indexes.iter().collect();
+            indexes.sort_by_key(|i| &i.name);
+            for index in indexes {
+                let columns: Vec<String> = index.columns.iter().map(|c| c.as_sql()).collect();
+                let _ =
+                    writeln!(canon, "  index {} {:?} unique={} method={:?}", index.name, columns, index.unique, index.method);
+            }
+            let mut foreign_keys: Vec<&ForeignKey> = table.foreign_keys.iter().collect();
+            foreign_keys.sort_by_key(|fk| &fk.name);
+            for fk in foreign_keys {
+                let _ = writeln!(
+                    canon,
+                    "  fk {} {:?} -> {} {:?} on_delete={:?} on_update={:?} deferrable={}",
+                    fk.name, fk.columns, fk.ref_table, fk.ref_columns, fk.on_delete, fk.on_update, fk.deferrable
+                );
+            }
+        }
+        let mut enum_names: Vec<&String> = self.enums.keys().collect();
+        enum_names.sort();
+        for name in enum_names {
+            let item = &self.enums[name];
+            let _ = writeln!(canon, "enum {} {:?} string_layout={}", item.name, item.variants, item.string_layout);
+        }
+        // FNV-1a, so the digest does not depend on the standard library's
+        // unspecified hasher.
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in canon.bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        format!("{hash:016x}")
+    }
+}
+
+/// A single database table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    /// The schema this table lives in, from the namespace.
+    pub schema: Option<String>,
+    /// The physical table name.
+    pub name: String,
+    /// The name of the struct this table was lowered from.
+    pub struct_name: String,
+    /// The columns in declaration order.
+    pub columns: Vec<Column>,
+    /// The primary key column names, possibly composite.
+    pub primary_key: Vec<String>,
+    /// Secondary indexes.
+    pub indexes: Vec<Index>,
+    /// Foreign key constraints.
+    pub foreign_keys: Vec<ForeignKey>,
+    /// Declared relations to other tables.
+    pub relations: Vec<Relation>,
+    /// The `@deprecated` note when the struct is marked deprecated; empty
+    /// when no note was given.
+    pub deprecated: Option<String>,
+    /// Whether the struct is marked `@temporal`; such tables carry
+    /// `valid_from`/`valid_to` columns and system versioning where supported.
+    pub temporal: bool,
+    /// Span of the originating struct declaration.
+    pub span: Span,
+}
+
+impl Table {
+    /// Look up a column by name.
+    pub fn column(&self, name: &str) -> Option<&Column> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+    /// The physical name qualified with the schema, when present.
+    pub fn qualified_name(&self) -> String {
+        match &self.schema {
+            Some(schema) => format!("{}.{}", schema, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// A single table column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    /// The column name.
+    pub name: String,
+    /// The column type.
+    pub ty: MirType,
+    /// Whether NULL is allowed.
+    pub nullable: bool,
+    /// The default value, if declared.
+    pub default: Option<MirValue>,
+    /// Whether the column auto-increments.
+    pub auto_increment: bool,
+    /// Whether the column carries a single-column UNIQUE constraint.
+    pub unique: bool,
+    /// Whether the unique constraint treats NULLs as equal, from
+    /// `@unique(nulls_not_distinct: true)`. Postgres only.
+    pub unique_nulls_not_distinct: bool,
+    /// A raw SQL type from `@db_type("CITEXT")`, rendered verbatim in place
+    /// of the inferred type.
+    pub db_type: Option<String>,
+    /// The dialect keyword the `@db_type` override is restricted to, from
+    /// `@db_type("CITEXT", dialect: "postgres")`; `None` applies everywhere.
+    pub db_type_dialect: Option<String>,
+    /// The SQL expression of a `@generated(...)` computed column.
+    pub generated: Option<String>,
+    /// The collation name from `@collate`, for string columns.
+    pub collation: Option<String>,
+    /// Documentation attached to the originating field.
+    pub comment: Option<String>,
+    /// The `@deprecated` note when the field is marked deprecated; empty
+    /// when no note was given.
+    pub deprecated: Option<String>,
+    /// Span of the originating field.
+    pub span: Span,
+}
+
+/// The type of a [Column].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MirType {
+    /// 8-bit signed integer.
+    I8,
+    /// 16-bit signed integer.
+    I16,
+    /// 32-bit signed integer.
+    I32,
+    /// 64-bit signed integer.
+    I64,
+    /// 8-bit unsigned integer.
+    U8,
+    /// 16-bit unsigned integer.
+    U16,
+    /// 32-bit unsigned integer.
+    U32,
+    /// 64-bit unsigned integer.
+    U64,
+    /// 32-bit float.
+    F32,
+    /// 64-bit float.
+    F64,
+    /// Fixed point decimal, with optional precision and scale.
+    Decimal {
+        /// `(precision, scale)` when declared via `@precision`.
+        precision: Option<(u32, u32)>,
+    },
+    /// Boolean.
+    Bool,
+    /// Unbounded text.
+    Text,
+    /// Timestamp.
+    DateTime,
+    /// Calendar date.
+    Date,
+    /// Time of day.
+    Time,
+    /// UUID.
+    Uuid,
+    /// JSON document.
+    Json,
+    /// A reference to an enum, by fully qualified name.
+    Enum(String),
+}
+
+/// A literal default value on a column.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MirValue {
+    /// An integer value.
+    Int(i64),
+    /// A floating point value.
+    Float(f64),
+    /// A text value.
+    Text(String),
+    /// A boolean value.
+    Bool(bool),
+    /// A builtin function like `now`, rendered as a dialect-specific SQL
+    /// expression rather than a quoted literal.
+    Function(String),
+}
+
+/// A secondary index on a table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Index {
+    /// The generated index name.
+    pub name: String,
+    /// The indexed columns in order.
+    pub columns: Vec<IndexColumn>,
+    /// Whether this is a unique index.
+    pub unique: bool,
+    /// The index method from `using:`, e.g. `gin`. Postgres only; the other
+    /// dialects ignore it.
+    pub method: Option<String>,
+}
+
+/// A single indexed column, with its declared direction when one was written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexColumn {
+    /// The column name, or a rendered SQL expression for functional indexes
+    /// like `lower(email)`.
+    pub name: String,
+    /// Whether `name` is an expression rather than a plain column name.
+    pub expr: bool,
+    /// The direction given via `.asc()` / `.desc()`, if any.
+    pub order: Option<IndexOrder>,
+}
+
+impl IndexColumn {
+    /// The SQL spelling of this column inside `CREATE INDEX (...)`.
+    pub fn as_sql(&self) -> String {
+        match self.order {
+            None => self.name.clone(),
+            Some(IndexOrder::Asc) => format!("{} ASC", self.name),
+            Some(IndexOrder::Desc) => format!("{} DESC", self.name),
+        }
+    }
+}
+
+/// An explicit per-column index direction.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IndexOrder {
+    /// `ASC`
+    Asc,
+    /// `DESC`
+    Desc,
+}
+
+/// A foreign key constraint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForeignKey {
+    /// The generated constraint name.
+    pub name: String,
+    /// The referencing columns.
+    pub columns: Vec<String>,
+    /// The referenced table's physical name.
+    pub ref_table: String,
+    /// The referenced columns.
+    pub ref_columns: Vec<String>,
+    /// The `ON DELETE` action, if declared.
+    pub on_delete: Option<RefAction>,
+    /// The `ON UPDATE` action, if declared.
+    pub on_update: Option<RefAction>,
+    /// Whether `@relation(deferrable: true)` was given; renders as
+    /// `DEFERRABLE INITIALLY DEFERRED` on dialects that support it.
+    pub deferrable: bool,
+}
+
+/// A referential action on a foreign key.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RefAction {
+    /// `CASCADE`
+    Cascade,
+    /// `RESTRICT`
+    Restrict,
+    /// `SET NULL`
+    SetNull,
+    /// `SET DEFAULT`
+    SetDefault,
+    /// `NO ACTION`
+    NoAction,
+}
+
+impl RefAction {
+    /// Parse an action keyword as written in a `@relation` attribute.
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        let action = match keyword {
+            "cascade" => Self::Cascade,
+            "restrict" => Self::Restrict,
+            "set_null" => Self::SetNull,
+            "set_default" => Self::SetDefault,
+            "no_action" => Self::NoAction,
+            _ => return None,
+        };
+        Some(action)
+    }
+    /// The SQL spelling of this action.
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Self::Cascade => "CASCADE",
+            Self::Restrict => "RESTRICT",
+            Self::SetNull => "SET NULL",
+            Self::SetDefault => "SET DEFAULT",
+            Self::NoAction => "NO ACTION",
+        }
+    }
+}
+
+/// A declared relation between two tables, used for joins and codegen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Relation {
+    /// The relation name, from the field that declared it.
+    pub name: String,
+    /// The physical name of the owning table.
+    pub from_table: String,
+    /// The physical name of the related table.
+    pub to_table: String,
+    /// The FK column on the owning table, for singular relations.
+    pub fk_column: Option<String>,
+    /// Whether the relation yields many rows.
+    pub is_list: bool,
+    /// Whether a target row is guaranteed to exist, from
+    /// `@relation(required: true)`; required relations join with `INNER`.
+    pub required: bool,
+    /// How many rows relate on each side.
+    pub cardinality: Cardinality,
+}
+
+/// The cardinality of a [Relation], for diagram and codegen consumers.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Cardinality {
+    /// A unique foreign key: each row relates to at most one row.
+    OneToOne,
+    /// A plain foreign key: many owning rows relate to one target row.
+    OneToMany,
+    /// Paired list relations on both sides.
+    ManyToMany,
+}
+
+/// One fixture row to insert, lowered from a `seed` declaration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MirSeed {
+    /// The fully qualified struct name, keying into [MirProgram::tables].
+    pub table: String,
+    /// The columns given a value, in declaration order.
+    pub columns: Vec<String>,
+    /// The values, parallel to `columns`.
+    pub values: Vec<MirValue>,
+}
+
+/// An enum referenced by at least one column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MirEnum {
+    /// The enum's physical name, snake cased.
+    pub name: String,
+    /// `(variant name, stored value)` pairs in declaration order.
+    pub variants: Vec<(String, i64)>,
+    /// Whether values are stored as strings or integers.
+    pub string_layout: bool,
+}
+
+/// A named query lowered from a `let` declaration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MirQuery {
+    /// The query name.
+    pub name: String,
+    /// The physical name of the table the query reads from.
+    pub table: String,
+    /// The WHERE predicate.
+    pub filter: Option<MirExpr>,
+    /// ORDER BY keys in order.
+    pub order_by: Vec<MirOrderBy>,
+    /// Projected columns; empty means `*`.
+    pub projections: Vec<MirProjection>,
+    /// LIMIT, if set.
+    pub limit: Option<u64>,
+    /// OFFSET, if set.
+    pub offset: Option<u64>,
+}
+
+/// A single ORDER BY key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MirOrderBy {
+    /// The column to order by.
+    pub column: String,
+    /// Whether to order descending.
+    pub desc: bool,
+    /// Where NULLs sort, when declared.
+    pub nulls: Option<NullsOrder>,
+}
+
+/// A projected select item.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MirProjection {
+    /// A plain column reference.
+    Column(String),
+    /// An aliased select item, rendered as `expr AS name`.
+    Alias(String, MirExpr),
+}
+
+/// A scalar expression in a query.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MirExpr {
+    /// A column reference.
+    Column(String),
+    /// A literal value.
+    Value(MirValue),
+    /// The `*` wildcard.
+    Star,
+    /// A prefix unary operation.
+    Unary {
+        /// The operator.
+        op: UnaryOpKind,
+        /// The operand.
+        expr: Box<MirExpr>,
+    },
+    /// An infix binary operation.
+    Binary {
+        /// The operator.
+        op: BinaryOpKind,
+        /// Left operand.
+        lhs: Box<MirExpr>,
+        /// Right operand.
+        rhs: Box<MirExpr>,
+    },
+    /// A function call.
+    Call {
+        /// The function name.
+        func: String,
+        /// The arguments.
+        args: Vec<MirExpr>,
+    },
+    /// A two-branch conditional, rendered as `CASE WHEN ... END`.
+    Case {
+        /// The condition.
+        when: Box<MirExpr>,
+        /// The value when the condition holds.
+        then: Box<MirExpr>,
+        /// The value otherwise.
+        otherwise: Box<MirExpr>,
+    },
+    /// A path into a JSON column, from member access on a json-layout field.
+    JsonAccess {
+        /// The JSON column.
+        column: String,
+        /// The member path inside the document.
+        path: Vec<String>,
+    },
+}
@@ -0,0 +1,47 @@
+use crate::{
+    compiler::Compiler,
+    hir::HirProgram,
+    lir::{Dialect, sql_gen::SqlGenerator},
+    mir::{MirProgram, mir_gen::MirLowerer},
+};
+use kql_types::{KqlError, Result};
+
+/// The supported end-to-end surface of the analyzer: source to HIR to MIR to
+/// SQL, one method per stage.
+///
+/// Embedders should prefer this over reaching for [MirLowerer] and
+/// [SqlGenerator] by module path; those remain available for callers that
+/// need their full APIs, but [Pipeline] is the interface that stays stable.
+#[derive(Debug, Default)]
+pub struct Pipeline {}
+
+impl Pipeline {
+    /// Create a pipeline.
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Parse and lower `source` to a checked [HirProgram]. Like
+    /// [Compiler::compile_source], `import` declarations are ignored.
+    pub fn compile(&self, source: &str) -> std::result::Result<HirProgram, Vec<KqlError>> {
+        Compiler::new().compile_source(source)
+    }
+
+    /// Lower a checked program to the relational [MirProgram].
+    pub fn to_mir(&self, hir: HirProgram) -> Result<MirProgram> {
+        MirLowerer::new(hir).lower()
+    }
+
+    /// Render the program as SQL for `dialect`, one string per statement.
+    /// DDL comes first in dependency order, followed by the `let` queries;
+    /// statements carry no trailing semicolon.
+    pub fn to_sql(&self, mir: &MirProgram, dialect: Dialect) -> Result<Vec<String>> {
+        let generator = SqlGenerator::new(mir, dialect);
+        generator.validate()?;
+        let mut statements: Vec<String> = generator.generate_ddl().iter().map(|s| generator.render(s)).collect();
+        for query in &mir.queries {
+            statements.push(format!("-- let {}\n{}", query.name, generator.generate_select(query, &[])));
+        }
+        Ok(statements)
+    }
+}
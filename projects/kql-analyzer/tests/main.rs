@@ -0,0 +1,1400 @@
+use kql_analyzer::{
+    Compiler, Pipeline,
+    lir::{Dialect, SqliteU64, sql_gen::SqlGenerator},
+    mir::{RefAction, mir_gen::MirLowerer},
+};
+
+#[test]
+fn ready() {
+    println!("it works!")
+}
+
+const SCHEMA: &str = r#"
+namespace demo {
+    @table("users")
+    struct User {
+        /// The surrogate primary key.
+        id: Key<User, i64>,
+        name: String,
+        age: i32?,
+    }
+
+    let adults = User.filter { $.age >= 18 }.sort { $.age.desc() }
+}
+"#;
+
+#[test]
+fn lowers_to_hir() {
+    let hir = Compiler::new().compile_source(SCHEMA).unwrap();
+    let user = hir.struct_by_name("demo::User").expect("struct lowered");
+    assert_eq!(user.fields.len(), 3);
+    assert_eq!(hir.lets.len(), 1);
+}
+
+#[test]
+fn lowers_to_mir() {
+    let hir = Compiler::new().compile_source(SCHEMA).unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let table = mir.table_by_name("users").expect("table lowered");
+    assert_eq!(table.schema.as_deref(), Some("demo"));
+    assert_eq!(table.primary_key, vec!["id".to_string()]);
+    assert!(table.column("age").unwrap().nullable);
+}
+
+#[test]
+fn reports_errors_from_every_struct() {
+    let source = r#"
+struct A { x: Missing1 }
+struct B { y: Missing2 }
+struct C { z: Missing3 }
+"#;
+    let errors = Compiler::new().compile_source(source).unwrap_err();
+    assert_eq!(errors.len(), 3, "{errors:?}");
+    for (error, name) in errors.iter().zip(["Missing1", "Missing2", "Missing3"]) {
+        assert!(error.message().contains(name), "{error:?}");
+    }
+}
+
+#[test]
+fn compiles_large_schema_without_clone_churn() {
+    // Regression guard for the arena-style lowering: 500 structs with
+    // cross-references used to clone the whole declaration tree per pass.
+    let mut source = String::new();
+    for i in 0..500 {
+        source.push_str(&format!("struct S{i} {{ id: Key<S{i}, i64>, name: String, other: i32? }}\n"));
+    }
+    let started = std::time::Instant::now();
+    let hir = Compiler::new().compile_source(&source).unwrap();
+    assert_eq!(hir.structs.len(), 500);
+    assert!(started.elapsed() < std::time::Duration::from_secs(5), "lowering took {:?}", started.elapsed());
+}
+
+#[test]
+fn monomorphizes_generic_structs() {
+    let source = r#"
+@layout(json)
+struct Paged<T> {
+    items: [T],
+    total: i64,
+}
+
+@layout(json)
+struct User { name: String }
+
+@layout(json)
+struct Post { title: String }
+
+struct Feeds {
+    id: Key<Feeds, i64>,
+    users: Paged<User>,
+    posts: Paged<Post>,
+}
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let users = hir.struct_by_name("Paged<User>").expect("instantiated for User");
+    let posts = hir.struct_by_name("Paged<Post>").expect("instantiated for Post");
+    assert_ne!(users.id, posts.id);
+    assert_eq!(users.fields[1].name, "total");
+    // The generic template itself is not lowered.
+    assert!(hir.struct_by_name("Paged").is_none());
+}
+
+#[test]
+fn rejects_generic_arity_mismatch() {
+    let source = "struct Paged<T> { total: i64 } struct S { id: Key<S, i64>, x: Paged<i32, i64> }";
+    let errors = Compiler::new().compile_source(source).unwrap_err();
+    assert!(errors.iter().any(|e| e.message().contains("expects 1 type argument")), "{errors:?}");
+}
+
+#[test]
+fn tuple_fields_become_json_columns() {
+    let hir = Compiler::new().compile_source("struct Point { id: Key<Point, i64>, coords: (i32, i32) }").unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let table = mir.table_by_name("point").unwrap();
+    assert_eq!(table.column("coords").unwrap().ty, kql_analyzer::mir::MirType::Json);
+}
+
+#[test]
+fn emits_seed_inserts() {
+    let source = r#"
+struct User {
+    id: Key<User, i64> @auto_increment,
+    name: String,
+    age: i32?,
+}
+
+seed User { name: "Alice", age: 30 }
+seed User { name: "Bob" }
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let sql = SqlGenerator::new(&mir, Dialect::Postgres).generate_seed();
+    assert!(sql.contains("INSERT INTO user (name, age) VALUES ('Alice', 30);"), "{sql}");
+    assert!(sql.contains("INSERT INTO user (name) VALUES ('Bob');"), "{sql}");
+}
+
+#[test]
+fn rejects_mistyped_seed_values() {
+    let source = r#"
+struct User { id: Key<User, i64>, name: String }
+seed User { id: 1, name: 42 }
+"#;
+    let errors = Compiler::new().compile_source(source).unwrap_err();
+    assert!(errors.iter().any(|e| e.message().contains("does not match")), "{errors:?}");
+}
+
+#[test]
+fn checks_aggregate_arguments() {
+    let base = "struct User { id: Key<User, i64>, age: i32 }\n";
+    let star = format!("{base}let total = User.filter {{ count(*) > 0 }}");
+    assert!(Compiler::new().compile_source(&star).is_ok());
+    let field = format!("{base}let by_id = User.filter {{ count($.id) > 0 }}");
+    assert!(Compiler::new().compile_source(&field).is_ok());
+    let bad = format!("{base}let sums = User.filter {{ sum(*) > 0 }}");
+    let errors = Compiler::new().compile_source(&bad).unwrap_err();
+    assert!(errors.iter().any(|e| e.message().contains("cannot aggregate `*`")), "{errors:?}");
+}
+
+#[test]
+fn lowers_negative_defaults() {
+    let source = "struct Job { id: Key<Job, i64>, priority: i32 @default(-1) }";
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let column = mir.table_by_name("job").unwrap().column("priority").unwrap();
+    assert_eq!(column.default, Some(kql_analyzer::mir::MirValue::Int(-1)));
+}
+
+#[test]
+fn fingerprint_ignores_declaration_order_only() {
+    let compile = |source: &str| MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    let base = compile("struct User { id: Key<User, i64>, name: String, age: i32? }");
+    // Field order is presentation; the fingerprint canonicalizes it away.
+    let reordered = compile("struct User { id: Key<User, i64>, age: i32?, name: String }");
+    assert_eq!(base.fingerprint(), reordered.fingerprint());
+    // A type change is semantic and must show up.
+    let retyped = compile("struct User { id: Key<User, i64>, name: String, age: i64? }");
+    assert_ne!(base.fingerprint(), retyped.fingerprint());
+}
+
+#[test]
+fn resolves_enum_defaults() {
+    let source = r#"
+enum Status { Active, Disabled }
+
+struct Account {
+    id: Key<Account, i64>,
+    status: Status @default(Active),
+}
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let column = mir.table_by_name("account").unwrap().column("status").unwrap();
+    assert_eq!(column.default, Some(kql_analyzer::mir::MirValue::Text("Active".to_string())));
+
+    let bad = source.replace("@default(Active)", "@default(Archived)");
+    let hir = Compiler::new().compile_source(&bad).unwrap();
+    let error = MirLowerer::new(hir).lower().unwrap_err();
+    assert!(error.message().contains("not a variant of `Status`"), "{error:?}");
+}
+
+#[test]
+fn reports_dialect_portability_warnings() {
+    let source = r#"
+enum Status { Active, Disabled }
+
+struct Account {
+    id: Key<Account, i64>,
+    status: Status,
+    quota: u64,
+}
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let warnings = SqlGenerator::new(&mir, Dialect::Postgres).portability_report();
+    assert!(warnings.iter().any(|w| w.dialect == Dialect::Sqlite && w.message.contains("native enum")), "{warnings:?}");
+    assert!(warnings.iter().any(|w| w.dialect == Dialect::Postgres && w.message.contains("unsigned")), "{warnings:?}");
+}
+
+#[test]
+fn generates_keyset_pagination_queries() {
+    let hir = Compiler::new().compile_source(SCHEMA).unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let generator = SqlGenerator::new(&mir, Dialect::Postgres);
+    let table = mir.table_by_name("users").unwrap();
+    let single = generator.generate_keyset_page(table, &["id"], 20);
+    assert_eq!(single, "SELECT * FROM demo.users WHERE id > $1 ORDER BY id LIMIT 20");
+    let multi = generator.generate_keyset_page(table, &["age", "id"], 20);
+    assert_eq!(multi, "SELECT * FROM demo.users WHERE (age, id) > ($1, $2) ORDER BY age, id LIMIT 20");
+}
+
+#[test]
+fn names_foreign_key_columns_from_the_type_argument() {
+    let source = r#"
+struct User { id: Key<User, i64> }
+
+struct Post {
+    id: Key<Post, i64>,
+    author: ForeignKey<User>,
+    reviewed_by: ForeignKey<reviewer: User>,
+}
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let table = mir.table_by_name("post").unwrap();
+    assert!(table.column("author_id").is_some(), "{:?}", table.columns);
+    assert!(table.column("reviewer_id").is_some(), "{:?}", table.columns);
+    let names: Vec<&str> = table.relations.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, ["author", "reviewer"]);
+}
+
+#[test]
+fn applies_standalone_referential_action_attributes() {
+    let source = r#"
+struct User { id: Key<User, i64> }
+
+struct Post {
+    id: Key<Post, i64>,
+    author_id: Key<User, i64> @on_delete(cascade) @on_update(restrict),
+}
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let sql = SqlGenerator::new(&mir, Dialect::Postgres).generate_sql();
+    assert!(sql.contains("ON DELETE CASCADE"), "{sql}");
+    assert!(sql.contains("ON UPDATE RESTRICT"), "{sql}");
+}
+
+#[test]
+fn warns_on_cross_entity_keys() {
+    let matching = "struct User { id: Key<User, i64> }";
+    let hir = Compiler::new().compile_source(matching).unwrap();
+    assert!(hir.warnings.is_empty(), "{:?}", hir.warnings);
+
+    let mismatching = r#"
+struct User { id: Key<User, i64> }
+struct Post { id: Key<User, i64>, title: String }
+"#;
+    let hir = Compiler::new().compile_source(mismatching).unwrap();
+    assert_eq!(hir.warnings.len(), 1, "{:?}", hir.warnings);
+    assert_eq!(hir.warnings[0].severity, kql_types::Severity::Warning);
+    assert!(hir.warnings[0].message.contains("`Key<User, _>` inside `Post`"), "{:?}", hir.warnings);
+}
+
+#[test]
+fn orders_composite_index_columns() {
+    let source = r#"
+@index(columns: [created_at.desc(), id.asc()])
+struct Event {
+    id: Key<Event, i64>,
+    created_at: DateTime,
+}
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let index = &mir.table_by_name("event").unwrap().indexes[0];
+    assert_eq!(index.name, "event_created_at_id_idx");
+    let sql = SqlGenerator::new(&mir, Dialect::Postgres).generate_sql();
+    assert!(sql.contains("(created_at DESC, id ASC)"), "{sql}");
+}
+
+#[test]
+fn filters_on_json_layout_fields_via_json_paths() {
+    let source = r#"
+@layout(json)
+struct Metadata { color: String }
+
+struct Product {
+    id: Key<Product, i64>,
+    metadata: Metadata,
+}
+
+let reds = Product.filter { $.metadata.color == "red" }
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let postgres = SqlGenerator::new(&mir, Dialect::Postgres).generate_sql();
+    assert!(postgres.contains("metadata->>'color' = 'red'"), "{postgres}");
+    for dialect in [Dialect::MySql, Dialect::Sqlite] {
+        let sql = SqlGenerator::new(&mir, dialect).generate_sql();
+        assert!(sql.contains("JSON_EXTRACT(metadata, '$.color') = 'red'"), "{dialect}: {sql}");
+    }
+}
+
+#[test]
+fn emits_temporal_validity_columns() {
+    let source = r#"
+@temporal
+struct Price {
+    id: Key<Price, i64>,
+    amount: i64,
+}
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    for dialect in Dialect::all() {
+        let sql = SqlGenerator::new(&mir, dialect).generate_sql();
+        assert!(sql.contains("valid_from"), "{dialect}: {sql}");
+        assert!(sql.contains("valid_to"), "{dialect}: {sql}");
+        // `WITH SYSTEM VERSIONING` is MariaDB-only; stock MySQL rejects it,
+        // so every dialect gets the two-column fallback.
+        assert!(!sql.contains("WITH SYSTEM VERSIONING"), "{dialect}: {sql}");
+    }
+}
+
+#[test]
+fn emits_nulls_placement_on_sort_keys() {
+    let source = r#"
+struct User {
+    id: Key<User, i64>,
+    age: i32?,
+}
+
+let oldest = User.sort { $.age.desc().nulls_last() }
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let postgres = SqlGenerator::new(&mir, Dialect::Postgres).generate_sql();
+    assert!(postgres.contains("ORDER BY age DESC NULLS LAST"), "{postgres}");
+    let mysql = SqlGenerator::new(&mir, Dialect::MySql).generate_sql();
+    assert!(mysql.contains("ORDER BY age IS NULL, age DESC"), "{mysql}");
+}
+
+#[test]
+fn generates_postgres_ddl() {
+    let hir = Compiler::new().compile_source(SCHEMA).unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let sql = SqlGenerator::new(&mir, Dialect::Postgres).generate_sql();
+    assert!(sql.contains("CREATE SCHEMA IF NOT EXISTS demo"), "{sql}");
+    assert!(sql.contains("CREATE TABLE demo.users"), "{sql}");
+    assert!(sql.contains("-- let adults"), "{sql}");
+}
+
+#[test]
+fn resolves_numeric_literal_suffixes() {
+    use kql_analyzer::hir::{HirExprKind, HirQueryOp, HirType, PrimitiveType};
+    let source = r#"
+@table("events")
+struct Event {
+    id: Key<Event, i64>,
+    count: i64,
+}
+
+let big = Event.filter { $.count == 10i64 }
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let query = &hir.lets.values().next().unwrap().query;
+    let HirQueryOp::Filter(predicate) = &query.ops[0] else {
+        panic!("expected a filter op");
+    };
+    let HirExprKind::Binary { rhs, .. } = &predicate.kind else {
+        panic!("expected a comparison: {:?}", predicate.kind);
+    };
+    // The suffix overrides the default i32 inference.
+    assert_eq!(rhs.ty, HirType::Primitive(PrimitiveType::I64));
+
+    let bad = source.replace("10i64", "10i65");
+    let errors = Compiler::new().compile_source(&bad).unwrap_err();
+    assert!(errors.iter().any(|e| e.to_string().contains("`i65` is not a valid numeric literal suffix")), "{errors:?}");
+}
+
+#[test]
+fn validate_catches_corrupted_programs() {
+    use kql_analyzer::hir::HirType;
+    let mut hir = Compiler::new().compile_source(SCHEMA).unwrap();
+    assert!(hir.validate().is_empty());
+
+    // Point a field at a struct id that does not exist.
+    let user_id = hir.name_to_id["demo::User"];
+    hir.structs[&user_id].fields[1].ty = HirType::Struct(9999);
+    // Register a name whose id has no kind, and drop a kind's backing entry.
+    hir.name_to_id.insert("demo::Ghost".to_string(), 9998);
+    hir.structs[&user_id].fields[2].ty = HirType::Optional(Box::new(HirType::ForeignKey { name: None, entity: 9997 }));
+    let errors = hir.validate();
+    assert_eq!(errors.len(), 3, "{errors:?}");
+    assert!(errors.iter().any(|e| e.to_string().contains("`demo::Ghost` has an id with no declaration kind")), "{errors:?}");
+    assert!(
+        errors.iter().any(|e| e.to_string().contains("`demo::User.name` references a struct that does not exist")),
+        "{errors:?}"
+    );
+    assert!(errors.iter().any(|e| e.to_string().contains("foreign key to a struct that does not exist")), "{errors:?}");
+}
+
+#[test]
+fn infers_relation_cardinalities() {
+    use kql_analyzer::mir::Cardinality;
+    let source = r#"
+struct User {
+    id: Key<User, i64>,
+    profile: ForeignKey<Profile> @unique,
+    groups: [ForeignKey<Group>],
+}
+
+struct Profile { id: Key<Profile, i64> }
+
+struct Group {
+    id: Key<Group, i64>,
+    members: [ForeignKey<User>],
+}
+
+struct Post {
+    id: Key<Post, i64>,
+    author: ForeignKey<User>,
+}
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let relation = |table: &str, name: &str| {
+        mir.table_by_name(table).unwrap().relations.iter().find(|r| r.name == name).unwrap().cardinality
+    };
+    // A unique FK column pins each side to a single row.
+    assert_eq!(relation("user", "profile"), Cardinality::OneToOne);
+    assert_eq!(relation("post", "author"), Cardinality::OneToMany);
+    // Lists on both sides pair into a many-to-many.
+    assert_eq!(relation("user", "groups"), Cardinality::ManyToMany);
+    assert_eq!(relation("group", "members"), Cardinality::ManyToMany);
+}
+
+#[test]
+fn pipeline_form_lowers_like_method_chains() {
+    let base = "struct User { id: Key<User, i64>, age: i32 }\n";
+    let compile = |source: &str| MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    let chained = compile(&format!("{base}let adults = User.filter {{ $.age >= 18 }}.sort {{ $.age.desc() }}.limit(10)"));
+    let piped =
+        compile(&format!("{base}let adults = User |> filter {{ $.age >= 18 }} |> sort {{ $.age.desc() }} |> limit(10)"));
+    assert_eq!(chained.queries, piped.queries);
+}
+
+#[test]
+fn shortens_overlong_generated_identifiers() {
+    let source = r#"
+@table("extremely_long_measurement_aggregation")
+@index(columns: [first_extremely_descriptive_column_name, second_extremely_descriptive_column_name])
+struct Measurement {
+    id: Key<Measurement, i64>,
+    first_extremely_descriptive_column_name: i64,
+    second_extremely_descriptive_column_name: i64,
+}
+"#;
+    let compile = |source: &str| MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    let mir = compile(source);
+    let index = &mir.table_by_name("extremely_long_measurement_aggregation").unwrap().indexes[0];
+    // Fits the 63-byte Postgres bound, keeps a readable prefix and ends in a
+    // stable hash so reruns agree.
+    assert!(index.name.len() <= 63, "{}", index.name);
+    assert!(index.name.starts_with("extremely_long_measurement_aggregation_first_extremely"), "{}", index.name);
+    assert_eq!(index.name, compile(source).table_by_name("extremely_long_measurement_aggregation").unwrap().indexes[0].name);
+}
+
+#[test]
+fn emits_stored_generated_columns() {
+    let source = r#"
+struct User {
+    id: Key<User, i64> @auto_increment,
+    first_name: String,
+    last_name: String,
+    full_name: String @generated("first_name || ' ' || last_name"),
+}
+"#;
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    for dialect in Dialect::all() {
+        let generator = SqlGenerator::new(&mir, dialect);
+        let sql = generator.generate_sql();
+        assert!(sql.contains("GENERATED ALWAYS AS (first_name || ' ' || last_name) STORED"), "{dialect}: {sql}");
+        // The database computes the value, so inserts must not bind it.
+        let insert = generator.generate_insert(mir.table_by_name("user").unwrap());
+        assert!(!insert.contains("full_name"), "{dialect}: {insert}");
+    }
+}
+
+#[test]
+fn rejects_non_string_generated_expressions() {
+    let source = "struct User { id: Key<User, i64>, age: i32 @generated(42) }";
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let error = MirLowerer::new(hir).lower().unwrap_err();
+    assert!(error.to_string().contains("`@generated` expects a SQL expression string"), "{error}");
+}
+
+#[test]
+fn lowers_conditionals_to_case_expressions() {
+    let source = r#"
+struct User {
+    id: Key<User, i64>,
+    age: i64,
+}
+
+let flagged = User.filter { (if $.age >= 18 then 1 else 0) == 1 }
+"#;
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    let sql = SqlGenerator::new(&mir, Dialect::Postgres).generate_select(&mir.queries[0], &[]);
+    assert!(sql.contains("CASE WHEN age >= 18 THEN 1 ELSE 0 END = 1"), "{sql}");
+}
+
+#[test]
+fn rejects_conditionals_with_mismatched_branches() {
+    let source = r#"
+struct User {
+    id: Key<User, i64>,
+    age: i64,
+}
+
+let flagged = User.filter { (if $.age >= 18 then 1 else "minor") == 1 }
+"#;
+    let errors = Compiler::new().compile_source(source).unwrap_err();
+    assert!(errors.iter().any(|e| e.to_string().contains("`if` branches must have the same type")), "{errors:?}");
+}
+
+#[test]
+fn normalizes_ifnull_per_dialect() {
+    let source = r#"
+struct User {
+    id: Key<User, i64>,
+    age: i64?,
+}
+
+let known = User.filter { ifnull($.age, 0) >= 18 }
+"#;
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    for dialect in Dialect::all() {
+        let sql = SqlGenerator::new(&mir, dialect).generate_select(&mir.queries[0], &[]);
+        match dialect {
+            // Postgres has no `ifnull`; the standard spelling works everywhere.
+            Dialect::Postgres => assert!(sql.contains("coalesce(age, 0)"), "{dialect}: {sql}"),
+            _ => assert!(sql.contains("ifnull(age, 0)"), "{dialect}: {sql}"),
+        }
+    }
+}
+
+#[test]
+fn reports_populated_compile_timings() {
+    let (sql, timings) = Compiler::new().compile_timed(SCHEMA, Dialect::Postgres).unwrap();
+    assert!(!sql.is_empty());
+    use std::time::Duration;
+    for (stage, duration) in [
+        ("parse", timings.parse),
+        ("name collection", timings.name_collection),
+        ("lowering", timings.lowering),
+        ("mir", timings.mir),
+        ("sql", timings.sql),
+    ] {
+        assert!(duration > Duration::ZERO, "{stage} was not timed");
+        assert!(timings.total() >= duration, "{stage} exceeds the total");
+    }
+}
+
+#[test]
+fn emits_postgres_index_methods() {
+    let source = r#"
+@index(columns: [payload], using: "gin")
+struct Event {
+    id: Key<Event, i64>,
+    payload: Json,
+}
+"#;
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    let sql = SqlGenerator::new(&mir, Dialect::Postgres).generate_sql();
+    assert!(sql.contains("CREATE INDEX event_payload_idx ON event USING gin (payload)"), "{sql}");
+    // The other dialects have no pluggable index methods and fall back.
+    let sql = SqlGenerator::new(&mir, Dialect::Sqlite).generate_sql();
+    assert!(sql.contains("CREATE INDEX event_payload_idx ON event (payload)"), "{sql}");
+}
+
+#[test]
+fn rejects_unknown_index_methods() {
+    let source = r#"
+@index(columns: [payload], using: "quadtree")
+struct Event {
+    id: Key<Event, i64>,
+    payload: Json,
+}
+"#;
+    let error = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap_err();
+    assert!(error.to_string().contains("unknown index method `quadtree`"), "{error}");
+}
+
+#[test]
+fn warns_when_declarations_shadow_builtin_types() {
+    let source = r#"
+struct String { id: Key<String, i64> }
+
+enum Key { A, B }
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let shadowed: Vec<&str> = hir
+        .warnings
+        .iter()
+        .filter(|w| w.message.contains("shadows a built-in type name"))
+        .map(|w| w.message.split('`').nth(1).unwrap())
+        .collect();
+    assert_eq!(shadowed, ["String", "Key"], "{:?}", hir.warnings);
+}
+
+#[test]
+fn classifies_primitive_types() {
+    use PrimitiveType::*;
+    use kql_analyzer::hir::{HirType, PrimitiveType};
+    for p in [I8, I16, I32, I64, U8, U16, U32, U64] {
+        assert!(p.is_integer() && p.is_numeric() && !p.is_float(), "{p:?}");
+    }
+    for p in [F32, F64, D128] {
+        assert!(p.is_float() && p.is_numeric() && !p.is_integer(), "{p:?}");
+    }
+    for p in [Bool, String, DateTime, Date, Time, Uuid, Json] {
+        assert!(!p.is_numeric() && !p.is_integer() && !p.is_float(), "{p:?}");
+    }
+    assert!(HirType::Primitive(I32).is_numeric());
+    assert!(HirType::Primitive(String).is_string());
+    assert!(!HirType::Unknown.is_numeric() && !HirType::Unknown.is_string());
+    assert!(!HirType::Optional(Box::new(HirType::Primitive(I32))).is_numeric());
+}
+
+#[test]
+fn rejects_auto_increment_on_non_integer_columns() {
+    let source = r#"
+struct User {
+    id: Key<User, i64>,
+    name: String @auto_increment,
+}
+"#;
+    let error = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap_err();
+    assert!(error.to_string().contains("requires an integer column type"), "{error}");
+}
+
+#[test]
+fn rejects_two_auto_increment_columns_in_one_table() {
+    let source = r#"
+struct User {
+    id: Key<User, i64> @auto_increment,
+    counter: i64 @auto_increment,
+}
+"#;
+    let error = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap_err();
+    assert!(error.to_string().contains("more than one `@auto_increment`"), "{error}");
+}
+
+#[test]
+fn collects_field_level_primary_keys_into_one_composite() {
+    let source = r#"
+struct Membership {
+    user_id: i64 @primary_key,
+    group_id: i64 @primary_key,
+}
+"#;
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    let table = mir.table_by_name("membership").unwrap();
+    assert_eq!(table.primary_key, ["user_id", "group_id"]);
+    let sql = SqlGenerator::new(&mir, Dialect::Postgres).generate_sql();
+    assert!(sql.contains("PRIMARY KEY (user_id, group_id)"), "{sql}");
+    assert_eq!(sql.matches("PRIMARY KEY").count(), 1, "{sql}");
+}
+
+#[test]
+fn struct_level_primary_key_overrides_field_markers() {
+    let source = r#"
+@primary_key(code)
+struct Country {
+    code: String,
+    numeric_id: i64 @primary_key,
+}
+"#;
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    assert_eq!(mir.table_by_name("country").unwrap().primary_key, ["code"]);
+}
+
+#[test]
+fn renders_function_call_defaults_per_dialect() {
+    let source = r#"
+struct Session {
+    id: Key<Session, i64>,
+    token: Uuid @default(uuid()),
+    created_at: DateTime @default(now()),
+}
+"#;
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    for dialect in Dialect::all() {
+        let sql = SqlGenerator::new(&mir, dialect).generate_sql();
+        assert!(sql.contains("DEFAULT CURRENT_TIMESTAMP"), "{dialect}: {sql}");
+        let uuid_default = match dialect {
+            Dialect::Postgres => "DEFAULT gen_random_uuid()",
+            Dialect::MySql => "DEFAULT (UUID())",
+            Dialect::Sqlite => "DEFAULT (lower(hex(randomblob(16))))",
+        };
+        assert!(sql.contains(uuid_default), "{dialect}: {sql}");
+    }
+}
+
+#[test]
+fn rejects_unsupported_default_functions() {
+    let source = r#"
+struct User {
+    id: Key<User, i64>,
+    name: String @default(lower("X")),
+}
+"#;
+    let error = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap_err();
+    assert!(error.to_string().contains("cannot be used as a column default"), "{error}");
+}
+
+#[test]
+fn maps_unsigned_types_for_sqlite() {
+    let source = r#"
+struct Stats {
+    id: Key<Stats, i64>,
+    tiny: u8,
+    small: u16,
+    medium: u32,
+    big: u64,
+}
+"#;
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    let sql = SqlGenerator::new(&mir, Dialect::Sqlite).generate_sql();
+    assert!(sql.contains("tiny INTEGER NOT NULL"), "{sql}");
+    assert!(sql.contains("small INTEGER NOT NULL"), "{sql}");
+    assert!(sql.contains("medium BIGINT NOT NULL"), "{sql}");
+    // The default keeps u64 NUMERIC; TEXT is available for lossless storage.
+    assert!(sql.contains("big NUMERIC NOT NULL"), "{sql}");
+    let sql = SqlGenerator::new(&mir, Dialect::Sqlite).with_sqlite_u64(SqliteU64::Text).generate_sql();
+    assert!(sql.contains("big TEXT NOT NULL"), "{sql}");
+}
+
+#[test]
+fn compiles_trivial_sources_to_empty_schemas() {
+    for source in ["", "   \n\t\n", "// just a comment\n/* block */\n"] {
+        let hir = Compiler::new().compile_source(source).unwrap();
+        assert!(hir.warnings.is_empty(), "{source:?} produced {:?}", hir.warnings);
+        let mir = MirLowerer::new(hir).lower().unwrap();
+        assert!(mir.tables.is_empty());
+        let sql = SqlGenerator::new(&mir, Dialect::Sqlite).generate_sql();
+        assert!(sql.trim().is_empty(), "{sql:?}");
+    }
+}
+
+#[test]
+fn quotes_reserved_column_names_per_dialect() {
+    let source = "struct Item {\n    id: Key<Item, i64>,\n    `order`: i32,\n}\n";
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    let postgres = SqlGenerator::new(&mir, Dialect::Postgres).generate_sql();
+    assert!(postgres.contains("\"order\" INTEGER NOT NULL"), "{postgres}");
+    let mysql = SqlGenerator::new(&mir, Dialect::MySql).generate_sql();
+    assert!(mysql.contains("`order` INTEGER NOT NULL"), "{mysql}");
+    // Ordinary names stay unquoted.
+    assert!(postgres.contains("id BIGINT"), "{postgres}");
+}
+
+#[test]
+fn hir_visitor_walks_query_predicates() {
+    use kql_analyzer::hir::visit::{HirVisitor, walk_expr};
+    use kql_analyzer::hir::{HirExpr, HirExprKind};
+
+    struct ContextFieldCounter(usize);
+    impl HirVisitor for ContextFieldCounter {
+        fn visit_expr(&mut self, expr: &HirExpr) {
+            if matches!(expr.kind, HirExprKind::ContextField(_)) {
+                self.0 += 1;
+            }
+            walk_expr(self, expr);
+        }
+    }
+
+    let source = "struct User {\n    id: Key<User, i64>,\n    age: i32,\n    active: bool,\n}\n\nlet adults = User.filter { $.age >= 18 && $.active }\n";
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mut counter = ContextFieldCounter(0);
+    counter.visit_program(&hir);
+    assert_eq!(counter.0, 2);
+}
+
+#[test]
+fn applies_configured_default_referential_actions() {
+    use kql_types::RelationsConfig;
+
+    let source = r#"
+struct User { id: Key<User, i64> }
+
+struct Post {
+    id: Key<Post, i64>,
+    author_id: Key<User, i64>,
+    editor_id: Key<User, i64> @relation(on_delete: restrict),
+}
+"#;
+    let config =
+        RelationsConfig { default_on_delete: Some("cascade".to_string()), default_on_update: Some("no_action".to_string()) };
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mir = MirLowerer::new(hir).with_relation_defaults(&config).lower().unwrap();
+    let post = mir.table_by_name("post").unwrap();
+    let author = post.foreign_keys.iter().find(|fk| fk.columns == ["author_id"]).unwrap();
+    assert_eq!(author.on_delete, Some(RefAction::Cascade));
+    assert_eq!(author.on_update, Some(RefAction::NoAction));
+    // An explicit attribute overrides the configured default.
+    let editor = post.foreign_keys.iter().find(|fk| fk.columns == ["editor_id"]).unwrap();
+    assert_eq!(editor.on_delete, Some(RefAction::Restrict));
+    assert_eq!(editor.on_update, Some(RefAction::NoAction));
+}
+
+#[test]
+fn rejects_two_structs_mapped_to_one_table() {
+    let source = r#"
+@table("users")
+struct User { id: Key<User, i64> }
+
+@table("users")
+struct Account { id: Key<Account, i64> }
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let error = MirLowerer::new(hir).lower().unwrap_err();
+    assert!(error.message().contains("`User`"), "{error:?}");
+    assert!(error.message().contains("`Account`"), "{error:?}");
+    assert!(error.message().contains("`users`"), "{error:?}");
+}
+
+#[test]
+fn optional_foreign_keys_produce_nullable_columns_with_constraints() {
+    let source = r#"
+struct User { id: Key<User, i64> }
+
+struct Post {
+    id: Key<Post, i64>,
+    author: ForeignKey<User>?,
+}
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let post = mir.table_by_name("post").unwrap();
+    let column = post.column("author_id").unwrap();
+    assert!(column.nullable);
+    let fk = post.foreign_keys.iter().find(|fk| fk.columns == ["author_id"]).unwrap();
+    assert_eq!(fk.ref_table, "user");
+    let sql = SqlGenerator::new(&mir, Dialect::Postgres).generate_sql();
+    assert!(sql.contains("author_id BIGINT,"), "{sql}");
+    assert!(sql.contains("FOREIGN KEY (author_id) REFERENCES user (id)"), "{sql}");
+}
+
+#[test]
+fn emits_column_collations_per_dialect() {
+    let source = r#"
+struct User {
+    id: Key<User, i64>,
+    name: String @collate("en_US"),
+    nickname: String @collate("nocase"),
+}
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let postgres = SqlGenerator::new(&mir, Dialect::Postgres).generate_sql();
+    assert!(postgres.contains("name TEXT NOT NULL COLLATE \"en_US\""), "{postgres}");
+    let mysql = SqlGenerator::new(&mir, Dialect::MySql).generate_sql();
+    assert!(mysql.contains("name TEXT NOT NULL COLLATE en_US"), "{mysql}");
+    // Sqlite keeps its built-in collations and drops the rest.
+    let sqlite = SqlGenerator::new(&mir, Dialect::Sqlite).generate_sql();
+    assert!(sqlite.contains("name TEXT NOT NULL,"), "{sqlite}");
+    assert!(sqlite.contains("nickname TEXT NOT NULL COLLATE nocase"), "{sqlite}");
+}
+
+#[test]
+fn rejects_collate_on_non_string_columns() {
+    let source = "struct User {\n    id: Key<User, i64>,\n    age: i32 @collate(\"en_US\"),\n}\n";
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let error = MirLowerer::new(hir).lower().unwrap_err();
+    assert!(error.message().contains("string columns"), "{error:?}");
+}
+#[test]
+fn resolves_forward_references() {
+    use kql_analyzer::hir::HirType;
+    use kql_analyzer::hir::visit::{HirVisitor, walk_type};
+
+    // Collects the types that failed to resolve, including nested ones.
+    struct UnknownFinder(usize);
+    impl HirVisitor for UnknownFinder {
+        fn visit_type(&mut self, ty: &HirType) {
+            if *ty == HirType::Unknown {
+                self.0 += 1;
+            }
+            walk_type(self, ty);
+        }
+    }
+
+    // A struct referencing a later struct, an alias used before its
+    // declaration (also nested in a list), and an enum declared below its use.
+    let source = r#"
+struct Post {
+    id: Key<Post, i64>,
+    author: ForeignKey<User>,
+    status: Status,
+    label: Label,
+    tags: [Label],
+}
+
+type Label = String
+
+struct User { id: Key<User, i64> }
+
+enum Status { Active, Closed }
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mut finder = UnknownFinder(0);
+    finder.visit_program(&hir);
+    assert_eq!(finder.0, 0);
+    let post = hir.struct_by_name("Post").unwrap();
+    assert_eq!(post.field("label").unwrap().ty, HirType::Primitive(kql_analyzer::hir::PrimitiveType::String));
+}
+
+#[test]
+fn pipeline_compiles_source_to_sql_statements() {
+    let pipeline = Pipeline::new();
+    let hir = pipeline.compile(SCHEMA).unwrap();
+    let mir = pipeline.to_mir(hir).unwrap();
+    let statements = pipeline.to_sql(&mir, Dialect::Postgres).unwrap();
+    assert!(statements.iter().any(|s| s.starts_with("CREATE TABLE") && s.contains("users")), "{statements:?}");
+    assert!(statements.iter().any(|s| s.starts_with("-- let adults\nSELECT")), "{statements:?}");
+    // Statement-joining with `;` reproduces the single-script form exactly.
+    let joined = statements.iter().map(|s| format!("{s};")).collect::<Vec<_>>().join("\n\n");
+    assert_eq!(joined, SqlGenerator::new(&mir, Dialect::Postgres).generate_sql());
+
+    let errors = pipeline.compile("struct User { id: Unknown }").unwrap_err();
+    assert!(!errors.is_empty());
+}
+
+#[test]
+fn emits_unique_nulls_not_distinct_for_postgres_only() {
+    let source = r#"
+struct User {
+    id: Key<User, i64>,
+    email: String? @unique(nulls_not_distinct: true),
+    name: String @unique,
+}
+"#;
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    let postgres = SqlGenerator::new(&mir, Dialect::Postgres);
+    postgres.validate().unwrap();
+    let sql = postgres.generate_sql();
+    assert!(sql.contains("email TEXT UNIQUE NULLS NOT DISTINCT"), "{sql}");
+    assert!(sql.contains("name TEXT NOT NULL UNIQUE"), "{sql}");
+    // The other dialects have no equivalent clause and must refuse.
+    for dialect in [Dialect::MySql, Dialect::Sqlite] {
+        let error = SqlGenerator::new(&mir, dialect).validate().unwrap_err();
+        assert!(error.message().contains("nulls_not_distinct"), "{error:?}");
+    }
+    let error = Pipeline::new().to_sql(&mir, Dialect::Sqlite).unwrap_err();
+    assert!(error.message().contains("postgres"), "{error:?}");
+}
+
+#[test]
+fn infers_list_literal_element_types() {
+    use kql_analyzer::hir::visit::{HirVisitor, walk_expr};
+    use kql_analyzer::hir::{HirExpr, HirExprKind, HirType, PrimitiveType};
+
+    struct ListTypes(Vec<HirType>);
+    impl HirVisitor for ListTypes {
+        fn visit_expr(&mut self, expr: &HirExpr) {
+            if matches!(expr.kind, HirExprKind::List(_)) {
+                self.0.push(expr.ty.clone());
+            }
+            walk_expr(self, expr);
+        }
+    }
+
+    let source = "struct User { id: Key<User, i64> }\n\nlet q = User.filter { count([1, 2, 3]) >= 1 }\n";
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mut lists = ListTypes(Vec::new());
+    lists.visit_program(&hir);
+    assert_eq!(lists.0, [HirType::List(Box::new(HirType::Primitive(PrimitiveType::I32)))]);
+}
+
+#[test]
+fn emits_functional_indexes() {
+    let source = r#"
+@index(lower(email))
+@index(columns: [name, lower(email).desc()])
+struct User {
+    id: Key<User, i64>,
+    email: String,
+    name: String,
+}
+"#;
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    let postgres = SqlGenerator::new(&mir, Dialect::Postgres).generate_sql();
+    assert!(postgres.contains("CREATE INDEX user_lower_email_idx ON user (lower(email))"), "{postgres}");
+    assert!(postgres.contains("CREATE INDEX user_name_lower_email_idx ON user (name, lower(email) DESC)"), "{postgres}");
+    // MySQL requires its own parentheses around a functional key part.
+    let mysql = SqlGenerator::new(&mir, Dialect::MySql).generate_sql();
+    assert!(mysql.contains("ON user ((lower(email)))"), "{mysql}");
+}
+
+#[test]
+fn renders_boolean_columns_per_dialect() {
+    let source = "struct User {\n    id: Key<User, i64>,\n    active: bool @default(true),\n}\n";
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    let postgres = SqlGenerator::new(&mir, Dialect::Postgres).generate_sql();
+    assert!(postgres.contains("active BOOLEAN NOT NULL DEFAULT TRUE"), "{postgres}");
+    // MySQL's BOOLEAN is an alias for TINYINT(1) and sqlite stores integers,
+    // so both spell the storage type and take numeric defaults.
+    let mysql = SqlGenerator::new(&mir, Dialect::MySql).generate_sql();
+    assert!(mysql.contains("active TINYINT(1) NOT NULL DEFAULT 1"), "{mysql}");
+    let sqlite = SqlGenerator::new(&mir, Dialect::Sqlite).generate_sql();
+    assert!(sqlite.contains("active INTEGER NOT NULL DEFAULT 1"), "{sqlite}");
+}
+
+#[test]
+fn required_relations_join_with_inner() {
+    let source = r#"
+struct User { id: Key<User, i64> }
+
+struct Post {
+    id: Key<Post, i64>,
+    author: ForeignKey<User> @relation(required: true),
+    editor: ForeignKey<User>?,
+}
+
+let posts = Post.filter { $.id >= 1 }
+"#;
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    let generator = SqlGenerator::new(&mir, Dialect::Postgres);
+    let sql = generator.generate_select(&mir.queries[0], &["author", "editor"]);
+    assert!(sql.contains("INNER JOIN user ON post.author_id = user.id"), "{sql}");
+    assert!(sql.contains("LEFT OUTER JOIN user ON post.editor_id = user.id"), "{sql}");
+}
+
+#[test]
+fn rejects_queries_over_undefined_sources() {
+    let source = "struct User { id: Key<User, i64> }\n\nlet q = Missing.filter { $.id >= 1 }\n";
+    let errors = Compiler::new().compile_source(source).unwrap_err();
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert!(errors[0].message().contains("`Missing` is not a known struct"), "{errors:?}");
+}
+
+#[test]
+fn rejects_mixed_type_list_literals() {
+    let source = "struct User { id: Key<User, i64> }\n\nlet q = User.filter { count([1, \"a\"]) >= 1 }\n";
+    let errors = Compiler::new().compile_source(source).unwrap_err();
+    assert!(errors.iter().any(|e| e.message().contains("same type")), "{errors:?}");
+}
+
+#[test]
+fn unknown_types_report_a_single_diagnostic() {
+    let source =
+        "struct User {\n    id: Key<User, i64>,\n    age: Wat,\n}\n\nlet adults = User.filter { $.age >= 18 && $.age >= 21 }\n";
+    let errors = Compiler::new().compile_source(source).unwrap_err();
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert!(errors[0].message().contains("unknown type `Wat`"), "{errors:?}");
+}
+
+#[test]
+fn update_and_delete_by_pk_can_return_the_row() {
+    let source = "struct User { id: Key<User, i64> @auto_increment, name: String }";
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    let table = mir.table_by_name("user").unwrap();
+    let update = SqlGenerator::new(&mir, Dialect::Postgres).generate_update_by_pk(table, true);
+    assert_eq!(update, "UPDATE user SET name = $1 WHERE id = $2 RETURNING *");
+    let delete = SqlGenerator::new(&mir, Dialect::Sqlite).generate_delete_by_pk(table, true);
+    assert_eq!(delete, "DELETE FROM user WHERE id = ? RETURNING *");
+    // MySQL has no RETURNING, so the flag renders nothing extra.
+    let update = SqlGenerator::new(&mir, Dialect::MySql).generate_update_by_pk(table, true);
+    assert_eq!(update, "UPDATE user SET name = ? WHERE id = ?");
+    let delete = SqlGenerator::new(&mir, Dialect::Postgres).generate_delete_by_pk(table, false);
+    assert_eq!(delete, "DELETE FROM user WHERE id = $1");
+}
+
+#[test]
+fn applies_table_prefix_to_tables_and_references() {
+    let source = r#"
+struct User { id: Key<User, i64> }
+
+@table("legacy_posts")
+struct Post {
+    id: Key<Post, i64>,
+    author: ForeignKey<User>,
+}
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mir = MirLowerer::new(hir).with_table_prefix("app_").lower().unwrap();
+    assert!(mir.table_by_name("app_user").is_some());
+    // An explicit `@table` name stays authoritative, but its references
+    // still point at the prefixed target.
+    let post = mir.table_by_name("legacy_posts").unwrap();
+    assert_eq!(post.foreign_keys[0].ref_table, "app_user");
+    assert_eq!(post.relations[0].to_table, "app_user");
+    let sql = SqlGenerator::new(&mir, Dialect::Postgres).generate_sql();
+    assert!(sql.contains("CREATE TABLE app_user"), "{sql}");
+    assert!(sql.contains("REFERENCES app_user"), "{sql}");
+}
+
+#[test]
+fn enum_checks_constrain_values_without_native_enums() {
+    let source = r#"
+enum Status { Active, Disabled, Archived }
+
+struct Account {
+    id: Key<Account, i64>,
+    status: Status,
+}
+"#;
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    let sqlite = SqlGenerator::new(&mir, Dialect::Sqlite).with_enum_checks(true).generate_sql();
+    assert!(sqlite.contains("status TEXT NOT NULL CHECK (status IN ('Active', 'Disabled', 'Archived'))"), "{sqlite}");
+    // Postgres swaps its native enum type for constrained TEXT.
+    let postgres = SqlGenerator::new(&mir, Dialect::Postgres).with_enum_checks(true).generate_sql();
+    assert!(!postgres.contains("CREATE TYPE"), "{postgres}");
+    assert!(postgres.contains("CHECK (status IN ('Active', 'Disabled', 'Archived'))"), "{postgres}");
+    // Without the flag, sqlite keeps its unchecked TEXT storage.
+    let plain = SqlGenerator::new(&mir, Dialect::Sqlite).generate_sql();
+    assert!(!plain.contains("CHECK"), "{plain}");
+}
+
+#[test]
+fn regroups_parenthesized_arithmetic() {
+    let source = r#"
+struct Order {
+    id: Key<Order, i64>,
+    price: i64,
+    tax: i64,
+    quantity: i64,
+}
+
+let totals = Order.filter { ($.price + $.tax) * $.quantity > 100 }
+"#;
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    let sql = SqlGenerator::new(&mir, Dialect::Postgres).generate_select(&mir.queries[0], &[]);
+    // The parser drops the grouping, so rendering must re-insert it from
+    // precedence; `price + tax * quantity` would compute something else.
+    assert!(sql.contains("(price + tax) * quantity > 100"), "{sql}");
+    // Grouping that matches precedence anyway stays unparenthesized.
+    let plain = source.replace("($.price + $.tax) * $.quantity", "$.price + $.tax * $.quantity");
+    let mir = MirLowerer::new(Compiler::new().compile_source(&plain).unwrap()).lower().unwrap();
+    let sql = SqlGenerator::new(&mir, Dialect::Postgres).generate_select(&mir.queries[0], &[]);
+    assert!(sql.contains("price + tax * quantity > 100"), "{sql}");
+}
+
+#[test]
+fn virtual_fields_stay_out_of_the_table() {
+    let source = r#"
+struct User {
+    id: Key<User, i64>,
+    name: String,
+    display_name: String @virtual,
+}
+
+let named = User.filter { $.display_name == "x" }
+"#;
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    assert!(mir.table_by_name("user").unwrap().column("display_name").is_none());
+    // The field is still type-checked in queries.
+    let bad = source.replace("== \"x\"", "== 1");
+    let errors = Compiler::new().compile_source(&bad).unwrap_err();
+    assert!(errors.iter().any(|e| e.message().contains("cannot compare")), "{errors:?}");
+    // Indexes cannot reference a field that has no column.
+    let indexed = source.replace("struct User {", "@index(display_name)\nstruct User {");
+    let error = MirLowerer::new(Compiler::new().compile_source(&indexed).unwrap()).lower().unwrap_err();
+    assert!(error.message().contains("`@virtual` fields have no column"), "{error:?}");
+}
+
+#[test]
+fn db_type_overrides_the_inferred_column_type() {
+    let source = r#"
+struct User {
+    id: Key<User, i64>,
+    email: String @db_type("CITEXT", dialect: "postgres"),
+    flags: i64 @db_type("BIT(8)"),
+}
+"#;
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    let postgres = SqlGenerator::new(&mir, Dialect::Postgres).generate_sql();
+    assert!(postgres.contains("email CITEXT NOT NULL"), "{postgres}");
+    assert!(postgres.contains("flags BIT(8) NOT NULL"), "{postgres}");
+    // The dialect-scoped override does not leak into other dialects.
+    let sqlite = SqlGenerator::new(&mir, Dialect::Sqlite).generate_sql();
+    assert!(sqlite.contains("email TEXT NOT NULL"), "{sqlite}");
+    assert!(sqlite.contains("flags BIT(8) NOT NULL"), "{sqlite}");
+    let bad = source.replace("\"postgres\"", "\"oracle\"");
+    let error = MirLowerer::new(Compiler::new().compile_source(&bad).unwrap()).lower().unwrap_err();
+    assert!(error.message().contains("unknown dialect `oracle`"), "{error}");
+}
+
+#[test]
+fn warns_on_mandatory_foreign_key_cycles() {
+    // A self-reference is satisfiable: insert with the FK pointing at the row
+    // itself or at an existing parent.
+    let self_reference = r#"
+struct Category {
+    id: Key<Category, i64>,
+    parent: ForeignKey<Category>?,
+}
+"#;
+    let hir = Compiler::new().compile_source(self_reference).unwrap();
+    assert!(hir.warnings.is_empty(), "{:?}", hir.warnings);
+
+    // A cycle broken by one nullable FK is satisfiable too.
+    let nullable_cycle = r#"
+struct User { id: Key<User, i64>, team: ForeignKey<Team>? }
+struct Team { id: Key<Team, i64>, owner: ForeignKey<User> }
+"#;
+    let hir = Compiler::new().compile_source(nullable_cycle).unwrap();
+    assert!(hir.warnings.is_empty(), "{:?}", hir.warnings);
+
+    // All-mandatory cycles admit no first insert.
+    let mandatory_cycle = r#"
+struct User { id: Key<User, i64>, team: ForeignKey<Team> }
+struct Team { id: Key<Team, i64>, owner: ForeignKey<User> }
+"#;
+    let hir = Compiler::new().compile_source(mandatory_cycle).unwrap();
+    assert_eq!(hir.warnings.len(), 1, "{:?}", hir.warnings);
+    let warning = &hir.warnings[0];
+    assert!(warning.message.contains("mandatory foreign keys form a cycle"), "{warning:?}");
+    assert!(warning.message.contains("`User`") && warning.message.contains("`Team`"), "{warning:?}");
+}
+
+#[test]
+fn deferrable_relations_render_on_postgres_only() {
+    let source = r#"
+struct User { id: Key<User, i64> }
+struct Post {
+    id: Key<Post, i64>,
+    author: ForeignKey<User> @relation(deferrable: true),
+}
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let sql = SqlGenerator::new(&mir, Dialect::Postgres).generate_sql();
+    assert!(
+        sql.contains(
+            "CONSTRAINT post_author_id_fkey FOREIGN KEY (author_id) REFERENCES user (id) DEFERRABLE INITIALLY DEFERRED"
+        ),
+        "{sql}"
+    );
+    // The other dialects enforce constraints immediately; the option is dropped.
+    for dialect in [Dialect::MySql, Dialect::Sqlite] {
+        let sql = SqlGenerator::new(&mir, dialect).generate_sql();
+        assert!(!sql.contains("DEFERRABLE"), "{sql}");
+    }
+}
+
+#[test]
+fn aliased_imports_resolve_under_the_alias_namespace() {
+    let imported = std::env::temp_dir().join("kql_alias_auth.kql");
+    std::fs::write(&imported, "struct User { id: Key<User, i64>, name: String }\n").unwrap();
+    let main = std::env::temp_dir().join("kql_alias_main.kql");
+    std::fs::write(
+        &main,
+        r#"
+import "kql_alias_auth.kql" as Auth
+
+struct Post {
+    id: Key<Post, i64>,
+    author: ForeignKey<Auth::User>,
+}
+"#,
+    )
+    .unwrap();
+
+    let hir = Compiler::new().compile_file(&main).unwrap();
+    assert!(hir.struct_by_name("Auth::User").is_some());
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let user = mir.table_by_name("user").expect("imported table lowered");
+    assert_eq!(user.schema.as_deref(), Some("auth"));
+    let post = mir.table_by_name("post").unwrap();
+    assert_eq!(post.foreign_keys[0].ref_table, "user");
+}
+
+#[test]
+fn snake_case_keeps_acronyms_and_digit_boundaries() {
+    use kql_analyzer::mir::mir_gen::to_snake_case;
+    assert_eq!(to_snake_case("SimpleCase"), "simple_case");
+    assert_eq!(to_snake_case("HTTPServer"), "http_server");
+    assert_eq!(to_snake_case("User2FA"), "user2_fa");
+    assert_eq!(to_snake_case("OAuthToken"), "o_auth_token");
+
+    // Table names follow: an acronym-heavy struct keeps its runs together.
+    let hir = Compiler::new().compile_source("struct APIKey { id: Key<APIKey, i64> }").unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    assert!(mir.table_by_name("api_key").is_some());
+}
+
+#[test]
+fn uuid_primary_keys_default_to_a_generated_uuid() {
+    let source = r#"
+struct User {
+    id: Key<User, Uuid> @default(gen_random_uuid()),
+    name: String,
+}
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let pg = SqlGenerator::new(&mir, Dialect::Postgres).generate_sql();
+    assert!(pg.contains("id UUID NOT NULL DEFAULT gen_random_uuid()"), "{pg}");
+    // A generated default is not auto-increment: no serial column.
+    assert!(!pg.contains("SERIAL"), "{pg}");
+    let mysql = SqlGenerator::new(&mir, Dialect::MySql).generate_sql();
+    assert!(mysql.contains("DEFAULT (UUID())"), "{mysql}");
+    let sqlite = SqlGenerator::new(&mir, Dialect::Sqlite).generate_sql();
+    assert!(sqlite.contains("DEFAULT (lower(hex(randomblob(16))))"), "{sqlite}");
+    assert!(!sqlite.contains("AUTOINCREMENT"), "{sqlite}");
+
+    // `@default(uuid())` is the same generator under its portable name.
+    let hir = Compiler::new().compile_source("struct T { id: Key<T, Uuid> @default(uuid()) }").unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let pg = SqlGenerator::new(&mir, Dialect::Postgres).generate_sql();
+    assert!(pg.contains("DEFAULT gen_random_uuid()"), "{pg}");
+
+    // The generator only makes sense on a UUID column.
+    let hir = Compiler::new().compile_source("struct T { id: Key<T, i64> @default(uuid()) }").unwrap();
+    let error = MirLowerer::new(hir).lower().unwrap_err();
+    assert!(error.to_string().contains("the column must have type `Uuid`"), "{error}");
+}
+
+#[test]
+fn drop_all_reverses_create_order() {
+    let source = r#"
+enum Status { Active, Retired }
+struct Comment { id: Key<Comment, i64>, post: ForeignKey<Post> }
+struct Post { id: Key<Post, i64>, author: ForeignKey<User> }
+struct User { id: Key<User, i64>, status: Status @default(Active) }
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let generator = SqlGenerator::new(&mir, Dialect::Postgres);
+    let creates: Vec<String> = generator
+        .generate_ddl()
+        .iter()
+        .filter_map(|s| match s {
+            kql_analyzer::lir::Statement::CreateTable(create) => Some(create.name.clone()),
+            _ => None,
+        })
+        .collect();
+    let drops: Vec<String> = generator
+        .generate_drop_all()
+        .iter()
+        .filter_map(|s| match s {
+            kql_analyzer::lir::Statement::DropTable { name, .. } => Some(name.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut reversed = creates.clone();
+    reversed.reverse();
+    assert_eq!(drops, reversed, "{creates:?}");
+
+    // Rendered teardown: referencing tables drop first, and the native enum
+    // type goes last.
+    let script = generator.generate_drop_all().iter().map(|s| generator.render(s)).collect::<Vec<_>>().join(";\n");
+    assert!(script.contains("DROP TABLE IF EXISTS comment"), "{script}");
+    assert!(script.ends_with("DROP TYPE IF EXISTS status"), "{script}");
+}
+
+#[test]
+fn record_projections_render_aliased_select_items() {
+    let source = r#"
+struct User {
+    id: Key<User, i64>,
+    name: String,
+}
+
+let handles = User.map { $.{ user_id: id, display: name } }
+"#;
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    let sql = SqlGenerator::new(&mir, Dialect::Postgres).generate_select(&mir.queries[0], &[]);
+    assert_eq!(sql, "SELECT id AS user_id, name AS display FROM user");
+
+    // The source side of each entry must name a real field.
+    let bad = r#"
+struct User { id: Key<User, i64> }
+let broken = User.map { $.{ user_id: missing } }
+"#;
+    let errors = Compiler::new().compile_source(bad).unwrap_err();
+    assert!(errors.iter().any(|e| e.to_string().contains("no field `missing` on struct `User`")), "{errors:?}");
+}
@@ -0,0 +1,145 @@
+use crate::Ident;
+use kql_types::Span;
+
+/// An expression together with the span it was parsed from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    /// Which form of expression this is.
+    pub kind: ExprKind,
+    /// Location in the source file.
+    pub span: Span,
+}
+
+/// The shape of an [Expr].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    /// A literal value.
+    Literal(Literal),
+    /// A bare identifier, e.g. a column name in an attribute or a table name heading a query.
+    Variable(Ident),
+    /// The `$` row variable inside a query closure.
+    Context,
+    /// A member access `base.name`.
+    Member {
+        /// The expression before the dot.
+        base: Box<Expr>,
+        /// The member name.
+        name: Ident,
+    },
+    /// A method call `base.name(args)` or `base.name { closure }`.
+    MethodCall {
+        /// The receiver expression.
+        base: Box<Expr>,
+        /// The method name.
+        method: Ident,
+        /// Parenthesized arguments, empty for `base.name()`.
+        args: Vec<Expr>,
+        /// The `{ ... }` closure body, for query operators like `filter`.
+        closure: Option<Box<Expr>>,
+    },
+    /// A free function call `name(args)`.
+    Call {
+        /// The function name.
+        func: Ident,
+        /// The call arguments.
+        args: Vec<Expr>,
+    },
+    /// A prefix unary operation.
+    Unary {
+        /// The operator.
+        op: UnaryOpKind,
+        /// The operand.
+        expr: Box<Expr>,
+    },
+    /// An infix binary operation.
+    Binary {
+        /// The operator.
+        op: BinaryOpKind,
+        /// Left operand.
+        lhs: Box<Expr>,
+        /// Right operand.
+        rhs: Box<Expr>,
+    },
+    /// A conditional `if cond then a else b`.
+    If {
+        /// The condition.
+        cond: Box<Expr>,
+        /// The value when the condition holds.
+        then_branch: Box<Expr>,
+        /// The value otherwise.
+        else_branch: Box<Expr>,
+    },
+    /// A list literal `[a, b, c]`.
+    List(Vec<Expr>),
+    /// A tuple literal `(a, b)`. A parenthesized single expression is not a
+    /// tuple.
+    Tuple(Vec<Expr>),
+    /// A renamed-field projection `$.{ alias: field, ... }`, producing an
+    /// anonymous record; only valid inside `.map { ... }`.
+    Record(Vec<RecordField>),
+}
+
+/// A single `alias: expr` entry of a `$.{ ... }` record projection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordField {
+    /// The output name.
+    pub name: Ident,
+    /// The projected expression.
+    pub value: Expr,
+}
+
+/// A literal value in the source.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    /// An integer literal, with an optional type suffix as written (`10i64`).
+    Int(i64, Option<String>),
+    /// A floating point literal, with an optional type suffix as written
+    /// (`2.5f32`).
+    Float(f64, Option<String>),
+    /// A string literal, with quotes and escapes resolved.
+    String(String),
+    /// A boolean literal.
+    Bool(bool),
+    /// The `*` wildcard, only valid as an aggregate argument like `count(*)`.
+    Star,
+}
+
+/// A prefix unary operator.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UnaryOpKind {
+    /// Arithmetic negation `-x`.
+    Neg,
+    /// Logical negation `!x`.
+    Not,
+}
+
+/// An infix binary operator.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BinaryOpKind {
+    /// `+`
+    Add,
+    /// `-`
+    Sub,
+    /// `*`
+    Mul,
+    /// `/`
+    Div,
+    /// `%`
+    Mod,
+    /// `==`
+    Eq,
+    /// `!=`
+    NotEq,
+    /// `<`
+    Lt,
+    /// `<=`
+    Le,
+    /// `>`
+    Gt,
+    /// `>=`
+    Ge,
+    /// Logical and, written `&`.
+    And,
+    /// Logical or, written `|`.
+    Or,
+}
@@ -0,0 +1,237 @@
+#![deny(missing_debug_implementations)]
+#![warn(rustdoc::missing_crate_level_docs)]
+#![doc = include_str!("../readme.md")]
+
+mod expr;
+mod ty;
+pub mod visit;
+
+pub use crate::{
+    expr::{BinaryOpKind, Expr, ExprKind, Literal, RecordField, UnaryOpKind},
+    ty::{Type, TypeArg, TypeKind},
+};
+use kql_types::Span;
+
+/// An identifier together with the span it was parsed from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ident {
+    /// The identifier text.
+    pub name: String,
+    /// Location in the source file.
+    pub span: Span,
+}
+
+/// The root of a parsed KQL file: a flat list of top-level declarations.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Database {
+    /// Top-level declarations in source order.
+    pub decls: Vec<Decl>,
+}
+
+/// Any declaration that can appear at the top level or inside a namespace.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Decl {
+    /// A `struct` declaration, mapped to a table.
+    Struct(StructDecl),
+    /// An `enum` declaration.
+    Enum(EnumDecl),
+    /// A `type` alias declaration.
+    TypeAlias(TypeAliasDecl),
+    /// A `let` query declaration.
+    Let(LetDecl),
+    /// A `namespace { ... }` block, mapped to a schema.
+    Namespace(NamespaceDecl),
+    /// An `import "file.kql"` declaration.
+    Import(ImportDecl),
+    /// A `seed Entity { ... }` fixture row declaration.
+    Seed(SeedDecl),
+}
+
+impl Decl {
+    /// The name this declaration introduces, if any.
+    pub fn name(&self) -> Option<&Ident> {
+        match self {
+            Self::Struct(s) => Some(&s.name),
+            Self::Enum(e) => Some(&e.name),
+            Self::TypeAlias(t) => Some(&t.name),
+            Self::Let(l) => Some(&l.name),
+            Self::Namespace(n) => Some(&n.name),
+            Self::Import(_) | Self::Seed(_) => None,
+        }
+    }
+    /// The span covering the whole declaration.
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Struct(s) => s.span,
+            Self::Enum(e) => e.span,
+            Self::TypeAlias(t) => t.span,
+            Self::Let(l) => l.span,
+            Self::Namespace(n) => n.span,
+            Self::Import(i) => i.span,
+            Self::Seed(s) => s.span,
+        }
+    }
+}
+
+/// A `struct` declaration and its fields.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructDecl {
+    /// The struct name.
+    pub name: Ident,
+    /// Generic type parameters, e.g. the `T` in `struct Paged<T>`.
+    pub generics: Vec<Ident>,
+    /// Attributes written before the declaration, e.g. `@table("users")`.
+    pub attributes: Vec<Attribute>,
+    /// The fields in source order.
+    pub fields: Vec<FieldDecl>,
+    /// `///` doc comments written above the declaration.
+    pub docs: Vec<String>,
+    /// Span covering the whole declaration.
+    pub span: Span,
+}
+
+/// A single `name: Type` field inside a struct.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDecl {
+    /// The field name.
+    pub name: Ident,
+    /// The declared type.
+    pub ty: Type,
+    /// Attributes written after the type, e.g. `@unique`.
+    pub attributes: Vec<Attribute>,
+    /// `///` doc comments written above the field.
+    pub docs: Vec<String>,
+    /// Span covering the whole field.
+    pub span: Span,
+}
+
+/// An `enum` declaration and its variants.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumDecl {
+    /// The enum name.
+    pub name: Ident,
+    /// Attributes written before the declaration.
+    pub attributes: Vec<Attribute>,
+    /// The variants in source order.
+    pub variants: Vec<VariantDecl>,
+    /// `///` doc comments written above the declaration.
+    pub docs: Vec<String>,
+    /// Span covering the whole declaration.
+    pub span: Span,
+}
+
+/// A single enum variant, optionally with an explicit stored value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariantDecl {
+    /// The variant name.
+    pub name: Ident,
+    /// Explicit stored value, e.g. `Active = 1`.
+    pub value: Option<Expr>,
+    /// `///` doc comments written above the variant.
+    pub docs: Vec<String>,
+    /// Span covering the variant.
+    pub span: Span,
+}
+
+/// A `type Alias = T` declaration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeAliasDecl {
+    /// The alias name.
+    pub name: Ident,
+    /// The aliased type.
+    pub ty: Type,
+    /// `///` doc comments written above the declaration.
+    pub docs: Vec<String>,
+    /// Span covering the whole declaration.
+    pub span: Span,
+}
+
+/// A `let name = <query expression>` declaration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LetDecl {
+    /// The query name.
+    pub name: Ident,
+    /// The query expression, usually a chain of method calls.
+    pub value: Expr,
+    /// `///` doc comments written above the declaration.
+    pub docs: Vec<String>,
+    /// Span covering the whole declaration.
+    pub span: Span,
+}
+
+/// A `namespace Name { ... }` block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamespaceDecl {
+    /// The namespace name.
+    pub name: Ident,
+    /// Declarations nested inside the block.
+    pub decls: Vec<Decl>,
+    /// Span covering the whole block.
+    pub span: Span,
+}
+
+/// An `import "path.kql"` declaration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportDecl {
+    /// The imported file path as written in the source.
+    pub path: String,
+    /// The namespace alias from `import "..." as Name`, if given; the file's
+    /// top-level declarations then resolve under the `Name::` prefix.
+    pub alias: Option<Ident>,
+    /// Span covering the whole declaration.
+    pub span: Span,
+}
+
+/// A `seed Entity { field: value, ... }` declaration: one fixture row for the
+/// named struct.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeedDecl {
+    /// The struct the row belongs to.
+    pub target: Ident,
+    /// The `field: value` entries in source order.
+    pub values: Vec<SeedValue>,
+    /// Span covering the whole declaration.
+    pub span: Span,
+}
+
+/// A single `field: value` entry inside a seed block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeedValue {
+    /// The field name.
+    pub name: Ident,
+    /// The literal value.
+    pub value: Expr,
+    /// Span covering the entry.
+    pub span: Span,
+}
+
+/// An `@name(args)` attribute on a declaration or field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    /// The attribute name, without the leading `@`.
+    pub name: Ident,
+    /// Arguments between the parentheses, possibly empty.
+    pub args: Vec<AttributeArg>,
+    /// Span covering the whole attribute.
+    pub span: Span,
+}
+
+impl Attribute {
+    /// The first positional argument, if any.
+    pub fn first_arg(&self) -> Option<&Expr> {
+        self.args.first().map(|a| &a.value)
+    }
+    /// The value of the named argument `name`, if present.
+    pub fn named_arg(&self, name: &str) -> Option<&Expr> {
+        self.args.iter().find(|a| a.name.as_ref().is_some_and(|n| n.name == name)).map(|a| &a.value)
+    }
+}
+
+/// A single attribute argument, positional (`@default(0)`) or named (`@index(unique: true)`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeArg {
+    /// The argument name for named arguments.
+    pub name: Option<Ident>,
+    /// The argument value.
+    pub value: Expr,
+}
@@ -0,0 +1,44 @@
+use crate::Ident;
+use kql_types::Span;
+
+/// A type annotation as written in the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Type {
+    /// Which form of type this is.
+    pub kind: TypeKind,
+    /// Location in the source file.
+    pub span: Span,
+}
+
+/// The shape of a [Type].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeKind {
+    /// A possibly qualified, possibly generic named type: `User`, `auth::User`, `Key<User, i32>`.
+    Named {
+        /// The `::`-separated path segments.
+        path: Vec<Ident>,
+        /// Generic arguments between `<` and `>`, possibly empty.
+        args: Vec<TypeArg>,
+    },
+    /// A list type `[T]`.
+    List(Box<Type>),
+    /// An optional type `T?`.
+    Optional(Box<Type>),
+    /// An anonymous tuple type `(A, B)`. A parenthesized single type is not
+    /// a tuple.
+    Tuple(Vec<Type>),
+}
+
+/// A generic argument, positional (`Key<User, i32>`) or named (`ForeignKey<author: User>`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeArg {
+    /// A plain type argument.
+    Type(Type),
+    /// A `name: Type` argument.
+    Named {
+        /// The argument name.
+        name: Ident,
+        /// The argument type.
+        ty: Type,
+    },
+}
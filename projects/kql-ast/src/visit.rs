@@ -0,0 +1,237 @@
+//! A read-only visitor over the AST.
+//!
+//! Every [Visitor] method defaults to walking the node's children via the
+//! matching `walk_*` function, so an implementation overrides only the nodes
+//! it cares about and calls the `walk_*` function itself when it still wants
+//! to descend.
+
+use crate::{
+    Attribute, Database, Decl, EnumDecl, Expr, ExprKind, FieldDecl, Ident, ImportDecl, LetDecl, NamespaceDecl, SeedDecl,
+    StructDecl, Type, TypeAliasDecl, TypeArg, TypeKind, VariantDecl,
+};
+
+/// A traversal over a parsed [Database] and everything nested inside it.
+pub trait Visitor {
+    fn visit_database(&mut self, db: &Database) {
+        walk_database(self, db);
+    }
+    fn visit_decl(&mut self, decl: &Decl) {
+        walk_decl(self, decl);
+    }
+    fn visit_struct(&mut self, decl: &StructDecl) {
+        walk_struct(self, decl);
+    }
+    fn visit_field(&mut self, field: &FieldDecl) {
+        walk_field(self, field);
+    }
+    fn visit_enum(&mut self, decl: &EnumDecl) {
+        walk_enum(self, decl);
+    }
+    fn visit_variant(&mut self, variant: &VariantDecl) {
+        walk_variant(self, variant);
+    }
+    fn visit_type_alias(&mut self, decl: &TypeAliasDecl) {
+        walk_type_alias(self, decl);
+    }
+    fn visit_let(&mut self, decl: &LetDecl) {
+        walk_let(self, decl);
+    }
+    fn visit_namespace(&mut self, decl: &NamespaceDecl) {
+        walk_namespace(self, decl);
+    }
+    fn visit_import(&mut self, decl: &ImportDecl) {
+        let _ = decl;
+    }
+    fn visit_seed(&mut self, decl: &SeedDecl) {
+        walk_seed(self, decl);
+    }
+    fn visit_attribute(&mut self, attribute: &Attribute) {
+        walk_attribute(self, attribute);
+    }
+    fn visit_type(&mut self, ty: &Type) {
+        walk_type(self, ty);
+    }
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr);
+    }
+    fn visit_ident(&mut self, ident: &Ident) {
+        let _ = ident;
+    }
+}
+
+/// Visit every declaration of the database.
+pub fn walk_database<V: Visitor + ?Sized>(visitor: &mut V, db: &Database) {
+    for decl in &db.decls {
+        visitor.visit_decl(decl);
+    }
+}
+
+/// Dispatch a declaration to the method for its kind.
+pub fn walk_decl<V: Visitor + ?Sized>(visitor: &mut V, decl: &Decl) {
+    match decl {
+        Decl::Struct(s) => visitor.visit_struct(s),
+        Decl::Enum(e) => visitor.visit_enum(e),
+        Decl::TypeAlias(t) => visitor.visit_type_alias(t),
+        Decl::Let(l) => visitor.visit_let(l),
+        Decl::Namespace(n) => visitor.visit_namespace(n),
+        Decl::Import(i) => visitor.visit_import(i),
+        Decl::Seed(s) => visitor.visit_seed(s),
+    }
+}
+
+/// Visit the name, generics, attributes and fields of a struct.
+pub fn walk_struct<V: Visitor + ?Sized>(visitor: &mut V, decl: &StructDecl) {
+    visitor.visit_ident(&decl.name);
+    for generic in &decl.generics {
+        visitor.visit_ident(generic);
+    }
+    for attribute in &decl.attributes {
+        visitor.visit_attribute(attribute);
+    }
+    for field in &decl.fields {
+        visitor.visit_field(field);
+    }
+}
+
+/// Visit the name, type and attributes of a field.
+pub fn walk_field<V: Visitor + ?Sized>(visitor: &mut V, field: &FieldDecl) {
+    visitor.visit_ident(&field.name);
+    visitor.visit_type(&field.ty);
+    for attribute in &field.attributes {
+        visitor.visit_attribute(attribute);
+    }
+}
+
+/// Visit the name, attributes and variants of an enum.
+pub fn walk_enum<V: Visitor + ?Sized>(visitor: &mut V, decl: &EnumDecl) {
+    visitor.visit_ident(&decl.name);
+    for attribute in &decl.attributes {
+        visitor.visit_attribute(attribute);
+    }
+    for variant in &decl.variants {
+        visitor.visit_variant(variant);
+    }
+}
+
+/// Visit the name and explicit value of a variant.
+pub fn walk_variant<V: Visitor + ?Sized>(visitor: &mut V, variant: &VariantDecl) {
+    visitor.visit_ident(&variant.name);
+    if let Some(value) = &variant.value {
+        visitor.visit_expr(value);
+    }
+}
+
+/// Visit the name and target type of a type alias.
+pub fn walk_type_alias<V: Visitor + ?Sized>(visitor: &mut V, decl: &TypeAliasDecl) {
+    visitor.visit_ident(&decl.name);
+    visitor.visit_type(&decl.ty);
+}
+
+/// Visit the name and query expression of a `let` declaration.
+pub fn walk_let<V: Visitor + ?Sized>(visitor: &mut V, decl: &LetDecl) {
+    visitor.visit_ident(&decl.name);
+    visitor.visit_expr(&decl.value);
+}
+
+/// Visit the name and nested declarations of a namespace.
+pub fn walk_namespace<V: Visitor + ?Sized>(visitor: &mut V, decl: &NamespaceDecl) {
+    visitor.visit_ident(&decl.name);
+    for nested in &decl.decls {
+        visitor.visit_decl(nested);
+    }
+}
+
+/// Visit the target and `field: value` entries of a seed row.
+pub fn walk_seed<V: Visitor + ?Sized>(visitor: &mut V, decl: &SeedDecl) {
+    visitor.visit_ident(&decl.target);
+    for value in &decl.values {
+        visitor.visit_ident(&value.name);
+        visitor.visit_expr(&value.value);
+    }
+}
+
+/// Visit the name and argument expressions of an attribute.
+pub fn walk_attribute<V: Visitor + ?Sized>(visitor: &mut V, attribute: &Attribute) {
+    visitor.visit_ident(&attribute.name);
+    for arg in &attribute.args {
+        if let Some(name) = &arg.name {
+            visitor.visit_ident(name);
+        }
+        visitor.visit_expr(&arg.value);
+    }
+}
+
+/// Visit the path idents and nested types of a type expression.
+pub fn walk_type<V: Visitor + ?Sized>(visitor: &mut V, ty: &Type) {
+    match &ty.kind {
+        TypeKind::Named { path, args } => {
+            for segment in path {
+                visitor.visit_ident(segment);
+            }
+            for arg in args {
+                match arg {
+                    TypeArg::Type(ty) => visitor.visit_type(ty),
+                    TypeArg::Named { name, ty } => {
+                        visitor.visit_ident(name);
+                        visitor.visit_type(ty);
+                    }
+                }
+            }
+        }
+        TypeKind::List(inner) | TypeKind::Optional(inner) => visitor.visit_type(inner),
+        TypeKind::Tuple(items) => {
+            for item in items {
+                visitor.visit_type(item);
+            }
+        }
+    }
+}
+
+/// Visit every sub-expression of an expression.
+pub fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expr) {
+    match &expr.kind {
+        ExprKind::Literal(_) | ExprKind::Context => {}
+        ExprKind::Variable(ident) => visitor.visit_ident(ident),
+        ExprKind::Member { base, name } => {
+            visitor.visit_expr(base);
+            visitor.visit_ident(name);
+        }
+        ExprKind::MethodCall { base, method, args, closure } => {
+            visitor.visit_expr(base);
+            visitor.visit_ident(method);
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+            if let Some(closure) = closure {
+                visitor.visit_expr(closure);
+            }
+        }
+        ExprKind::Call { func, args } => {
+            visitor.visit_ident(func);
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+        }
+        ExprKind::Unary { expr, .. } => visitor.visit_expr(expr),
+        ExprKind::Binary { lhs, rhs, .. } => {
+            visitor.visit_expr(lhs);
+            visitor.visit_expr(rhs);
+        }
+        ExprKind::If { cond, then_branch, else_branch } => {
+            visitor.visit_expr(cond);
+            visitor.visit_expr(then_branch);
+            visitor.visit_expr(else_branch);
+        }
+        ExprKind::List(items) | ExprKind::Tuple(items) => {
+            for item in items {
+                visitor.visit_expr(item);
+            }
+        }
+        ExprKind::Record(fields) => {
+            for field in fields {
+                visitor.visit_ident(&field.name);
+                visitor.visit_expr(&field.value);
+            }
+        }
+    }
+}
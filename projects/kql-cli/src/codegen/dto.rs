@@ -0,0 +1,106 @@
+//! Plain serde DTO generation: dependency-light structs and enums mirroring
+//! the KQL types, without the sqlx machinery of [super::rust].
+
+use kql_analyzer::hir::{DeclId, HirProgram, HirStruct, HirType, PrimitiveType};
+use std::fmt::Write;
+
+/// Generate a Rust module with a `Serialize`/`Deserialize` struct for every
+/// KQL struct and a Rust enum for every KQL enum in `hir`.
+pub fn generate(hir: &HirProgram) -> String {
+    let mut out = String::new();
+    out.push_str("//! Generated by `kql generate`. Do not edit.\n\n");
+    out.push_str("use serde::{Deserialize, Serialize};\n\n");
+    for item in hir.enums.values() {
+        for doc in &item.docs {
+            writeln!(out, "/// {}", doc).unwrap();
+        }
+        writeln!(out, "#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]").unwrap();
+        writeln!(out, "pub enum {} {{", item.name).unwrap();
+        for variant in &item.variants {
+            writeln!(out, "    {},", variant.name).unwrap();
+        }
+        out.push_str("}\n\n");
+    }
+    for item in hir.structs.values() {
+        generate_struct(&mut out, hir, item);
+    }
+    out
+}
+
+fn generate_struct(out: &mut String, hir: &HirProgram, item: &HirStruct) {
+    for doc in &item.docs {
+        writeln!(out, "/// {}", doc).unwrap();
+    }
+    writeln!(out, "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]").unwrap();
+    writeln!(out, "pub struct {} {{", item.name).unwrap();
+    for field in &item.fields {
+        for doc in &field.docs {
+            writeln!(out, "    /// {}", doc).unwrap();
+        }
+        writeln!(out, "    pub {}: {},", field.name, rust_type(hir, &field.ty)).unwrap();
+    }
+    out.push_str("}\n\n");
+}
+
+fn rust_type(hir: &HirProgram, ty: &HirType) -> String {
+    match ty {
+        HirType::Primitive(primitive) => primitive_type(*primitive).to_string(),
+        HirType::Optional(inner) => format!("Option<{}>", rust_type(hir, inner)),
+        HirType::List(inner) => format!("Vec<{}>", rust_type(hir, inner)),
+        HirType::Tuple(items) => {
+            let items: Vec<String> = items.iter().map(|item| rust_type(hir, item)).collect();
+            format!("({})", items.join(", "))
+        }
+        HirType::Struct(id) | HirType::Enum(id) => decl_type_name(hir, *id),
+        HirType::Key { ty, .. } => rust_type(hir, ty),
+        // A DTO carries the referenced entity's key, not the entity itself.
+        HirType::ForeignKey { entity, .. } => foreign_key_type(hir, *entity),
+        HirType::Unknown => "serde_json::Value".to_string(),
+    }
+}
+
+/// The Rust type of the referenced entity's primary key, defaulting to `i64`.
+fn foreign_key_type(hir: &HirProgram, entity: DeclId) -> String {
+    if let Some(item) = hir.structs.get(&entity) {
+        for field in &item.fields {
+            if let HirType::Key { ty, .. } = &field.ty {
+                return rust_type(hir, ty);
+            }
+        }
+    }
+    "i64".to_string()
+}
+
+fn decl_type_name(hir: &HirProgram, id: DeclId) -> String {
+    if let Some(item) = hir.structs.get(&id) {
+        return item.name.clone();
+    }
+    if let Some(item) = hir.enums.get(&id) {
+        return item.name.clone();
+    }
+    "serde_json::Value".to_string()
+}
+
+fn primitive_type(primitive: PrimitiveType) -> &'static str {
+    match primitive {
+        PrimitiveType::I8 => "i8",
+        PrimitiveType::I16 => "i16",
+        PrimitiveType::I32 => "i32",
+        PrimitiveType::I64 => "i64",
+        PrimitiveType::U8 => "u8",
+        PrimitiveType::U16 => "u16",
+        PrimitiveType::U32 => "u32",
+        PrimitiveType::U64 => "u64",
+        PrimitiveType::F32 => "f32",
+        PrimitiveType::F64 => "f64",
+        PrimitiveType::Bool => "bool",
+        PrimitiveType::Json => "serde_json::Value",
+        // Textual encodings keep the DTOs free of chrono/uuid dependencies.
+        PrimitiveType::D128
+        | PrimitiveType::String
+        | PrimitiveType::DateTime
+        | PrimitiveType::Date
+        | PrimitiveType::Time
+        | PrimitiveType::Uuid => "String",
+    }
+}
@@ -0,0 +1,6 @@
+//! Code generators driven by `kql generate` and `kql compile --emit`.
+
+pub mod dto;
+pub mod openapi;
+pub mod proto;
+pub mod rust;
@@ -0,0 +1,141 @@
+//! OpenAPI 3 schema generation: one `components.schemas` entry per struct
+//! and enum, for scaffolding REST services over the model.
+
+use kql_analyzer::hir::{EnumLayout, HirProgram, HirStruct, HirType, PrimitiveType};
+use serde_json::{Map, Value, json};
+
+/// Generate an OpenAPI 3 document with a `components.schemas` entry for every
+/// struct and enum in `hir`, serialized as pretty JSON.
+pub fn generate(hir: &HirProgram) -> String {
+    let mut schemas = Map::new();
+    for item in hir.enums.values() {
+        let name = schema_name(&item.namespace, &item.name);
+        let schema = match item.layout {
+            EnumLayout::String => json!({
+                "type": "string",
+                "enum": item.variants.iter().map(|v| v.name.clone()).collect::<Vec<_>>(),
+            }),
+            EnumLayout::Int => json!({
+                "type": "integer",
+                "enum": item.variants.iter().map(|v| v.value).collect::<Vec<_>>(),
+            }),
+        };
+        schemas.insert(name, schema);
+    }
+    for item in hir.structs.values() {
+        schemas.insert(schema_name(&item.namespace, &item.name), struct_schema(hir, item));
+    }
+    let document = json!({
+        "openapi": "3.0.3",
+        "info": { "title": "KQL schema", "version": "0.0.0" },
+        "components": { "schemas": Value::Object(schemas) },
+    });
+    let mut out = serde_json::to_string_pretty(&document).expect("document serializes");
+    out.push('\n');
+    out
+}
+
+fn struct_schema(hir: &HirProgram, item: &HirStruct) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for field in &item.fields {
+        let mut schema = type_schema(hir, &field.ty);
+        if !field.docs.is_empty() {
+            if let Value::Object(map) = &mut schema {
+                map.insert("description".to_string(), Value::String(field.docs.join("\n")));
+            }
+        }
+        if !matches!(field.ty, HirType::Optional(_)) {
+            required.push(field.name.clone());
+        }
+        properties.insert(field.name.clone(), schema);
+    }
+    let mut schema = Map::new();
+    schema.insert("type".to_string(), Value::String("object".to_string()));
+    if !item.docs.is_empty() {
+        schema.insert("description".to_string(), Value::String(item.docs.join("\n")));
+    }
+    schema.insert("properties".to_string(), Value::Object(properties));
+    if !required.is_empty() {
+        schema.insert("required".to_string(), required.into_iter().map(Value::String).collect());
+    }
+    Value::Object(schema)
+}
+
+fn type_schema(hir: &HirProgram, ty: &HirType) -> Value {
+    match ty {
+        HirType::Primitive(primitive) => primitive_schema(*primitive),
+        HirType::Struct(id) | HirType::Enum(id) => match hir.id_to_kind.get(id) {
+            Some(_) => {
+                let (namespace, name) = match hir.structs.get(id) {
+                    Some(s) => (s.namespace.clone(), s.name.clone()),
+                    None => match hir.enums.get(id) {
+                        Some(e) => (e.namespace.clone(), e.name.clone()),
+                        None => return json!({}),
+                    },
+                };
+                json!({ "$ref": format!("#/components/schemas/{}", schema_name(&namespace, &name)) })
+            }
+            None => json!({}),
+        },
+        HirType::List(inner) => json!({ "type": "array", "items": type_schema(hir, inner) }),
+        HirType::Optional(inner) => {
+            let mut schema = type_schema(hir, inner);
+            if let Value::Object(map) = &mut schema {
+                map.insert("nullable".to_string(), Value::Bool(true));
+            }
+            schema
+        }
+        HirType::Tuple(items) => json!({
+            "type": "array",
+            "prefixItems": items.iter().map(|item| type_schema(hir, item)).collect::<Vec<_>>(),
+        }),
+        // Keys and foreign keys surface as their underlying primitive: an API
+        // client sees the id value, not the relation.
+        HirType::Key { ty, .. } => type_schema(hir, ty),
+        HirType::ForeignKey { entity, .. } => foreign_key_schema(hir, *entity),
+        HirType::Unknown => json!({}),
+    }
+}
+
+/// The schema of the referenced entity's primary key, defaulting to `int64`.
+fn foreign_key_schema(hir: &HirProgram, entity: kql_analyzer::hir::DeclId) -> Value {
+    if let Some(item) = hir.structs.get(&entity) {
+        for field in &item.fields {
+            if let HirType::Key { ty, .. } = &field.ty {
+                return type_schema(hir, ty);
+            }
+        }
+    }
+    json!({ "type": "integer", "format": "int64" })
+}
+
+fn primitive_schema(primitive: PrimitiveType) -> Value {
+    match primitive {
+        PrimitiveType::I8
+        | PrimitiveType::I16
+        | PrimitiveType::I32
+        | PrimitiveType::U8
+        | PrimitiveType::U16
+        | PrimitiveType::U32 => json!({ "type": "integer", "format": "int32" }),
+        PrimitiveType::I64 | PrimitiveType::U64 => json!({ "type": "integer", "format": "int64" }),
+        PrimitiveType::F32 => json!({ "type": "number", "format": "float" }),
+        PrimitiveType::F64 => json!({ "type": "number", "format": "double" }),
+        PrimitiveType::D128 => json!({ "type": "string", "format": "decimal" }),
+        PrimitiveType::Bool => json!({ "type": "boolean" }),
+        PrimitiveType::String => json!({ "type": "string" }),
+        PrimitiveType::DateTime => json!({ "type": "string", "format": "date-time" }),
+        PrimitiveType::Date => json!({ "type": "string", "format": "date" }),
+        PrimitiveType::Time => json!({ "type": "string", "format": "time" }),
+        PrimitiveType::Uuid => json!({ "type": "string", "format": "uuid" }),
+        PrimitiveType::Json => json!({}),
+    }
+}
+
+/// The `components.schemas` key for a declaration: namespace segments and the
+/// name joined with `.`, which stays within OpenAPI's allowed key characters.
+fn schema_name(namespace: &[String], name: &str) -> String {
+    let mut parts: Vec<&str> = namespace.iter().map(String::as_str).collect();
+    parts.push(name);
+    parts.join(".")
+}
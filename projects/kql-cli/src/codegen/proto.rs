@@ -0,0 +1,123 @@
+//! Protobuf generation: one `message` per struct and one `enum` per KQL enum,
+//! for exposing the model over gRPC.
+
+use kql_analyzer::hir::{DeclId, HirProgram, HirStruct, HirType, PrimitiveType};
+use std::fmt::Write;
+
+/// Generate a proto3 file with a message for every struct and an enum for
+/// every KQL enum in `hir`.
+///
+/// Field numbers follow declaration order, so appending a field keeps every
+/// existing number stable.
+pub fn generate(hir: &HirProgram) -> String {
+    let mut out = String::new();
+    out.push_str("// Generated by `kql generate`. Do not edit.\n");
+    out.push_str("syntax = \"proto3\";\n");
+    if let Some(package) = shared_package(hir) {
+        writeln!(out, "\npackage {};", package).unwrap();
+    }
+    for item in hir.enums.values() {
+        writeln!(out, "\nenum {} {{", item.name).unwrap();
+        // proto3 requires the first value to be zero, so values are assigned
+        // by position regardless of the declared KQL values.
+        for (index, variant) in item.variants.iter().enumerate() {
+            writeln!(out, "    {} = {};", to_shouty_case(&variant.name), index).unwrap();
+        }
+        out.push_str("}\n");
+    }
+    for item in hir.structs.values() {
+        generate_message(&mut out, hir, item);
+    }
+    out
+}
+
+fn generate_message(out: &mut String, hir: &HirProgram, item: &HirStruct) {
+    writeln!(out, "\nmessage {} {{", item.name).unwrap();
+    for (index, field) in item.fields.iter().enumerate() {
+        let (rule, ty) = match &field.ty {
+            HirType::List(inner) => ("repeated ", proto_type(hir, inner)),
+            HirType::Optional(inner) => ("optional ", proto_type(hir, inner)),
+            other => ("", proto_type(hir, other)),
+        };
+        writeln!(out, "    {}{} {} = {};", rule, ty, field.name, index + 1).unwrap();
+    }
+    out.push_str("}\n");
+}
+
+fn proto_type(hir: &HirProgram, ty: &HirType) -> String {
+    match ty {
+        HirType::Primitive(primitive) => primitive_type(*primitive).to_string(),
+        HirType::Struct(id) => decl_type_name(hir, *id),
+        HirType::Enum(id) => decl_type_name(hir, *id),
+        // Nested lists and tuples have no direct proto shape; they travel as
+        // JSON text, matching their relational representation.
+        HirType::List(_) | HirType::Tuple(_) => "string".to_string(),
+        HirType::Optional(inner) => proto_type(hir, inner),
+        HirType::Key { ty, .. } => proto_type(hir, ty),
+        HirType::ForeignKey { entity, .. } => foreign_key_type(hir, *entity),
+        HirType::Unknown => "string".to_string(),
+    }
+}
+
+/// The proto type of the referenced entity's primary key, defaulting to `int64`.
+fn foreign_key_type(hir: &HirProgram, entity: DeclId) -> String {
+    if let Some(item) = hir.structs.get(&entity) {
+        for field in &item.fields {
+            if let HirType::Key { ty, .. } = &field.ty {
+                return proto_type(hir, ty);
+            }
+        }
+    }
+    "int64".to_string()
+}
+
+fn decl_type_name(hir: &HirProgram, id: DeclId) -> String {
+    if let Some(item) = hir.structs.get(&id) {
+        return item.name.clone();
+    }
+    if let Some(item) = hir.enums.get(&id) {
+        return item.name.clone();
+    }
+    "string".to_string()
+}
+
+fn primitive_type(primitive: PrimitiveType) -> &'static str {
+    match primitive {
+        PrimitiveType::I8 | PrimitiveType::I16 | PrimitiveType::I32 => "int32",
+        PrimitiveType::I64 => "int64",
+        PrimitiveType::U8 | PrimitiveType::U16 | PrimitiveType::U32 => "uint32",
+        PrimitiveType::U64 => "uint64",
+        PrimitiveType::F32 => "float",
+        PrimitiveType::F64 => "double",
+        PrimitiveType::Bool => "bool",
+        PrimitiveType::D128
+        | PrimitiveType::String
+        | PrimitiveType::DateTime
+        | PrimitiveType::Date
+        | PrimitiveType::Time
+        | PrimitiveType::Uuid
+        | PrimitiveType::Json => "string",
+    }
+}
+
+/// The `package` line when every declaration lives in the same namespace.
+fn shared_package(hir: &HirProgram) -> Option<String> {
+    let mut namespaces = hir.structs.values().map(|s| &s.namespace).chain(hir.enums.values().map(|e| &e.namespace));
+    let first = namespaces.next()?;
+    if first.is_empty() || namespaces.any(|n| n != first) {
+        return None;
+    }
+    Some(first.join("."))
+}
+
+/// Convert a PascalCase variant name into SHOUTY_SNAKE_CASE.
+fn to_shouty_case(name: &str) -> String {
+    let mut out = String::new();
+    for (index, c) in name.chars().enumerate() {
+        if c.is_uppercase() && index > 0 {
+            out.push('_');
+        }
+        out.extend(c.to_uppercase());
+    }
+    out
+}
@@ -0,0 +1,92 @@
+//! Rust client code generation: one sqlx-backed entity struct per table.
+
+use kql_analyzer::mir::{Column, MirEnum, MirProgram, MirType, Table};
+use std::fmt::Write;
+
+/// Generate a Rust module with an entity struct and [kql_runtime] glue for
+/// every table in `mir`.
+pub fn generate(mir: &MirProgram) -> String {
+    let mut out = String::new();
+    out.push_str("//! Generated by `kql generate`. Do not edit.\n\n");
+    out.push_str("use kql_runtime::KqlEntity;\n\n");
+    for e in mir.enums.values() {
+        generate_enum(&mut out, e);
+    }
+    for table in mir.tables.values() {
+        generate_table(&mut out, table);
+    }
+    out
+}
+
+fn generate_enum(out: &mut String, e: &MirEnum) {
+    let name = to_pascal_case(&e.name);
+    writeln!(out, "#[derive(Debug, Clone, Copy, PartialEq, Eq)]").unwrap();
+    writeln!(out, "pub enum {} {{", name).unwrap();
+    for (variant, _) in &e.variants {
+        writeln!(out, "    {},", variant).unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+}
+
+fn generate_table(out: &mut String, table: &Table) {
+    if let Some(note) = &table.deprecated {
+        writeln!(out, "{}", deprecated_attr(note)).unwrap();
+    }
+    writeln!(out, "#[derive(Debug, Clone, sqlx::FromRow)]").unwrap();
+    writeln!(out, "pub struct {} {{", table.struct_name).unwrap();
+    for column in &table.columns {
+        if let Some(comment) = &column.comment {
+            writeln!(out, "    /// {}", comment).unwrap();
+        }
+        if let Some(note) = &column.deprecated {
+            writeln!(out, "    {}", deprecated_attr(note)).unwrap();
+        }
+        writeln!(out, "    pub {}: {},", column.name, rust_type(column)).unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+
+    writeln!(out, "impl KqlEntity for {} {{", table.struct_name).unwrap();
+    writeln!(out, "    const TABLE: &'static str = {:?};", table.name).unwrap();
+    let columns: Vec<&str> = table.columns.iter().map(|c| c.name.as_str()).collect();
+    writeln!(out, "    const COLUMNS: &'static [&'static str] = &{:?};", columns).unwrap();
+    let primary_key = table.primary_key.first().map(String::as_str).unwrap_or("id");
+    writeln!(out, "    const PRIMARY_KEY: &'static str = {:?};", primary_key).unwrap();
+    writeln!(out, "}}\n").unwrap();
+}
+
+fn deprecated_attr(note: &str) -> String {
+    if note.is_empty() { "#[deprecated]".to_string() } else { format!("#[deprecated(note = {:?})]", note) }
+}
+
+fn rust_type(column: &Column) -> String {
+    let base = match &column.ty {
+        MirType::I8 => "i8",
+        MirType::I16 => "i16",
+        MirType::I32 => "i32",
+        MirType::I64 => "i64",
+        MirType::U8 => "u8",
+        MirType::U16 => "u16",
+        MirType::U32 => "u32",
+        MirType::U64 => "u64",
+        MirType::F32 => "f32",
+        MirType::F64 => "f64",
+        MirType::Bool => "bool",
+        MirType::Decimal { .. } | MirType::Text | MirType::DateTime | MirType::Date | MirType::Time | MirType::Uuid => "String",
+        MirType::Json => "serde_json::Value",
+        MirType::Enum(_) => "String",
+    };
+    if column.nullable { format!("Option<{}>", base) } else { base.to_string() }
+}
+
+/// Convert a snake_case physical name back into a PascalCase type name.
+pub fn to_pascal_case(name: &str) -> String {
+    name.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,657 @@
+#![deny(missing_debug_implementations)]
+#![warn(rustdoc::missing_crate_level_docs)]
+#![doc = include_str!("../readme.md")]
+
+pub mod codegen;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use kql_analyzer::{
+    Compiler, Pipeline,
+    lir::{Dialect, sql_gen::SqlGenerator},
+    mir::mir_gen::MirLowerer,
+};
+use kql_migrate::MigrationEngine;
+use kql_types::{KqlConfig, KqlError, Result};
+use std::path::{Path, PathBuf};
+
+/// The `kql` command line interface.
+#[derive(Debug, Parser)]
+#[command(name = "kql", version, about = "The KQL schema language compiler")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Commands,
+}
+
+/// Top level subcommands.
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// Scaffold a new project in the current or given directory.
+    Init(InitArgs),
+    /// Compile a schema and print the generated artifact.
+    Compile(CompileArgs),
+    /// Validate a schema without generating anything.
+    Check(CheckArgs),
+    /// Generate client code according to `kql.toml`.
+    Generate(GenerateArgs),
+    /// Diff two schema versions and print the migration script.
+    Migrate(MigrateArgs),
+    /// Translate existing SQL DDL into a KQL schema, or the reverse.
+    Transpile(TranspileArgs),
+}
+
+/// Arguments for `kql init`.
+#[derive(Debug, clap::Args)]
+pub struct InitArgs {
+    /// Directory to scaffold into, defaulting to the working directory.
+    pub path: Option<PathBuf>,
+    /// Default SQL dialect written to `kql.toml`.
+    #[arg(long)]
+    pub dialect: Option<Dialect>,
+    /// Overwrite files that already exist.
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Arguments for `kql compile`.
+#[derive(Debug, clap::Args)]
+pub struct CompileArgs {
+    /// Input schema file, defaulting to the `schema` entry of `kql.toml`.
+    pub input: Option<PathBuf>,
+    /// The artifact to emit.
+    #[arg(long, value_enum, default_value_t = Emit::Sql)]
+    pub emit: Emit,
+    /// Target SQL dialect, or `all` to emit every backend at once.
+    #[arg(long)]
+    pub dialect: Option<DialectArg>,
+    /// Treat warnings as errors.
+    #[arg(long)]
+    pub strict: bool,
+    /// Write the artifact to a file instead of stdout; `-` means stdout.
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+    /// Also print per-stage compile timings.
+    #[arg(long)]
+    pub timings: bool,
+    /// With `--emit sql --out <dir>`, write one file per table plus a
+    /// combined `000_schema.sql` instead of a single script.
+    #[arg(long)]
+    pub split: bool,
+    /// Run the full validation pipeline without writing or printing any
+    /// artifact.
+    #[arg(long)]
+    pub check_only: bool,
+}
+
+/// Arguments for `kql check`.
+#[derive(Debug, clap::Args)]
+pub struct CheckArgs {
+    /// Input schema file, defaulting to the `schema` entry of `kql.toml`.
+    pub input: Option<PathBuf>,
+    /// Also report features that do not translate to every dialect.
+    #[arg(long)]
+    pub all_dialects: bool,
+    /// Also lower to MIR and run dialect validation. The default stops after
+    /// HIR type checking, which is noticeably faster on large schemas.
+    #[arg(long)]
+    pub deep: bool,
+    /// Treat warnings as errors.
+    #[arg(long)]
+    pub strict: bool,
+    /// Output format for diagnostics.
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+}
+
+/// How `kql check` reports its diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Human readable lines.
+    Text,
+    /// A JSON array of diagnostics, for CI integration.
+    Json,
+}
+
+/// Arguments for `kql generate`.
+#[derive(Debug, clap::Args)]
+pub struct GenerateArgs {
+    /// What to generate, overriding `codegen.language` in `kql.toml`.
+    #[arg(value_enum)]
+    pub target: Option<Target>,
+    /// Input schema file, defaulting to the `schema` entry of `kql.toml`.
+    pub input: Option<PathBuf>,
+    /// Output directory, overriding `codegen.output` in `kql.toml`.
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Languages `kql generate` can target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Target {
+    /// sqlx-backed Rust entity structs.
+    Rust,
+    /// Plain serde-derived Rust structs, without the sqlx machinery.
+    RustStructs,
+    /// proto3 message definitions.
+    Proto,
+    /// OpenAPI 3 component schemas, as JSON.
+    #[value(name = "openapi")]
+    OpenApi,
+}
+
+/// Arguments for `kql migrate`.
+#[derive(Debug, clap::Args)]
+pub struct MigrateArgs {
+    /// The currently deployed schema.
+    #[arg(long)]
+    pub from: PathBuf,
+    /// The desired schema.
+    #[arg(long)]
+    pub to: PathBuf,
+    /// Target SQL dialect.
+    #[arg(long)]
+    pub dialect: Option<Dialect>,
+}
+
+/// Arguments for `kql transpile`.
+#[derive(Debug, clap::Args)]
+pub struct TranspileArgs {
+    /// Input SQL file; a KQL file with `--reverse`.
+    pub input: PathBuf,
+    /// SQL dialect of the input, or of the generated SQL with `--reverse`.
+    #[arg(long)]
+    pub dialect: Option<Dialect>,
+    /// Translate KQL to SQL instead of SQL to KQL.
+    #[arg(long)]
+    pub reverse: bool,
+    /// Write the result to a file instead of stdout; `-` means stdout.
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+}
+
+/// The `--dialect` value of `kql compile`: one backend, or `all`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DialectArg {
+    /// A single concrete dialect.
+    One(Dialect),
+    /// Every supported dialect; requires `--emit sql --out <dir>` and writes
+    /// one `schema.<dialect>.sql` per backend.
+    All,
+}
+
+impl DialectArg {
+    /// The concrete dialect, or `None` for [DialectArg::All].
+    fn single(self) -> Option<Dialect> {
+        match self {
+            Self::One(dialect) => Some(dialect),
+            Self::All => None,
+        }
+    }
+}
+
+impl std::str::FromStr for DialectArg {
+    type Err = KqlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("all") { Ok(Self::All) } else { Ok(Self::One(s.parse()?)) }
+    }
+}
+
+/// Artifacts `kql compile` can emit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Emit {
+    /// SQL DDL plus the `let` queries.
+    Sql,
+    /// The typed high-level representation, for debugging.
+    Hir,
+    /// The relational representation, for debugging.
+    Mir,
+    /// OpenAPI 3 component schemas, as JSON.
+    #[value(name = "openapi")]
+    OpenApi,
+    /// A teardown script: `DROP TABLE IF EXISTS` for every table, in reverse
+    /// dependency order.
+    DropSql,
+}
+
+/// Run a parsed command line, writing output via `print!`. All compile
+/// errors found are returned, not just the first.
+pub fn run(cli: Cli) -> Result<(), Vec<KqlError>> {
+    let config = load_config();
+    match cli.command {
+        Commands::Init(args) => init(args),
+        Commands::Compile(args) => compile(&config, args),
+        Commands::Check(args) => check(&config, args),
+        Commands::Generate(args) => generate(&config, args),
+        Commands::Migrate(args) => migrate(&config, args),
+        Commands::Transpile(args) => transpile(&config, args),
+    }
+}
+
+/// The starter schema written by `kql init`.
+const EXAMPLE_SCHEMA: &str = r#"/// A minimal example entity; adapt or delete it.
+struct User {
+    id: Key<User, i64> @auto_increment,
+    name: String,
+    age: i32?,
+}
+
+let adults = User.filter { $.age >= 18 }
+"#;
+
+fn init(args: InitArgs) -> Result<(), Vec<KqlError>> {
+    let io_error = |e: std::io::Error| vec![KqlError::IoError { message: e.to_string() }];
+    let root = args.path.unwrap_or_else(|| PathBuf::from("."));
+    std::fs::create_dir_all(&root).map_err(io_error)?;
+    let config = KqlConfig {
+        schema: Some("schema.kql".to_string()),
+        dialect: Some(args.dialect.unwrap_or_default().to_string()),
+        migrations: kql_types::MigrationsConfig { directory: Some("migrations".to_string()) },
+        ..KqlConfig::default()
+    };
+    let manifest = toml::to_string(&config)
+        .map_err(|e| vec![KqlError::IoError { message: format!("failed to serialize kql.toml: {}", e) }])?;
+    for (name, content) in [("kql.toml", manifest.as_str()), ("schema.kql", EXAMPLE_SCHEMA)] {
+        let path = root.join(name);
+        if path.exists() && !args.force {
+            let message = format!("`{}` already exists; pass --force to overwrite", path.display());
+            return Err(vec![KqlError::IoError { message }]);
+        }
+        std::fs::write(&path, content).map_err(io_error)?;
+        println!("wrote {}", path.display());
+    }
+    std::fs::create_dir_all(root.join("migrations")).map_err(io_error)?;
+    println!("created {}", root.join("migrations").display());
+    Ok(())
+}
+
+fn compile(config: &KqlConfig, args: CompileArgs) -> Result<(), Vec<KqlError>> {
+    let input = resolve_input(config, args.input).map_err(|e| vec![e])?;
+    let hir = Compiler::new().compile_file(&input)?;
+    if args.strict && !hir.warnings.is_empty() {
+        return Err(promote_warnings(&hir));
+    }
+    if args.check_only {
+        let pipeline = Pipeline::new();
+        let mir = pipeline.to_mir(hir).map_err(|e| vec![e])?;
+        let dialects = match args.dialect {
+            Some(DialectArg::All) => Dialect::all().to_vec(),
+            other => vec![resolve_dialect(config, other.and_then(DialectArg::single)).map_err(|e| vec![e])?],
+        };
+        for dialect in dialects {
+            SqlGenerator::new(&mir, dialect).validate().map_err(|e| vec![e])?;
+        }
+        println!("{}: no errors found", input.display());
+        return Ok(());
+    }
+    if args.dialect == Some(DialectArg::All) {
+        let io_error = |message: String| vec![KqlError::IoError { message }];
+        if args.emit != Emit::Sql {
+            return Err(io_error("`--dialect all` only applies to `--emit sql`".to_string()));
+        }
+        if args.split {
+            return Err(io_error("`--dialect all` cannot be combined with `--split`".to_string()));
+        }
+        let Some(dir) = args.out.as_ref().filter(|p| *p != Path::new("-")) else {
+            return Err(io_error("`--dialect all` requires `--out <directory>`".to_string()));
+        };
+        let pipeline = Pipeline::new();
+        let mir = pipeline.to_mir(hir).map_err(|e| vec![e])?;
+        std::fs::create_dir_all(dir).map_err(|e| io_error(e.to_string()))?;
+        for dialect in Dialect::all() {
+            let statements = pipeline.to_sql(&mir, dialect).map_err(|e| vec![e])?;
+            let script = statements.iter().map(|s| format!("{s};")).collect::<Vec<_>>().join("\n\n");
+            let path = dir.join(format!("schema.{}.sql", dialect));
+            std::fs::write(&path, format!("{script}\n")).map_err(|e| io_error(e.to_string()))?;
+            println!("wrote {}", path.display());
+        }
+        return Ok(());
+    }
+    let chosen_dialect = args.dialect.and_then(DialectArg::single);
+    if args.split {
+        let io_error = |message: String| vec![KqlError::IoError { message }];
+        if args.emit != Emit::Sql {
+            return Err(io_error("`--split` only applies to `--emit sql`".to_string()));
+        }
+        let Some(dir) = args.out.as_ref().filter(|p| *p != Path::new("-")) else {
+            return Err(io_error("`--split` requires `--out <directory>`".to_string()));
+        };
+        let mir = MirLowerer::new(hir).lower().map_err(|e| vec![e])?;
+        let dialect = resolve_dialect(config, chosen_dialect).map_err(|e| vec![e])?;
+        let generator = SqlGenerator::new(&mir, dialect);
+        generator.validate().map_err(|e| vec![e])?;
+        std::fs::create_dir_all(dir).map_err(|e| io_error(e.to_string()))?;
+        let combined = dir.join("000_schema.sql");
+        std::fs::write(&combined, format!("{}\n", generator.generate_sql())).map_err(|e| io_error(e.to_string()))?;
+        println!("wrote {}", combined.display());
+        for (table, script) in generator.generate_split_sql() {
+            let path = dir.join(format!("{}.sql", table));
+            std::fs::write(&path, script).map_err(|e| io_error(e.to_string()))?;
+            println!("wrote {}", path.display());
+        }
+        return Ok(());
+    }
+    let pipeline = Pipeline::new();
+    let artifact = match args.emit {
+        Emit::Hir => format!("{hir:#?}\n"),
+        Emit::Mir => {
+            let mir = pipeline.to_mir(hir).map_err(|e| vec![e])?;
+            format!("{mir:#?}\n")
+        }
+        Emit::OpenApi => codegen::openapi::generate(&hir),
+        Emit::Sql => {
+            let mir = pipeline.to_mir(hir).map_err(|e| vec![e])?;
+            let dialect = resolve_dialect(config, chosen_dialect).map_err(|e| vec![e])?;
+            let statements = pipeline.to_sql(&mir, dialect).map_err(|e| vec![e])?;
+            statements.iter().map(|s| format!("{s};")).collect::<Vec<_>>().join("\n\n")
+        }
+        Emit::DropSql => {
+            let mir = pipeline.to_mir(hir).map_err(|e| vec![e])?;
+            let dialect = resolve_dialect(config, chosen_dialect).map_err(|e| vec![e])?;
+            let generator = SqlGenerator::new(&mir, dialect);
+            generator.validate().map_err(|e| vec![e])?;
+            let mut script = generator
+                .generate_drop_all()
+                .iter()
+                .map(|s| format!("{};", generator.render(s)))
+                .collect::<Vec<_>>()
+                .join("\n");
+            script.push('\n');
+            script
+        }
+    };
+    match args.out {
+        Some(path) if path != Path::new("-") => {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent).map_err(|e| vec![KqlError::IoError { message: e.to_string() }])?;
+            }
+            std::fs::write(&path, artifact).map_err(|e| vec![KqlError::IoError { message: e.to_string() }])?;
+            println!("wrote {}", path.display());
+        }
+        _ => print!("{artifact}"),
+    }
+    if args.timings {
+        let source = std::fs::read_to_string(&input).map_err(|e| vec![KqlError::IoError { message: e.to_string() }])?;
+        let dialect = resolve_dialect(config, chosen_dialect).map_err(|e| vec![e])?;
+        let (_, timings) = Compiler::new().compile_timed(&source, dialect)?;
+        println!("-- parse:           {:?}", timings.parse);
+        println!("-- name collection: {:?}", timings.name_collection);
+        println!("-- lowering:        {:?}", timings.lowering);
+        println!("-- mir:             {:?}", timings.mir);
+        println!("-- sql generation:  {:?}", timings.sql);
+        println!("-- total:           {:?}", timings.total());
+    }
+    Ok(())
+}
+
+fn check(config: &KqlConfig, args: CheckArgs) -> Result<(), Vec<KqlError>> {
+    let input = resolve_input(config, args.input).map_err(|e| vec![e])?;
+    if args.format == Format::Json {
+        let source = std::fs::read_to_string(&input).map_err(|e| vec![KqlError::IoError { message: e.to_string() }])?;
+        let (json, has_errors) = diagnostics_json(&source, &input.display().to_string());
+        println!("{json}");
+        // The diagnostics were already reported as JSON; an empty error list
+        // still makes the process exit non-zero.
+        return if has_errors { Err(Vec::new()) } else { Ok(()) };
+    }
+    let hir = match Compiler::new().compile_file(&input) {
+        Ok(hir) => hir,
+        Err(errors) => {
+            // Print the errors grouped and position-sorted rather than in
+            // accumulation order; the empty error list still makes the
+            // process exit non-zero.
+            let file = input.display().to_string();
+            let source = std::fs::read_to_string(&input).unwrap_or_default();
+            let diagnostics: Vec<kql_types::Diagnostic> = errors.iter().map(KqlError::to_diagnostic).collect();
+            print!("{}", render_diagnostics(&[(&file, &source, &diagnostics)]));
+            return Err(Vec::new());
+        }
+    };
+    for warning in &hir.warnings {
+        println!("warning: {}", warning.message);
+    }
+    if args.strict && !hir.warnings.is_empty() {
+        return Err(promote_warnings(&hir));
+    }
+    if args.deep || args.all_dialects {
+        let mir = MirLowerer::new(hir).lower().map_err(|e| vec![e])?;
+        if args.deep {
+            let dialect = resolve_dialect(config, None).map_err(|e| vec![e])?;
+            SqlGenerator::new(&mir, dialect).validate().map_err(|e| vec![e])?;
+        }
+        if args.all_dialects {
+            let warnings = SqlGenerator::new(&mir, Dialect::default()).portability_report();
+            for warning in &warnings {
+                println!("warning[{}]: {}", warning.dialect, warning.message);
+            }
+            if !warnings.is_empty() {
+                println!("{}: {} portability warning(s)", input.display(), warnings.len());
+                return Ok(());
+            }
+        }
+    }
+    println!("{}: no errors found", input.display());
+    Ok(())
+}
+
+fn generate(config: &KqlConfig, args: GenerateArgs) -> Result<(), Vec<KqlError>> {
+    let target = match args.target {
+        Some(target) => target,
+        None => match config.codegen.language.as_deref() {
+            Some(language) => parse_language(language).map_err(|e| vec![e])?,
+            None => Target::Rust,
+        },
+    };
+    let input = resolve_input(config, args.input).map_err(|e| vec![e])?;
+    let hir = Compiler::new().compile_file(&input)?;
+    let (file_name, code) = match target {
+        Target::Rust => {
+            let mir = MirLowerer::new(hir).lower().map_err(|e| vec![e])?;
+            ("mod.rs", codegen::rust::generate(&mir))
+        }
+        Target::RustStructs => ("dto.rs", codegen::dto::generate(&hir)),
+        Target::Proto => ("schema.proto", codegen::proto::generate(&hir)),
+        Target::OpenApi => ("openapi.json", codegen::openapi::generate(&hir)),
+    };
+    let output = args
+        .output
+        .or_else(|| config.codegen.output.as_ref().map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from("src/generated"));
+    std::fs::create_dir_all(&output).map_err(|e| vec![KqlError::IoError { message: e.to_string() }])?;
+    let path = output.join(file_name);
+    std::fs::write(&path, code).map_err(|e| vec![KqlError::IoError { message: e.to_string() }])?;
+    println!("generated {}", path.display());
+    Ok(())
+}
+
+fn migrate(config: &KqlConfig, args: MigrateArgs) -> Result<(), Vec<KqlError>> {
+    let compiler = Compiler::new();
+    let old = MirLowerer::new(compiler.compile_file(&args.from)?).lower().map_err(|e| vec![e])?;
+    let new = MirLowerer::new(compiler.compile_file(&args.to)?).lower().map_err(|e| vec![e])?;
+    let dialect = resolve_dialect(config, args.dialect).map_err(|e| vec![e])?;
+    if old.fingerprint() == new.fingerprint() {
+        println!("-- no changes");
+        return Ok(());
+    }
+    let engine = MigrationEngine::new();
+    let steps = engine.diff(&old, &new);
+    if steps.is_empty() {
+        println!("-- no changes");
+        return Ok(());
+    }
+    let generator = SqlGenerator::new(&new, dialect);
+    for step in &steps {
+        if let Some(warning) = step.warning() {
+            println!("-- WARNING: {warning}");
+        }
+        for statement in engine.generate_migration(std::slice::from_ref(step), &new, dialect) {
+            println!("{};", generator.render(&statement));
+        }
+    }
+    Ok(())
+}
+
+fn transpile(config: &KqlConfig, args: TranspileArgs) -> Result<(), Vec<KqlError>> {
+    let io_error = |e: std::io::Error| vec![KqlError::IoError { message: e.to_string() }];
+    let result = if args.reverse {
+        // The reverse direction is a plain compile of the KQL input.
+        let hir = Compiler::new().compile_file(&args.input)?;
+        let pipeline = Pipeline::new();
+        let mir = pipeline.to_mir(hir).map_err(|e| vec![e])?;
+        let dialect = resolve_dialect(config, args.dialect).map_err(|e| vec![e])?;
+        let statements = pipeline.to_sql(&mir, dialect).map_err(|e| vec![e])?;
+        statements.iter().map(|s| format!("{s};")).collect::<Vec<_>>().join("\n\n") + "\n"
+    } else {
+        let sql = std::fs::read_to_string(&args.input).map_err(io_error)?;
+        // Without an explicit dialect the permissive generic grammar is used;
+        // the `kql.toml` default describes the output side, not the input.
+        let input_dialect = match args.dialect {
+            None => kql_transpiler::InputDialect::Generic,
+            Some(Dialect::Postgres) => kql_transpiler::InputDialect::Postgres,
+            Some(Dialect::MySql) => kql_transpiler::InputDialect::MySql,
+            Some(Dialect::Sqlite) => kql_transpiler::InputDialect::Sqlite,
+        };
+        kql_transpiler::Transpiler::new().with_input_dialect(input_dialect).transpile(&sql).map_err(|e| vec![e])?
+    };
+    match args.out {
+        Some(path) if path != Path::new("-") => {
+            std::fs::write(&path, result).map_err(io_error)?;
+            println!("wrote {}", path.display());
+        }
+        _ => print!("{result}"),
+    }
+    Ok(())
+}
+
+/// Compile `source` and render every diagnostic as a JSON array for CI
+/// consumers, along with whether any of them is an error. `file` is echoed
+/// into each entry so results from several schemas can be merged.
+pub fn diagnostics_json(source: &str, file: &str) -> (String, bool) {
+    let mut entries = Vec::new();
+    let mut has_errors = false;
+    let index = kql_types::LineIndex::new(source);
+    let mut push = |severity: &str, code: &str, message: &str, span: kql_types::Span| {
+        let (start_line, start_col) = line_col(&index, span.start);
+        let (end_line, end_col) = line_col(&index, span.end);
+        entries.push(serde_json::json!({
+            "file": file,
+            "severity": severity,
+            "code": code,
+            "message": message,
+            "start": { "line": start_line, "col": start_col },
+            "end": { "line": end_line, "col": end_col },
+        }));
+    };
+    match Compiler::new().compile_source(source) {
+        Ok(hir) => {
+            for warning in &hir.warnings {
+                push("warning", "warning", &warning.message, warning.span);
+            }
+        }
+        Err(errors) => {
+            has_errors = true;
+            for error in &errors {
+                let code = match error {
+                    KqlError::SyntaxError { .. } => "syntax",
+                    KqlError::SemanticError { .. } => "semantic",
+                    KqlError::IoError { .. } => "io",
+                };
+                push("error", code, error.message(), error.span().unwrap_or_default());
+            }
+        }
+    }
+    (serde_json::Value::Array(entries).to_string(), has_errors)
+}
+
+/// One-based line and column of a byte offset.
+fn line_col(index: &kql_types::LineIndex, offset: usize) -> (usize, usize) {
+    let (line, col) = index.line_col(offset);
+    (line + 1, col + 1)
+}
+
+/// Render diagnostics from one or more files as grouped text: entries are
+/// sorted by (file, start line, start column), listed under one header per
+/// file, and followed by a summary line such as `3 errors, 1 warning`. Each
+/// `files` entry pairs a file name with its source text, for resolving spans,
+/// and the diagnostics reported against it.
+pub fn render_diagnostics(files: &[(&str, &str, &[kql_types::Diagnostic])]) -> String {
+    let mut ordered: Vec<(&str, usize, usize, &kql_types::Diagnostic)> = Vec::new();
+    for (file, source, diagnostics) in files {
+        let index = kql_types::LineIndex::new(source);
+        for diagnostic in *diagnostics {
+            let (line, col) = line_col(&index, diagnostic.span.start);
+            ordered.push((file, line, col, diagnostic));
+        }
+    }
+    ordered.sort_by(|a, b| (a.0, a.1, a.2).cmp(&(b.0, b.1, b.2)));
+    let mut out = String::new();
+    let mut current = None;
+    let (mut errors, mut warnings) = (0, 0);
+    for (file, line, col, diagnostic) in ordered {
+        if current != Some(file) {
+            if current.is_some() {
+                out.push('\n');
+            }
+            out.push_str(&format!("{file}:\n"));
+            current = Some(file);
+        }
+        let label = match diagnostic.severity {
+            kql_types::Severity::Error => {
+                errors += 1;
+                "error"
+            }
+            kql_types::Severity::Warning => {
+                warnings += 1;
+                "warning"
+            }
+        };
+        out.push_str(&format!("  {}:{} {}: {}\n", line, col, label, diagnostic.message));
+    }
+    let count = |n: usize, noun: &str| format!("{} {}{}", n, noun, if n == 1 { "" } else { "s" });
+    out.push_str(&format!("\n{}, {}\n", count(errors, "error"), count(warnings, "warning")));
+    out
+}
+
+/// Parse a `codegen.language` value from `kql.toml` into a generate target,
+/// listing the supported languages when the value is unknown.
+pub fn parse_language(language: &str) -> Result<Target> {
+    <Target as ValueEnum>::from_str(language, true).map_err(|_| {
+        let supported: Vec<String> =
+            Target::value_variants().iter().filter_map(|v| v.to_possible_value()).map(|v| v.get_name().to_string()).collect();
+        KqlError::IoError {
+            message: format!("unknown codegen language `{}`; expected one of {}", language, supported.join(", ")),
+        }
+    })
+}
+
+/// Convert warning diagnostics into hard errors for `--strict` runs.
+fn promote_warnings(hir: &kql_analyzer::hir::HirProgram) -> Vec<KqlError> {
+    hir.warnings.iter().map(|w| KqlError::semantic(w.message.clone(), w.span)).collect()
+}
+
+/// Read `kql.toml` from the working directory, falling back to defaults.
+pub fn load_config() -> KqlConfig {
+    let path = Path::new("kql.toml");
+    if !path.exists() {
+        return KqlConfig::default();
+    }
+    match std::fs::read_to_string(path) {
+        Ok(text) => toml::from_str(&text).unwrap_or_default(),
+        Err(_) => KqlConfig::default(),
+    }
+}
+
+fn resolve_input(config: &KqlConfig, input: Option<PathBuf>) -> Result<PathBuf> {
+    input
+        .or_else(|| config.schema.as_ref().map(PathBuf::from))
+        .ok_or_else(|| KqlError::IoError { message: "no input file given and no `schema` entry in kql.toml".to_string() })
+}
+
+fn resolve_dialect(config: &KqlConfig, dialect: Option<Dialect>) -> Result<Dialect> {
+    if let Some(dialect) = dialect {
+        return Ok(dialect);
+    }
+    match &config.dialect {
+        Some(name) => name.parse(),
+        None => Ok(Dialect::default()),
+    }
+}
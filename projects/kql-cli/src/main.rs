@@ -0,0 +1,13 @@
+//! The `kql` binary entry point.
+
+use clap::Parser;
+
+fn main() {
+    let cli = kql_cli::Cli::parse();
+    if let Err(errors) = kql_cli::run(cli) {
+        for error in &errors {
+            eprintln!("error: {}", error.message());
+        }
+        std::process::exit(1);
+    }
+}
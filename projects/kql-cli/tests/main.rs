@@ -0,0 +1,328 @@
+use kql_analyzer::{Compiler, mir::mir_gen::MirLowerer};
+
+#[test]
+fn ready() {
+    println!("it works!")
+}
+
+#[test]
+fn rust_codegen_emits_entities() {
+    let hir = Compiler::new().compile_source("struct User { id: Key<User, i64>, name: String }").unwrap();
+    let mir = MirLowerer::new(hir).lower().unwrap();
+    let code = kql_cli::codegen::rust::generate(&mir);
+    assert!(code.contains("pub struct User"), "{code}");
+    assert!(code.contains("impl KqlEntity for User"), "{code}");
+    assert!(code.contains("const TABLE: &'static str = \"user\";"), "{code}");
+}
+
+#[test]
+fn strict_check_promotes_warnings_to_errors() {
+    let source = "struct User { id: Key<User, i64> }\nstruct Post { id: Key<User, i64> }\n";
+    let path = std::env::temp_dir().join("kql_strict_check.kql");
+    std::fs::write(&path, source).unwrap();
+    let check = |strict| {
+        kql_cli::run(kql_cli::Cli {
+            command: kql_cli::Commands::Check(kql_cli::CheckArgs {
+                input: Some(path.clone()),
+                all_dialects: false,
+                deep: false,
+                strict,
+                format: kql_cli::Format::Text,
+            }),
+        })
+    };
+    assert!(check(false).is_ok());
+    let errors = check(true).unwrap_err();
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert!(errors[0].message().contains("cross-entity"), "{errors:?}");
+}
+
+#[test]
+fn compile_writes_sql_to_out_file() {
+    let input = std::env::temp_dir().join("kql_compile_out.kql");
+    std::fs::write(&input, "struct User { id: Key<User, i64>, name: String }\n").unwrap();
+    let out = std::env::temp_dir().join("kql_compile_out/schema.sql");
+    kql_cli::run(kql_cli::Cli {
+        command: kql_cli::Commands::Compile(kql_cli::CompileArgs {
+            input: Some(input),
+            emit: kql_cli::Emit::Sql,
+            dialect: None,
+            strict: false,
+            out: Some(out.clone()),
+            timings: false,
+            split: false,
+            check_only: false,
+        }),
+    })
+    .unwrap();
+    let sql = std::fs::read_to_string(&out).unwrap();
+    assert!(sql.contains("CREATE TABLE user"), "{sql}");
+}
+
+#[test]
+fn openapi_matches_golden_file() {
+    let source = r#"
+namespace shop {
+    struct Customer {
+        id: Key<Customer, i64>,
+        /// Display name.
+        name: String,
+        email: String?,
+    }
+
+    struct Order {
+        id: Key<Order, i64>,
+        customer: ForeignKey<Customer>,
+        total: d128,
+    }
+}
+"#;
+    let hir = Compiler::new().compile_source(source).unwrap();
+    let document = kql_cli::codegen::openapi::generate(&hir);
+    assert_eq!(document, include_str!("golden/openapi.json"));
+}
+
+const PROTO_SCHEMA: &str = r#"
+namespace shop {
+    enum Status { Active, Disabled }
+
+    struct Customer {
+        id: Key<Customer, i64>,
+        name: String,
+        status: Status,
+        tags: [String],
+        email: String?,
+    }
+
+    struct Order {
+        id: Key<Order, i64>,
+        customer: ForeignKey<Customer>,
+    }
+}
+"#;
+
+#[test]
+fn proto_matches_golden_file() {
+    let hir = Compiler::new().compile_source(PROTO_SCHEMA).unwrap();
+    let proto = kql_cli::codegen::proto::generate(&hir);
+    assert_eq!(proto, include_str!("golden/schema.proto"));
+}
+
+#[test]
+fn rust_structs_match_golden_file() {
+    let hir = Compiler::new().compile_source(PROTO_SCHEMA).unwrap();
+    let code = kql_cli::codegen::dto::generate(&hir);
+    assert_eq!(code, include_str!("golden/dto.rs"));
+}
+
+#[test]
+fn proto_field_numbers_survive_appended_fields() {
+    let appended = PROTO_SCHEMA.replace("email: String?,", "email: String?,\n        vip: bool,");
+    let hir = Compiler::new().compile_source(&appended).unwrap();
+    let proto = kql_cli::codegen::proto::generate(&hir);
+    // Every pre-existing field keeps its number; the new field takes the next one.
+    for line in include_str!("golden/schema.proto").lines().filter(|l| l.contains(" = ") && l.ends_with(';')) {
+        assert!(proto.contains(line), "missing {line:?} in:\n{proto}");
+    }
+    assert!(proto.contains("bool vip = 6;"), "{proto}");
+}
+
+#[test]
+fn init_scaffolds_a_project() {
+    let root = std::env::temp_dir().join("kql_init_test");
+    let _ = std::fs::remove_dir_all(&root);
+    let args =
+        || kql_cli::InitArgs { path: Some(root.clone()), dialect: Some(kql_analyzer::lir::Dialect::MySql), force: false };
+    kql_cli::run(kql_cli::Cli { command: kql_cli::Commands::Init(args()) }).unwrap();
+    assert!(root.join("schema.kql").is_file());
+    assert!(root.join("migrations").is_dir());
+    // The manifest round-trips through `KqlConfig` with the chosen dialect.
+    let manifest = std::fs::read_to_string(root.join("kql.toml")).unwrap();
+    let config: kql_types::KqlConfig = toml::from_str(&manifest).unwrap();
+    assert_eq!(config.schema.as_deref(), Some("schema.kql"));
+    assert_eq!(config.dialect.as_deref(), Some("mysql"));
+    assert_eq!(config.migrations.directory.as_deref(), Some("migrations"));
+    // A second run without --force refuses to clobber the files.
+    let error = kql_cli::run(kql_cli::Cli { command: kql_cli::Commands::Init(args()) }).unwrap_err();
+    assert!(error[0].to_string().contains("--force"), "{error:?}");
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn renders_diagnostics_as_json() {
+    let source = "struct User {\n    id: Missing,\n}\n";
+    let (json, has_errors) = kql_cli::diagnostics_json(source, "schema.kql");
+    assert!(has_errors);
+    let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let entries = parsed.as_array().unwrap();
+    assert_eq!(entries.len(), 1, "{json}");
+    let entry = &entries[0];
+    assert_eq!(entry["file"], "schema.kql");
+    assert_eq!(entry["severity"], "error");
+    assert_eq!(entry["code"], "semantic");
+    assert_eq!(entry["start"]["line"], 2);
+    assert!(entry["message"].as_str().unwrap().contains("Missing"), "{json}");
+    let (json, has_errors) = kql_cli::diagnostics_json("struct User { id: Key<User, i64> }", "schema.kql");
+    assert!(!has_errors);
+    assert_eq!(json, "[]");
+}
+
+#[test]
+fn transpile_turns_sql_into_kql() {
+    let input = std::env::temp_dir().join("kql_transpile_in.sql");
+    let sql = "CREATE TABLE user (\n    id BIGINT PRIMARY KEY,\n    name TEXT NOT NULL,\n    age INTEGER\n);\n";
+    std::fs::write(&input, sql).unwrap();
+    let out = std::env::temp_dir().join("kql_transpile_out.kql");
+    kql_cli::run(kql_cli::Cli {
+        command: kql_cli::Commands::Transpile(kql_cli::TranspileArgs {
+            input: input.clone(),
+            dialect: Some(kql_analyzer::lir::Dialect::Postgres),
+            reverse: false,
+            out: Some(out.clone()),
+        }),
+    })
+    .unwrap();
+    let kql = std::fs::read_to_string(&out).unwrap();
+    assert!(kql.contains("struct User {"), "{kql}");
+    assert!(kql.contains("id: Key<User, i64>"), "{kql}");
+    assert!(kql.contains("age: i32?"), "{kql}");
+    // A syntactically broken input surfaces as one clear parse error.
+    std::fs::write(&input, "CREATE TABLE (").unwrap();
+    let errors = kql_cli::run(kql_cli::Cli {
+        command: kql_cli::Commands::Transpile(kql_cli::TranspileArgs { input, dialect: None, reverse: false, out: None }),
+    })
+    .unwrap_err();
+    assert!(errors[0].message().contains("failed to parse SQL"), "{errors:?}");
+}
+
+#[test]
+fn split_compile_writes_one_file_per_table() {
+    let input = std::env::temp_dir().join("kql_split_compile.kql");
+    let source = r#"
+struct User { id: Key<User, i64> }
+
+@index(title)
+struct Post {
+    id: Key<Post, i64>,
+    author_id: Key<User, i64>,
+    title: String,
+}
+"#;
+    std::fs::write(&input, source).unwrap();
+    let dir = std::env::temp_dir().join("kql_split_compile_out");
+    let _ = std::fs::remove_dir_all(&dir);
+    kql_cli::run(kql_cli::Cli {
+        command: kql_cli::Commands::Compile(kql_cli::CompileArgs {
+            input: Some(input),
+            emit: kql_cli::Emit::Sql,
+            dialect: None,
+            strict: false,
+            out: Some(dir.clone()),
+            timings: false,
+            split: true,
+            check_only: false,
+        }),
+    })
+    .unwrap();
+    let combined = std::fs::read_to_string(dir.join("000_schema.sql")).unwrap();
+    assert!(combined.contains("CREATE TABLE user"), "{combined}");
+    assert!(combined.contains("CREATE TABLE post"), "{combined}");
+    let user = std::fs::read_to_string(dir.join("user.sql")).unwrap();
+    assert!(user.contains("CREATE TABLE user"), "{user}");
+    assert!(!user.contains("CREATE TABLE post"), "{user}");
+    let post = std::fs::read_to_string(dir.join("post.sql")).unwrap();
+    assert!(post.contains("CREATE TABLE post"), "{post}");
+    assert!(post.contains("REFERENCES user"), "{post}");
+    assert!(post.contains("CREATE INDEX post_title_idx"), "{post}");
+}
+
+#[test]
+fn dialect_all_writes_one_schema_per_backend() {
+    let input = std::env::temp_dir().join("kql_dialect_all.kql");
+    std::fs::write(&input, "struct User { id: Key<User, i64> @auto_increment, name: String }\n").unwrap();
+    let dir = std::env::temp_dir().join("kql_dialect_all_out");
+    let _ = std::fs::remove_dir_all(&dir);
+    kql_cli::run(kql_cli::Cli {
+        command: kql_cli::Commands::Compile(kql_cli::CompileArgs {
+            input: Some(input),
+            emit: kql_cli::Emit::Sql,
+            dialect: Some(kql_cli::DialectArg::All),
+            strict: false,
+            out: Some(dir.clone()),
+            timings: false,
+            split: false,
+            check_only: false,
+        }),
+    })
+    .unwrap();
+    let postgres = std::fs::read_to_string(dir.join("schema.postgres.sql")).unwrap();
+    let mysql = std::fs::read_to_string(dir.join("schema.mysql.sql")).unwrap();
+    let sqlite = std::fs::read_to_string(dir.join("schema.sqlite.sql")).unwrap();
+    // Auto-increment keys are where the backends visibly diverge.
+    assert!(mysql.contains("AUTO_INCREMENT"), "{mysql}");
+    assert!(sqlite.contains("AUTOINCREMENT"), "{sqlite}");
+    assert!(postgres != mysql && mysql != sqlite, "{postgres}");
+}
+
+#[test]
+fn codegen_language_parses_or_lists_supported_values() {
+    assert_eq!(kql_cli::parse_language("rust").unwrap(), kql_cli::Target::Rust);
+    assert_eq!(kql_cli::parse_language("rust-structs").unwrap(), kql_cli::Target::RustStructs);
+    assert_eq!(kql_cli::parse_language("OpenAPI").unwrap(), kql_cli::Target::OpenApi);
+    let error = kql_cli::parse_language("typescript").unwrap_err();
+    assert!(error.message().contains("unknown codegen language `typescript`"), "{error}");
+    assert!(error.message().contains("rust, rust-structs, proto, openapi"), "{error}");
+}
+
+#[test]
+fn deep_check_catches_mir_only_errors() {
+    // `@auto_increment` on a text column passes HIR type checking but fails
+    // MIR lowering, so the fast default accepts what `--deep` rejects.
+    let path = std::env::temp_dir().join("kql_deep_check.kql");
+    std::fs::write(&path, "struct User { id: Key<User, i64>, name: String @auto_increment }\n").unwrap();
+    let check = |deep| {
+        kql_cli::run(kql_cli::Cli {
+            command: kql_cli::Commands::Check(kql_cli::CheckArgs {
+                input: Some(path.clone()),
+                all_dialects: false,
+                deep,
+                strict: false,
+                format: kql_cli::Format::Text,
+            }),
+        })
+    };
+    assert!(check(false).is_ok());
+    let errors = check(true).unwrap_err();
+    assert!(errors[0].message().contains("integer column type"), "{errors:?}");
+    // `compile --check-only` runs the same deep validation without emitting.
+    let errors = kql_cli::run(kql_cli::Cli {
+        command: kql_cli::Commands::Compile(kql_cli::CompileArgs {
+            input: Some(path),
+            emit: kql_cli::Emit::Sql,
+            dialect: None,
+            strict: false,
+            out: None,
+            timings: false,
+            split: false,
+            check_only: true,
+        }),
+    })
+    .unwrap_err();
+    assert!(errors[0].message().contains("integer column type"), "{errors:?}");
+}
+
+#[test]
+fn diagnostics_render_grouped_and_sorted() {
+    use kql_types::{Diagnostic, Severity, Span};
+    let a_source = "struct A {\n    x: i32,\n    y: i32,\n}\n";
+    let b_source = "struct B {\n    z: i32,\n}\n";
+    // Accumulation order is deliberately jumbled across files and positions.
+    let a_diags = vec![
+        Diagnostic { severity: Severity::Error, message: "second".to_string(), span: Span::new(27, 28) },
+        Diagnostic { severity: Severity::Warning, message: "first".to_string(), span: Span::new(15, 16) },
+    ];
+    let b_diags = vec![Diagnostic { severity: Severity::Error, message: "third".to_string(), span: Span::new(15, 16) }];
+    let out = kql_cli::render_diagnostics(&[("b.kql", b_source, &b_diags), ("a.kql", a_source, &a_diags)]);
+    let expected = "a.kql:\n  2:5 warning: first\n  3:5 error: second\n\nb.kql:\n  2:5 error: third\n\n2 errors, 1 warning\n";
+    assert_eq!(out, expected);
+}
@@ -1,9 +0,0 @@
-use super::*;
-
-impl From<ExampleErrorKind> for ExampleError {
-    fn from(value: ExampleErrorKind) -> Self {
-        Self {
-            kind: Box::new(value),
-        }
-    }
-}
\ No newline at end of file
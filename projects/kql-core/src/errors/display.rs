@@ -1,23 +0,0 @@
-use super::*;
-
-impl Error for ExampleError {}
-
-
-impl Debug for ExampleError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        Debug::fmt(&self.kind, f)
-    }
-}
-
-impl Display for ExampleError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        Display::fmt(&self.kind, f)
-    }
-}
-
-
-impl Display for ExampleErrorKind {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self { ExampleErrorKind::UnknownError => { write!(f, "UnknownError") } }
-    }
-}
\ No newline at end of file
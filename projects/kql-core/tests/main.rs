@@ -1,4 +0,0 @@
-#[test]
-fn ready() {
-    println!("it works!")
-}
@@ -0,0 +1,76 @@
+//! Static completion data for the KQL language.
+
+use tower_lsp::lsp_types::{CompletionItem, CompletionItemKind};
+
+/// The declaration keywords of KQL.
+pub const KEYWORDS: &[&str] = &["struct", "enum", "type", "let", "namespace", "import"];
+
+/// The built-in type names, including the key wrappers.
+pub const TYPES: &[&str] = &[
+    "String",
+    "bool",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "f32",
+    "f64",
+    "d128",
+    "DateTime",
+    "Date",
+    "Time",
+    "Uuid",
+    "Json",
+    "Key",
+    "ForeignKey",
+];
+
+/// The attribute names recognized after `@`.
+pub const ATTRIBUTES: &[&str] = &[
+    "table",
+    "primary_key",
+    "unique",
+    "auto_increment",
+    "default",
+    "generated",
+    "index",
+    "relation",
+    "audit",
+    "soft_delete",
+    "layout",
+    "precision",
+];
+
+/// The method names usable in `let` query chains.
+pub const QUERY_METHODS: &[&str] = &["filter", "sort", "map", "limit", "offset", "asc", "desc"];
+
+/// Completion items for the declaration keywords.
+pub fn get_keyword_completions() -> Vec<CompletionItem> {
+    items(KEYWORDS, CompletionItemKind::KEYWORD)
+}
+
+/// Completion items for the built-in types.
+pub fn get_type_completions() -> Vec<CompletionItem> {
+    items(TYPES, CompletionItemKind::CLASS)
+}
+
+/// Completion items for the `@` attributes.
+pub fn get_attribute_completions() -> Vec<CompletionItem> {
+    items(ATTRIBUTES, CompletionItemKind::PROPERTY)
+}
+
+/// Completion items for the query chain methods.
+pub fn get_query_method_completions() -> Vec<CompletionItem> {
+    items(QUERY_METHODS, CompletionItemKind::METHOD)
+}
+
+fn items(labels: &[&str], kind: CompletionItemKind) -> Vec<CompletionItem> {
+    labels
+        .iter()
+        .map(|label| CompletionItem { label: label.to_string(), kind: Some(kind), ..CompletionItem::default() })
+        .collect()
+}
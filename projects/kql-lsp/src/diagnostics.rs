@@ -0,0 +1,110 @@
+//! Diagnostic collection and conversion between byte offsets and LSP
+//! line/character positions.
+
+use kql_analyzer::Compiler;
+use kql_types::{KqlError, LineIndex, Span};
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag, Position, Range, Url};
+
+/// Compile `source` and collect every diagnostic to publish: compile errors,
+/// or deprecation warnings when the document compiles cleanly.
+pub fn collect(source: &str) -> Vec<Diagnostic> {
+    let index = LineIndex::new(source);
+    collect_spanned(source).into_iter().map(|(span, diagnostic)| positioned(&index, span, diagnostic)).collect()
+}
+
+/// Compile every open document as one merged program, the way `import`
+/// declarations merge files, and route each diagnostic back to the document
+/// whose text produced it. Every document gets an entry, empty when it has no
+/// findings, so previously published diagnostics are cleared.
+pub fn collect_workspace(documents: &[(Url, String)]) -> Vec<(Url, Vec<Diagnostic>)> {
+    let mut ordered: Vec<&(Url, String)> = documents.iter().collect();
+    ordered.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+    let mut merged = String::new();
+    let mut starts = Vec::new();
+    for (_, source) in &ordered {
+        starts.push(merged.len());
+        merged.push_str(source);
+        if !merged.ends_with('\n') {
+            merged.push('\n');
+        }
+    }
+    let mut results: Vec<(Url, Vec<Diagnostic>)> = ordered.iter().map(|(uri, _)| (uri.clone(), Vec::new())).collect();
+    let indexes: Vec<LineIndex> = ordered.iter().map(|(_, source)| LineIndex::new(source)).collect();
+    for (span, diagnostic) in collect_spanned(&merged) {
+        let owner = starts.iter().rposition(|start| *start <= span.start).unwrap_or(0);
+        let start = starts[owner];
+        let local = Span::new(span.start - start, span.end - start);
+        results[owner].1.push(positioned(&indexes[owner], local, diagnostic));
+    }
+    results
+}
+
+/// Convert compile errors into LSP diagnostics against `source`.
+pub fn to_lsp_diagnostics(source: &str, errors: &[KqlError]) -> Vec<Diagnostic> {
+    let index = LineIndex::new(source);
+    errors.iter().map(|error| positioned(&index, error.span().unwrap_or_default(), error_diagnostic(error))).collect()
+}
+
+/// Compile `source` and pair each diagnostic with the span it was raised at,
+/// leaving the range unset so callers can position it against the right text.
+fn collect_spanned(source: &str) -> Vec<(Span, Diagnostic)> {
+    match Compiler::new().compile_source(source) {
+        Ok(hir) => hir
+            .deprecations
+            .iter()
+            .map(|use_site| {
+                let message = match &use_site.note {
+                    Some(note) => format!("`{}` is deprecated: {}", use_site.symbol, note),
+                    None => format!("`{}` is deprecated", use_site.symbol),
+                };
+                let diagnostic = Diagnostic {
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    source: Some("kql".to_string()),
+                    message,
+                    tags: Some(vec![DiagnosticTag::DEPRECATED]),
+                    ..Diagnostic::default()
+                };
+                (use_site.span, diagnostic)
+            })
+            .collect(),
+        Err(errors) => errors.iter().map(|error| (error.span().unwrap_or_default(), error_diagnostic(error))).collect(),
+    }
+}
+
+fn error_diagnostic(error: &KqlError) -> Diagnostic {
+    Diagnostic {
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some("kql".to_string()),
+        message: error.message().to_string(),
+        ..Diagnostic::default()
+    }
+}
+
+fn positioned(index: &LineIndex, span: Span, mut diagnostic: Diagnostic) -> Diagnostic {
+    diagnostic.range = index_span_to_range(index, span);
+    diagnostic
+}
+
+/// Convert a source span into an LSP range. Callers converting many spans
+/// should build a [LineIndex] once and use [index_span_to_range] instead.
+pub fn span_to_range(source: &str, span: Span) -> Range {
+    index_span_to_range(&LineIndex::new(source), span)
+}
+
+/// Convert a source span into an LSP range through a prebuilt [LineIndex].
+pub fn index_span_to_range(index: &LineIndex, span: Span) -> Range {
+    Range { start: index_to_position(index, span.start), end: index_to_position(index, span.end) }
+}
+
+/// Convert a byte offset into a zero-based LSP position. Callers converting
+/// many offsets should build a [LineIndex] once and use [index_to_position].
+pub fn offset_to_position(source: &str, offset: usize) -> Position {
+    index_to_position(&LineIndex::new(source), offset)
+}
+
+/// Convert a byte offset into a zero-based LSP position through a prebuilt
+/// [LineIndex].
+pub fn index_to_position(index: &LineIndex, offset: usize) -> Position {
+    let (line, character) = index.line_col(offset);
+    Position { line: line as u32, character: character as u32 }
+}
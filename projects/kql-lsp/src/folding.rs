@@ -0,0 +1,53 @@
+//! Folding ranges for braced declaration bodies and block comments.
+
+use crate::diagnostics::index_to_position;
+use kql_ast::Decl;
+use kql_ast::visit::{Visitor, walk_decl};
+use kql_parser::{Lexer, Parser, TokenKind};
+use kql_types::LineIndex;
+use tower_lsp::lsp_types::{FoldingRange, FoldingRangeKind};
+
+/// Compute the folding ranges of `source`: one region per multi-line
+/// `struct`/`enum`/`namespace`/`seed` body (namespaces recursively) and one
+/// comment range per multi-line `/* ... */` comment. Broken sources fold the
+/// declarations the recovering parse still understood.
+pub fn folding_ranges(source: &str) -> Vec<FoldingRange> {
+    let mut ranges = Vec::new();
+    let index = LineIndex::new(source);
+    let (db, _) = Parser::parse_program_recover(source);
+    let mut collector = FoldCollector { index: &index, ranges: &mut ranges };
+    collector.visit_database(&db);
+    for token in Lexer::tokenize_all(source) {
+        if matches!(token.kind, TokenKind::BlockComment(_)) {
+            push_range(&index, token.span.start, token.span.end, Some(FoldingRangeKind::Comment), &mut ranges);
+        }
+    }
+    ranges
+}
+
+/// Collects one folding region per braced declaration body, descending into
+/// namespaces via the default walk.
+struct FoldCollector<'a> {
+    index: &'a LineIndex<'a>,
+    ranges: &'a mut Vec<FoldingRange>,
+}
+
+impl Visitor for FoldCollector<'_> {
+    fn visit_decl(&mut self, decl: &Decl) {
+        if matches!(decl, Decl::Struct(_) | Decl::Enum(_) | Decl::Seed(_) | Decl::Namespace(_)) {
+            let span = decl.span();
+            push_range(self.index, span.start, span.end, Some(FoldingRangeKind::Region), self.ranges);
+        }
+        walk_decl(self, decl);
+    }
+}
+
+fn push_range(index: &LineIndex, start: usize, end: usize, kind: Option<FoldingRangeKind>, ranges: &mut Vec<FoldingRange>) {
+    let start = index_to_position(index, start);
+    let end = index_to_position(index, end);
+    // A single-line body has nothing to fold.
+    if start.line == end.line {
+        return;
+    }
+    ranges.push(FoldingRange { start_line: start.line, end_line: end.line, kind, ..FoldingRange::default() });
+}
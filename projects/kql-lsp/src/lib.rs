@@ -0,0 +1,11 @@
+#![deny(missing_debug_implementations)]
+#![warn(rustdoc::missing_crate_level_docs)]
+#![doc = include_str!("../readme.md")]
+
+pub mod completion;
+pub mod diagnostics;
+pub mod folding;
+pub mod relations;
+mod server;
+
+pub use crate::server::{KqlLanguageServer, run_stdio};
@@ -0,0 +1,6 @@
+//! The `kql-lsp` binary: runs the language server over stdio.
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    kql_lsp::run_stdio().await;
+}
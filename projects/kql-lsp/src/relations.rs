@@ -0,0 +1,70 @@
+//! Relation navigation: for one struct, the structs it references and the
+//! structs that reference it, served as the custom `kql/relations` request.
+
+use kql_analyzer::mir::{MirProgram, Relation};
+use serde::{Deserialize, Serialize};
+
+/// Parameters of the `kql/relations` request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelationParams {
+    /// The struct (or table) name to navigate from.
+    pub name: String,
+}
+
+/// The response of the `kql/relations` request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelationNavigation {
+    /// Relations declared on other structs that point at this one.
+    pub incoming: Vec<RelationEdge>,
+    /// Relations this struct declares to other structs.
+    pub outgoing: Vec<RelationEdge>,
+}
+
+/// One edge of the relation graph, as seen from the queried struct.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelationEdge {
+    /// The relation name, from the field that declared it.
+    pub name: String,
+    /// The table on the other end of the edge.
+    pub table: String,
+    /// The FK column on the declaring table, for singular relations.
+    pub fk_column: Option<String>,
+    /// `one_to_one`, `one_to_many` or `many_to_many`.
+    pub cardinality: String,
+}
+
+/// Collect the incoming and outgoing relations of the table named `name`,
+/// matched against either the physical table name or the struct name it was
+/// derived from. Returns [None] when no such table exists.
+pub fn navigate(mir: &MirProgram, name: &str) -> Option<RelationNavigation> {
+    let table = mir.tables.values().find(|t| t.name == name || t.struct_name == name)?;
+    let mut incoming = Vec::new();
+    let mut outgoing = Vec::new();
+    for other in mir.tables.values() {
+        for relation in &other.relations {
+            if other.name == table.name {
+                outgoing.push(edge(relation, &relation.to_table));
+            } else if relation.to_table == table.name {
+                incoming.push(edge(relation, &relation.from_table));
+            }
+        }
+    }
+    incoming.sort_by(|a, b| (&a.table, &a.name).cmp(&(&b.table, &b.name)));
+    outgoing.sort_by(|a, b| (&a.table, &a.name).cmp(&(&b.table, &b.name)));
+    Some(RelationNavigation { incoming, outgoing })
+}
+
+fn edge(relation: &Relation, table: &str) -> RelationEdge {
+    use kql_analyzer::mir::Cardinality;
+    let cardinality = match relation.cardinality {
+        Cardinality::OneToOne => "one_to_one",
+        Cardinality::OneToMany => "one_to_many",
+        Cardinality::ManyToMany => "many_to_many",
+    };
+    RelationEdge {
+        name: relation.name.clone(),
+        table: table.to_string(),
+        fk_column: relation.fk_column.clone(),
+        cardinality: cardinality.to_string(),
+    }
+}
@@ -0,0 +1,123 @@
+use crate::relations::{RelationNavigation, RelationParams};
+use kql_analyzer::{Compiler, mir::mir_gen::MirLowerer};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use tower_lsp::{
+    Client, LanguageServer, LspService, Server,
+    jsonrpc::Result,
+    lsp_types::{
+        CompletionOptions, CompletionParams, CompletionResponse, DiagnosticOptions, DiagnosticServerCapabilities,
+        DidChangeTextDocumentParams, DidOpenTextDocumentParams, FoldingRange, FoldingRangeParams,
+        FoldingRangeProviderCapability, InitializeParams, InitializeResult, MessageType, ServerCapabilities,
+        TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+    },
+};
+
+/// The KQL language server backend.
+#[derive(Debug)]
+pub struct KqlLanguageServer {
+    client: Client,
+    documents: RwLock<HashMap<Url, String>>,
+}
+
+impl KqlLanguageServer {
+    /// Create a backend publishing to `client`.
+    pub fn new(client: Client) -> Self {
+        Self { client, documents: RwLock::new(HashMap::new()) }
+    }
+
+    /// Recompile every open document as one workspace and publish per-file
+    /// diagnostics, so an error in a cross-file reference lands in the file
+    /// making the reference.
+    async fn check_workspace(&self) {
+        let documents: Vec<(Url, String)> =
+            self.documents.read().await.iter().map(|(uri, text)| (uri.clone(), text.clone())).collect();
+        for (uri, diagnostics) in crate::diagnostics::collect_workspace(&documents) {
+            self.client.publish_diagnostics(uri, diagnostics, None).await;
+        }
+    }
+
+    /// Handle the custom `kql/relations` request: the incoming and outgoing
+    /// relations of one struct, computed over every open document. Returns
+    /// [None] while the workspace does not compile or the struct is unknown.
+    pub async fn relations(&self, params: RelationParams) -> Result<Option<RelationNavigation>> {
+        let documents = self.documents.read().await;
+        let mut sources: Vec<&String> = documents.values().collect();
+        sources.sort();
+        let merged = sources.iter().map(|s| s.as_str()).collect::<Vec<_>>().join("\n");
+        let Ok(hir) = Compiler::new().compile_source(&merged) else {
+            return Ok(None);
+        };
+        let Ok(mir) = MirLowerer::new(hir).lower() else {
+            return Ok(None);
+        };
+        Ok(crate::relations::navigate(&mir, &params.name))
+    }
+}
+
+#[tower_lsp::async_trait]
+impl LanguageServer for KqlLanguageServer {
+    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+        Ok(InitializeResult {
+            capabilities: ServerCapabilities {
+                text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+                completion_provider: Some(CompletionOptions {
+                    trigger_characters: Some(vec!["@".to_string(), ".".to_string(), ":".to_string()]),
+                    ..CompletionOptions::default()
+                }),
+                folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+                diagnostic_provider: Some(DiagnosticServerCapabilities::Options(DiagnosticOptions {
+                    inter_file_dependencies: true,
+                    workspace_diagnostics: true,
+                    ..DiagnosticOptions::default()
+                })),
+                ..ServerCapabilities::default()
+            },
+            ..InitializeResult::default()
+        })
+    }
+
+    async fn initialized(&self, _: tower_lsp::lsp_types::InitializedParams) {
+        self.client.log_message(MessageType::INFO, "kql language server ready").await;
+    }
+
+    async fn shutdown(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        self.documents.write().await.insert(params.text_document.uri, params.text_document.text);
+        self.check_workspace().await;
+    }
+
+    async fn did_change(&self, params: DidChangeTextDocumentParams) {
+        let uri = params.text_document.uri;
+        let Some(change) = params.content_changes.into_iter().last() else {
+            return;
+        };
+        self.documents.write().await.insert(uri, change.text);
+        self.check_workspace().await;
+    }
+
+    async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
+        let documents = self.documents.read().await;
+        Ok(documents.get(&params.text_document.uri).map(|source| crate::folding::folding_ranges(source)))
+    }
+
+    async fn completion(&self, _: CompletionParams) -> Result<Option<CompletionResponse>> {
+        let mut items = crate::completion::get_keyword_completions();
+        items.extend(crate::completion::get_type_completions());
+        items.extend(crate::completion::get_attribute_completions());
+        items.extend(crate::completion::get_query_method_completions());
+        Ok(Some(CompletionResponse::Array(items)))
+    }
+}
+
+/// Run the language server over stdio until the client disconnects.
+pub async fn run_stdio() {
+    let stdin = tokio::io::stdin();
+    let stdout = tokio::io::stdout();
+    let (service, socket) =
+        LspService::build(KqlLanguageServer::new).custom_method("kql/relations", KqlLanguageServer::relations).finish();
+    Server::new(stdin, stdout, socket).serve(service).await;
+}
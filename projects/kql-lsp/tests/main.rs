@@ -0,0 +1,143 @@
+use kql_lsp::diagnostics::{collect, offset_to_position};
+use tower_lsp::lsp_types::{DiagnosticSeverity, DiagnosticTag};
+
+#[test]
+fn ready() {
+    println!("it works!")
+}
+
+#[test]
+fn converts_offsets_to_positions() {
+    let source = "struct User {\n    id: i64,\n}\n";
+    let position = offset_to_position(source, 0);
+    assert_eq!((position.line, position.character), (0, 0));
+    let position = offset_to_position(source, source.find("id").unwrap());
+    assert_eq!((position.line, position.character), (1, 4));
+}
+
+#[test]
+fn tags_uses_of_deprecated_structs() {
+    let source = r#"
+@deprecated("use Account instead")
+struct User { id: Key<User, i64> }
+
+struct Session {
+    id: Key<Session, i64>,
+    user: ForeignKey<User>,
+    owner: User,
+}
+"#;
+    let diagnostics = collect(source);
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+    let diagnostic = &diagnostics[0];
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(diagnostic.tags, Some(vec![DiagnosticTag::DEPRECATED]));
+    assert!(diagnostic.message.contains("use Account instead"), "{}", diagnostic.message);
+}
+
+#[test]
+fn completion_categories_cover_the_language_surface() {
+    use kql_lsp::completion;
+    use tower_lsp::lsp_types::CompletionItemKind;
+    let labels = |items: &[tower_lsp::lsp_types::CompletionItem]| items.iter().map(|i| i.label.clone()).collect::<Vec<_>>();
+    let keywords = completion::get_keyword_completions();
+    assert!(keywords.iter().all(|i| i.kind == Some(CompletionItemKind::KEYWORD)));
+    assert!(labels(&keywords).contains(&"struct".to_string()));
+    let types = completion::get_type_completions();
+    assert!(types.iter().all(|i| i.kind == Some(CompletionItemKind::CLASS)));
+    assert!(labels(&types).contains(&"ForeignKey".to_string()));
+    let attributes = completion::get_attribute_completions();
+    assert!(attributes.iter().all(|i| i.kind == Some(CompletionItemKind::PROPERTY)));
+    assert!(labels(&attributes).contains(&"auto_increment".to_string()));
+    let methods = completion::get_query_method_completions();
+    assert!(methods.iter().all(|i| i.kind == Some(CompletionItemKind::METHOD)));
+    assert!(labels(&methods).contains(&"filter".to_string()));
+}
+
+#[test]
+fn maps_compile_errors_to_lsp_diagnostics() {
+    let source = "struct User {\n    id: Missing,\n}\n";
+    let errors = kql_analyzer::Compiler::new().compile_source(source).unwrap_err();
+    let diagnostics = kql_lsp::diagnostics::to_lsp_diagnostics(source, &errors);
+    assert_eq!(diagnostics.len(), errors.len());
+    let diagnostic = &diagnostics[0];
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(diagnostic.range.start.line, 1);
+    assert!(diagnostic.message.contains("Missing"), "{}", diagnostic.message);
+}
+
+#[test]
+fn routes_workspace_diagnostics_to_the_referencing_file() {
+    use kql_lsp::diagnostics::collect_workspace;
+    use tower_lsp::lsp_types::Url;
+    let users = Url::parse("file:///a/users.kql").unwrap();
+    let posts = Url::parse("file:///a/posts.kql").unwrap();
+    let user_source = "struct User { id: Key<User, i64> }\n".to_string();
+    let good = vec![
+        (users.clone(), user_source.clone()),
+        (posts.clone(), "struct Post {\n    id: Key<Post, i64>,\n    author: ForeignKey<User>,\n}\n".to_string()),
+    ];
+    for (_, diagnostics) in collect_workspace(&good) {
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+    }
+    let broken = vec![
+        (users, user_source),
+        (posts.clone(), "struct Post {\n    id: Key<Post, i64>,\n    author: ForeignKey<Account>,\n}\n".to_string()),
+    ];
+    let results = collect_workspace(&broken);
+    let for_file = |uri: &Url| results.iter().find(|(u, _)| u == uri).unwrap().1.clone();
+    let diagnostics = for_file(&posts);
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+    // Positioned against posts.kql itself, not the merged workspace text.
+    assert_eq!(diagnostics[0].range.start.line, 2);
+    assert!(diagnostics[0].message.contains("expected a struct name"), "{}", diagnostics[0].message);
+    assert!(for_file(&broken[0].0).is_empty());
+}
+
+#[test]
+fn navigates_relations_in_both_directions() {
+    use kql_analyzer::{Compiler, mir::mir_gen::MirLowerer};
+    use kql_lsp::relations::navigate;
+    let source = r#"
+struct User { id: Key<User, i64> }
+
+struct Post {
+    id: Key<Post, i64>,
+    author: ForeignKey<User>,
+}
+"#;
+    let mir = MirLowerer::new(Compiler::new().compile_source(source).unwrap()).lower().unwrap();
+    let user = navigate(&mir, "User").unwrap();
+    assert!(user.outgoing.is_empty(), "{:?}", user.outgoing);
+    assert_eq!(user.incoming.len(), 1, "{:?}", user.incoming);
+    assert_eq!(user.incoming[0].name, "author");
+    assert_eq!(user.incoming[0].table, "post");
+    assert_eq!(user.incoming[0].fk_column.as_deref(), Some("author_id"));
+    assert_eq!(user.incoming[0].cardinality, "one_to_many");
+    let post = navigate(&mir, "post").unwrap();
+    assert!(post.incoming.is_empty(), "{:?}", post.incoming);
+    assert_eq!(post.outgoing.len(), 1, "{:?}", post.outgoing);
+    assert_eq!(post.outgoing[0].table, "user");
+    assert!(navigate(&mir, "Comment").is_none());
+}
+
+#[test]
+fn folds_declaration_bodies_and_block_comments() {
+    use kql_lsp::folding::folding_ranges;
+    use tower_lsp::lsp_types::FoldingRangeKind;
+    let source = "namespace app {\n    struct User {\n        id: Key<User, i64>,\n    }\n}\n\n/*\nlegacy notes\n*/\n";
+    let ranges = folding_ranges(source);
+    assert_eq!(ranges.len(), 3, "{ranges:?}");
+    assert_eq!((ranges[0].start_line, ranges[0].end_line), (0, 4));
+    assert_eq!(ranges[0].kind, Some(FoldingRangeKind::Region));
+    assert_eq!((ranges[1].start_line, ranges[1].end_line), (1, 3));
+    assert_eq!((ranges[2].start_line, ranges[2].end_line), (6, 8));
+    assert_eq!(ranges[2].kind, Some(FoldingRangeKind::Comment));
+}
+
+#[test]
+fn publishes_no_diagnostics_for_trivial_documents() {
+    for source in ["", "   \n\t\n", "// just a comment\n/* block */\n"] {
+        assert!(kql_lsp::diagnostics::collect(source).is_empty(), "{source:?}");
+    }
+}
@@ -0,0 +1,244 @@
+#![deny(missing_debug_implementations)]
+#![warn(rustdoc::missing_crate_level_docs)]
+#![doc = include_str!("../readme.md")]
+
+use kql_analyzer::{
+    lir::{AlterTableOp, CreateIndex, Dialect, Statement, sql_gen::SqlGenerator},
+    mir::{Column, Index, IndexColumn, MirProgram, MirType, Table},
+};
+
+/// A single schema change produced by [MigrationEngine::diff].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MigrationStep {
+    /// A table exists in the new schema only.
+    CreateTable(Table),
+    /// A table exists in the old schema only.
+    DropTable(String),
+    /// A column exists in the new version of a table only.
+    AddColumn {
+        /// The table the column belongs to.
+        table: String,
+        /// The new column.
+        column: Column,
+    },
+    /// A column exists in the old version of a table only.
+    DropColumn {
+        /// The table the column belongs to.
+        table: String,
+        /// The dropped column name.
+        column: String,
+    },
+    /// A column changed between versions. The columns are boxed to keep the
+    /// step enum compact.
+    AlterColumn {
+        /// The table the column belongs to.
+        table: String,
+        /// The column as it was.
+        old: Box<Column>,
+        /// The column as it should be.
+        new: Box<Column>,
+    },
+    /// An index exists in the new version of a table only, or changed.
+    AddIndex {
+        /// The table the index is on.
+        table: String,
+        /// The index to create.
+        index: Index,
+    },
+    /// An index exists in the old version of a table only, or changed.
+    DropIndex {
+        /// The table the index is on.
+        table: String,
+        /// The dropped index name.
+        name: String,
+    },
+}
+
+impl MigrationStep {
+    /// A human readable warning when applying this step may lose data. Column
+    /// type changes are classified as safe when the new type can represent
+    /// every value of the old one (a widening); everything else — narrowing,
+    /// or a switch between unrelated types — gets a warning. Other steps
+    /// return `None`.
+    pub fn warning(&self) -> Option<String> {
+        match self {
+            MigrationStep::AlterColumn { table, old, new } if old.ty != new.ty && !is_widening(&old.ty, &new.ty) => {
+                Some(format!("potentially lossy: {}.{} changes from {:?} to {:?}", table, new.name, old.ty, new.ty))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Whether `new` can represent every value of `old`: a wider integer of the
+/// same signedness, `f32` to `f64`, or any type to text.
+fn is_widening(old: &MirType, new: &MirType) -> bool {
+    match (int_width(old), int_width(new)) {
+        (Some((signed, from)), Some((new_signed, to))) => signed == new_signed && from <= to,
+        _ => matches!((old, new), (MirType::F32, MirType::F64)) || *new == MirType::Text,
+    }
+}
+
+/// `(signed, bits)` for integer types, `None` for everything else.
+fn int_width(ty: &MirType) -> Option<(bool, u8)> {
+    match ty {
+        MirType::I8 => Some((true, 8)),
+        MirType::I16 => Some((true, 16)),
+        MirType::I32 => Some((true, 32)),
+        MirType::I64 => Some((true, 64)),
+        MirType::U8 => Some((false, 8)),
+        MirType::U16 => Some((false, 16)),
+        MirType::U32 => Some((false, 32)),
+        MirType::U64 => Some((false, 64)),
+        _ => None,
+    }
+}
+
+/// Computes the steps needed to migrate between two schema versions.
+#[derive(Debug, Default)]
+pub struct MigrationEngine {}
+
+impl MigrationEngine {
+    /// Create an engine with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Diff two schemas, producing steps in a safe phase order: drops come
+    /// before adds so replacing a column or index under the same name never
+    /// conflicts with its old definition, and whole tables are handled last.
+    /// Within a phase, steps keep their discovery order.
+    pub fn diff(&self, old: &MirProgram, new: &MirProgram) -> Vec<MigrationStep> {
+        let mut steps = Vec::new();
+        for (key, new_table) in &new.tables {
+            match old.tables.get(key) {
+                Some(old_table) => self.diff_table(old_table, new_table, &mut steps),
+                None => steps.push(MigrationStep::CreateTable(new_table.clone())),
+            }
+        }
+        for (key, old_table) in &old.tables {
+            if !new.tables.contains_key(key) {
+                steps.push(MigrationStep::DropTable(old_table.name.clone()));
+            }
+        }
+        steps.sort_by_key(phase);
+        steps
+    }
+
+    fn diff_table(&self, old: &Table, new: &Table, steps: &mut Vec<MigrationStep>) {
+        for new_column in &new.columns {
+            match old.column(&new_column.name) {
+                Some(old_column) if old_column != new_column => {
+                    // A flipped field-level `@unique` migrates as a unique
+                    // index under the generated `<table>_<column>_key` name;
+                    // inline constraints cannot be altered portably.
+                    if old_column.unique != new_column.unique {
+                        let name = format!("{}_{}_key", new.name, new_column.name);
+                        if new_column.unique {
+                            let column = IndexColumn { name: new_column.name.clone(), expr: false, order: None };
+                            let index = Index { name, columns: vec![column], unique: true, method: None };
+                            steps.push(MigrationStep::AddIndex { table: new.name.clone(), index });
+                        } else {
+                            steps.push(MigrationStep::DropIndex { table: new.name.clone(), name });
+                        }
+                    }
+                    // The unique flag is handled above and a moved declaration
+                    // is not a schema change, so neither forces an alter.
+                    let mut normalized = old_column.clone();
+                    normalized.unique = new_column.unique;
+                    normalized.span = new_column.span;
+                    if normalized != *new_column {
+                        steps.push(MigrationStep::AlterColumn {
+                            table: new.name.clone(),
+                            old: Box::new(old_column.clone()),
+                            new: Box::new(new_column.clone()),
+                        });
+                    }
+                }
+                Some(_) => {}
+                None => steps.push(MigrationStep::AddColumn { table: new.name.clone(), column: new_column.clone() }),
+            }
+        }
+        for old_column in &old.columns {
+            if new.column(&old_column.name).is_none() {
+                steps.push(MigrationStep::DropColumn { table: new.name.clone(), column: old_column.name.clone() });
+            }
+        }
+        for new_index in &new.indexes {
+            match old.indexes.iter().find(|i| i.name == new_index.name) {
+                Some(old_index) if old_index != new_index => {
+                    steps.push(MigrationStep::DropIndex { table: new.name.clone(), name: old_index.name.clone() });
+                    steps.push(MigrationStep::AddIndex { table: new.name.clone(), index: new_index.clone() });
+                }
+                Some(_) => {}
+                None => steps.push(MigrationStep::AddIndex { table: new.name.clone(), index: new_index.clone() }),
+            }
+        }
+        for old_index in &old.indexes {
+            if !new.indexes.iter().any(|i| i.name == old_index.name) {
+                steps.push(MigrationStep::DropIndex { table: new.name.clone(), name: old_index.name.clone() });
+            }
+        }
+    }
+
+    /// Render migration steps as DDL statements against the new schema.
+    pub fn generate_migration(&self, steps: &[MigrationStep], new: &MirProgram, dialect: Dialect) -> Vec<Statement> {
+        let generator = SqlGenerator::new(new, dialect);
+        let mut statements = Vec::new();
+        for step in steps {
+            match step {
+                MigrationStep::CreateTable(table) => {
+                    let mir = single_table_program(table);
+                    let table_generator = SqlGenerator::new(&mir, dialect);
+                    statements.extend(table_generator.generate_ddl());
+                }
+                MigrationStep::DropTable(name) => statements.push(Statement::DropTable { name: name.clone(), if_exists: true }),
+                MigrationStep::AddColumn { table, column } => statements.push(Statement::AlterTable {
+                    table: table.clone(),
+                    op: AlterTableOp::AddColumn(generator.generate_column_def(column)),
+                }),
+                MigrationStep::DropColumn { table, column } => statements
+                    .push(Statement::AlterTable { table: table.clone(), op: AlterTableOp::DropColumn(column.clone()) }),
+                MigrationStep::AlterColumn { table, new, .. } => {
+                    let def = generator.generate_column_def(new);
+                    statements.push(Statement::AlterTable {
+                        table: table.clone(),
+                        op: AlterTableOp::AlterColumnType { name: new.name.clone(), data_type: def.data_type },
+                    });
+                }
+                MigrationStep::AddIndex { table, index } => statements.push(Statement::CreateIndex(CreateIndex {
+                    name: index.name.clone(),
+                    table: table.clone(),
+                    columns: index.columns.iter().map(|c| c.as_sql()).collect(),
+                    unique: index.unique,
+                    if_not_exists: true,
+                    using: index.method.clone(),
+                })),
+                MigrationStep::DropIndex { table, name } => {
+                    statements.push(Statement::DropIndex { name: name.clone(), table: table.clone(), if_exists: true })
+                }
+            }
+        }
+        statements
+    }
+}
+
+/// The execution phase of a step. The stable sort in [MigrationEngine::diff]
+/// runs earlier phases first.
+fn phase(step: &MigrationStep) -> u8 {
+    match step {
+        MigrationStep::DropIndex { .. } => 0,
+        MigrationStep::DropColumn { .. } => 1,
+        MigrationStep::AddColumn { .. } => 2,
+        MigrationStep::AlterColumn { .. } => 3,
+        MigrationStep::AddIndex { .. } => 4,
+        MigrationStep::DropTable(_) => 5,
+        MigrationStep::CreateTable(_) => 6,
+    }
+}
+
+fn single_table_program(table: &Table) -> MirProgram {
+    let mut mir = MirProgram::default();
+    mir.tables.insert(table.struct_name.clone(), table.clone());
+    mir
+}
@@ -0,0 +1,175 @@
+use kql_analyzer::{
+    Compiler,
+    lir::{Dialect, sql_gen::SqlGenerator},
+    mir::{MirProgram, mir_gen::MirLowerer},
+};
+use kql_migrate::{MigrationEngine, MigrationStep};
+
+#[test]
+fn ready() {
+    println!("it works!")
+}
+
+fn compile(source: &str) -> MirProgram {
+    let hir = Compiler::new().compile_source(source).unwrap();
+    MirLowerer::new(hir).lower().unwrap()
+}
+
+#[test]
+fn diff_detects_added_column() {
+    let old = compile("struct User { id: Key<User, i64>, name: String }");
+    let new = compile("struct User { id: Key<User, i64>, name: String, age: i32? }");
+    let steps = MigrationEngine::new().diff(&old, &new);
+    assert_eq!(steps.len(), 1);
+    let MigrationStep::AddColumn { table, column } = &steps[0] else {
+        panic!("expected AddColumn, got {:?}", steps[0]);
+    };
+    assert_eq!(table, "user");
+    assert_eq!(column.name, "age");
+}
+
+#[test]
+fn diff_detects_dropped_table() {
+    let old = compile("struct User { id: Key<User, i64> } struct Post { id: Key<Post, i64> }");
+    let new = compile("struct User { id: Key<User, i64> }");
+    let steps = MigrationEngine::new().diff(&old, &new);
+    assert!(matches!(&steps[..], [MigrationStep::DropTable(name)] if name == "post"));
+}
+
+#[test]
+fn migration_renders_alter_statements() {
+    let old = compile("struct User { id: Key<User, i64> }");
+    let new = compile("struct User { id: Key<User, i64>, email: String }");
+    let engine = MigrationEngine::new();
+    let steps = engine.diff(&old, &new);
+    let statements = engine.generate_migration(&steps, &new, Dialect::Postgres);
+    assert_eq!(statements.len(), 1);
+}
+
+#[test]
+fn index_migrations_render_per_dialect() {
+    let new = compile("@index(name)\nstruct User { id: Key<User, i64>, name: String }");
+    let index = new.tables.values().next().unwrap().indexes[0].clone();
+    let steps = [
+        MigrationStep::AddIndex { table: "user".into(), index: index.clone() },
+        MigrationStep::DropIndex { table: "user".into(), name: index.name.clone() },
+    ];
+    let engine = MigrationEngine::new();
+    let render = |dialect| {
+        let generator = SqlGenerator::new(&new, dialect);
+        engine.generate_migration(&steps, &new, dialect).iter().map(|s| generator.render(s)).collect::<Vec<_>>()
+    };
+    assert_eq!(
+        render(Dialect::Postgres),
+        ["CREATE INDEX IF NOT EXISTS user_name_idx ON user (name)", "DROP INDEX IF EXISTS user_name_idx"]
+    );
+    assert_eq!(
+        render(Dialect::Sqlite),
+        ["CREATE INDEX IF NOT EXISTS user_name_idx ON user (name)", "DROP INDEX IF EXISTS user_name_idx"]
+    );
+    // MySQL has no `IF [NOT] EXISTS` for indexes and drops them via the table.
+    assert_eq!(render(Dialect::MySql), ["CREATE INDEX user_name_idx ON user (name)", "DROP INDEX user_name_idx ON user"]);
+}
+
+#[test]
+fn widening_alterations_carry_no_warning() {
+    let old = compile("struct User { id: Key<User, i64>, age: i32 }");
+    let new = compile("struct User { id: Key<User, i64>, age: i64 }");
+    let steps = MigrationEngine::new().diff(&old, &new);
+    assert_eq!(steps.len(), 1, "{steps:#?}");
+    assert_eq!(steps[0].warning(), None);
+}
+
+#[test]
+fn lossy_alterations_warn() {
+    let old = compile("struct User { id: Key<User, i64>, age: String }");
+    let new = compile("struct User { id: Key<User, i64>, age: i32 }");
+    let steps = MigrationEngine::new().diff(&old, &new);
+    assert_eq!(steps.len(), 1, "{steps:#?}");
+    let warning = steps[0].warning().expect("a narrowing change should warn");
+    assert!(warning.contains("potentially lossy"), "{warning}");
+    assert!(warning.contains("user.age"), "{warning}");
+}
+
+#[test]
+fn diff_orders_steps_into_safe_phases() {
+    // `name`/`mail` and `i32`/`i64` keep unchanged columns at identical
+    // offsets, so only the real changes show up as steps.
+    let old = compile(
+        r#"
+@index(name)
+struct User {
+    id: Key<User, i64>,
+    name: String,
+    age: i32,
+    nickname: String,
+}
+
+struct Session { id: Key<Session, i64> }
+"#,
+    );
+    let new = compile(
+        r#"
+@index(mail)
+struct User {
+    id: Key<User, i64>,
+    name: String,
+    age: i64,
+    mail: String,
+}
+
+struct AuditLog { id: Key<AuditLog, i64> }
+"#,
+    );
+    let steps = MigrationEngine::new().diff(&old, &new);
+    let kinds: Vec<&str> = steps
+        .iter()
+        .map(|step| match step {
+            MigrationStep::DropIndex { .. } => "drop_index",
+            MigrationStep::DropColumn { .. } => "drop_column",
+            MigrationStep::AddColumn { .. } => "add_column",
+            MigrationStep::AlterColumn { .. } => "alter_column",
+            MigrationStep::AddIndex { .. } => "add_index",
+            MigrationStep::DropTable(_) => "drop_table",
+            MigrationStep::CreateTable(_) => "create_table",
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        ["drop_index", "drop_column", "add_column", "alter_column", "add_index", "drop_table", "create_table"],
+        "{steps:#?}"
+    );
+}
+
+#[test]
+fn toggling_field_unique_migrates_as_an_index() {
+    let old = compile("struct User { id: Key<User, i64>, email: String }");
+    let new = compile("struct User { id: Key<User, i64>, email: String @unique }");
+    let engine = MigrationEngine::new();
+    let steps = engine.diff(&old, &new);
+    assert!(
+        matches!(&steps[..], [MigrationStep::AddIndex { table, index }]
+            if table == "user" && index.unique && index.name == "user_email_key"),
+        "{steps:#?}"
+    );
+    let steps = engine.diff(&new, &old);
+    assert!(
+        matches!(&steps[..], [MigrationStep::DropIndex { table, name }]
+            if table == "user" && name == "user_email_key"),
+        "{steps:#?}"
+    );
+}
+
+#[test]
+fn decimal_precision_survives_into_alter_ddl() {
+    let old = compile("struct Invoice { id: Key<Invoice, i64>, total: d128 @precision(10, 2) }");
+    let new = compile("struct Invoice { id: Key<Invoice, i64>, total: d128 @precision(12, 4) }");
+    let engine = MigrationEngine::new();
+    let steps = engine.diff(&old, &new);
+    assert_eq!(steps.len(), 1, "{steps:?}");
+    assert!(matches!(&steps[0], MigrationStep::AlterColumn { .. }), "{steps:?}");
+    let statements = engine.generate_migration(&steps, &new, Dialect::Postgres);
+    let generator = SqlGenerator::new(&new, Dialect::Postgres);
+    let sql = generator.render(&statements[0]);
+    assert_eq!(sql, "ALTER TABLE invoice ALTER COLUMN total SET DATA TYPE DECIMAL(12, 4)");
+}
@@ -0,0 +1,350 @@
+use kql_types::Span;
+
+/// A single token together with the span it was lexed from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    /// Which kind of token this is.
+    pub kind: TokenKind,
+    /// Location in the source file.
+    pub span: Span,
+}
+
+/// The kind of a [Token].
+///
+/// Keywords are not distinguished at the lexer level; the parser matches on
+/// the identifier text instead.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    /// An identifier or keyword.
+    Ident(String),
+    /// A backtick-quoted identifier, without the backticks. Quoting lets a
+    /// name collide with a keyword without being parsed as one.
+    QuotedIdent(String),
+    /// An integer literal, with an optional type suffix like `i64`.
+    Int(i64, Option<String>),
+    /// A floating point literal, with an optional type suffix like `f32`.
+    Float(f64, Option<String>),
+    /// A string literal, with quotes and escapes resolved.
+    Str(String),
+    /// A `///` doc comment, without the leading slashes.
+    DocComment(String),
+    /// A `//` line comment, without the leading slashes.
+    LineComment(String),
+    /// A `/* ... */` block comment, without the delimiters.
+    BlockComment(String),
+    /// A run of whitespace.
+    Whitespace,
+    /// `{`
+    LBrace,
+    /// `}`
+    RBrace,
+    /// `(`
+    LParen,
+    /// `)`
+    RParen,
+    /// `[`
+    LBracket,
+    /// `]`
+    RBracket,
+    /// `,`
+    Comma,
+    /// `;`
+    Semicolon,
+    /// `:`
+    Colon,
+    /// `::`
+    ColonColon,
+    /// `.`
+    Dot,
+    /// `?`
+    Question,
+    /// `@`
+    At,
+    /// `$`
+    Dollar,
+    /// `=`
+    Assign,
+    /// `==`
+    EqEq,
+    /// `!`
+    Bang,
+    /// `!=` or `<>`
+    NotEq,
+    /// `<`
+    Lt,
+    /// `<=`
+    Le,
+    /// `>`
+    Gt,
+    /// `>=`
+    Ge,
+    /// `+`
+    Plus,
+    /// `-`
+    Minus,
+    /// `*`
+    Star,
+    /// `/`
+    Slash,
+    /// `%`
+    Percent,
+    /// `&`, reserved for future bitwise use.
+    Amp,
+    /// `&&`
+    AmpAmp,
+    /// `|`, reserved for future bitwise use.
+    Pipe,
+    /// `||`
+    PipePipe,
+    /// `|>`
+    PipeGt,
+    /// End of input.
+    Eof,
+    /// A character the lexer does not recognize.
+    Unknown(char),
+}
+
+impl TokenKind {
+    /// Whether this token is whitespace or a non-doc comment the parser skips.
+    pub fn is_trivia(&self) -> bool {
+        matches!(self, Self::Whitespace | Self::LineComment(_) | Self::BlockComment(_))
+    }
+}
+
+/// A hand written lexer over a source string.
+#[derive(Debug)]
+pub struct Lexer<'a> {
+    source: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    /// Create a lexer over `source`.
+    pub fn new(source: &'a str) -> Self {
+        Self { source, bytes: source.as_bytes(), pos: 0 }
+    }
+
+    /// Lex the whole of `source`, trivia included, up to but excluding the
+    /// trailing [TokenKind::Eof]. Intended for syntax-highlighting consumers
+    /// that want every comment and whitespace run.
+    pub fn tokenize_all(source: &str) -> Vec<Token> {
+        let mut lexer = Lexer::new(source);
+        let mut tokens = Vec::new();
+        loop {
+            let token = lexer.next_token();
+            if token.kind == TokenKind::Eof {
+                break;
+            }
+            tokens.push(token);
+        }
+        tokens
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn peek2(&self) -> Option<u8> {
+        self.bytes.get(self.pos + 1).copied()
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    /// Lex the next token, including trivia; returns [TokenKind::Eof] at the end forever after.
+    pub fn next_token(&mut self) -> Token {
+        let start = self.pos;
+        let kind = match self.peek() {
+            None => TokenKind::Eof,
+            Some(c) if c.is_ascii_whitespace() => {
+                while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+                    self.pos += 1;
+                }
+                TokenKind::Whitespace
+            }
+            Some(b'/') if self.peek2() == Some(b'/') => self.lex_line_comment(),
+            Some(b'/') if self.peek2() == Some(b'*') => self.lex_block_comment(),
+            Some(c) if c.is_ascii_digit() => self.lex_number(),
+            Some(c) if c.is_ascii_alphabetic() || c == b'_' => self.lex_ident(),
+            Some(b'"') => self.lex_string(),
+            Some(b'`') => self.lex_quoted_ident(),
+            Some(_) => self.lex_punct(),
+        };
+        Token { kind, span: Span::new(start, self.pos) }
+    }
+
+    fn lex_line_comment(&mut self) -> TokenKind {
+        self.pos += 2;
+        let doc = self.peek() == Some(b'/') && self.peek2() != Some(b'/');
+        if doc {
+            self.pos += 1;
+        }
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c != b'\n') {
+            self.pos += 1;
+        }
+        let text = self.source[start..self.pos].trim().to_string();
+        if doc { TokenKind::DocComment(text) } else { TokenKind::LineComment(text) }
+    }
+
+    fn lex_block_comment(&mut self) -> TokenKind {
+        self.pos += 2;
+        let start = self.pos;
+        let end;
+        loop {
+            match self.bump() {
+                None => {
+                    end = self.pos;
+                    break;
+                }
+                Some(b'*') if self.peek() == Some(b'/') => {
+                    end = self.pos - 1;
+                    self.pos += 1;
+                    break;
+                }
+                Some(_) => {}
+            }
+        }
+        TokenKind::BlockComment(self.source[start..end].trim().to_string())
+    }
+
+    fn lex_number(&mut self) -> TokenKind {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let mut float = false;
+        if self.peek() == Some(b'.') && self.peek2().is_some_and(|c| c.is_ascii_digit()) {
+            float = true;
+            self.pos += 1;
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+        }
+        let text = &self.source[start..self.pos];
+        // A trailing identifier is a type suffix like `10i64`; validation of
+        // the suffix name happens during lowering.
+        let mut suffix = None;
+        if self.peek().is_some_and(|c| c.is_ascii_alphabetic() || c == b'_') {
+            let suffix_start = self.pos;
+            while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || c == b'_') {
+                self.pos += 1;
+            }
+            suffix = Some(self.source[suffix_start..self.pos].to_string());
+        }
+        if float {
+            TokenKind::Float(text.parse().unwrap_or(0.0), suffix)
+        } else {
+            TokenKind::Int(text.parse().unwrap_or(0), suffix)
+        }
+    }
+
+    fn lex_ident(&mut self) -> TokenKind {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || c == b'_') {
+            self.pos += 1;
+        }
+        TokenKind::Ident(self.source[start..self.pos].to_string())
+    }
+
+    fn lex_string(&mut self) -> TokenKind {
+        self.pos += 1;
+        let mut text = String::new();
+        loop {
+            match self.bump() {
+                None | Some(b'"') => break,
+                Some(b'\\') => match self.bump() {
+                    Some(b'n') => text.push('\n'),
+                    Some(b't') => text.push('\t'),
+                    Some(c) => text.push(c as char),
+                    None => break,
+                },
+                Some(c) => text.push(c as char),
+            }
+        }
+        TokenKind::Str(text)
+    }
+
+    fn lex_quoted_ident(&mut self) -> TokenKind {
+        self.pos += 1;
+        let mut text = String::new();
+        loop {
+            match self.bump() {
+                None | Some(b'`') => break,
+                Some(c) => text.push(c as char),
+            }
+        }
+        TokenKind::QuotedIdent(text)
+    }
+
+    fn lex_punct(&mut self) -> TokenKind {
+        let c = self.bump().expect("lex_punct called at end of input");
+        match c {
+            b'{' => TokenKind::LBrace,
+            b'}' => TokenKind::RBrace,
+            b'(' => TokenKind::LParen,
+            b')' => TokenKind::RParen,
+            b'[' => TokenKind::LBracket,
+            b']' => TokenKind::RBracket,
+            b',' => TokenKind::Comma,
+            b';' => TokenKind::Semicolon,
+            b':' if self.peek() == Some(b':') => {
+                self.pos += 1;
+                TokenKind::ColonColon
+            }
+            b':' => TokenKind::Colon,
+            b'.' => TokenKind::Dot,
+            b'?' => TokenKind::Question,
+            b'@' => TokenKind::At,
+            b'$' => TokenKind::Dollar,
+            b'=' if self.peek() == Some(b'=') => {
+                self.pos += 1;
+                TokenKind::EqEq
+            }
+            b'=' => TokenKind::Assign,
+            b'!' if self.peek() == Some(b'=') => {
+                self.pos += 1;
+                TokenKind::NotEq
+            }
+            b'!' => TokenKind::Bang,
+            b'<' if self.peek() == Some(b'=') => {
+                self.pos += 1;
+                TokenKind::Le
+            }
+            b'<' if self.peek() == Some(b'>') => {
+                self.pos += 1;
+                TokenKind::NotEq
+            }
+            b'<' => TokenKind::Lt,
+            b'>' if self.peek() == Some(b'=') => {
+                self.pos += 1;
+                TokenKind::Ge
+            }
+            b'>' => TokenKind::Gt,
+            b'+' => TokenKind::Plus,
+            b'-' => TokenKind::Minus,
+            b'*' => TokenKind::Star,
+            b'/' => TokenKind::Slash,
+            b'%' => TokenKind::Percent,
+            b'&' if self.peek() == Some(b'&') => {
+                self.pos += 1;
+                TokenKind::AmpAmp
+            }
+            b'&' => TokenKind::Amp,
+            b'|' if self.peek() == Some(b'|') => {
+                self.pos += 1;
+                TokenKind::PipePipe
+            }
+            b'|' if self.peek() == Some(b'>') => {
+                self.pos += 1;
+                TokenKind::PipeGt
+            }
+            b'|' => TokenKind::Pipe,
+            c => TokenKind::Unknown(c as char),
+        }
+    }
+}
@@ -0,0 +1,11 @@
+#![deny(missing_debug_implementations)]
+#![warn(rustdoc::missing_crate_level_docs)]
+#![doc = include_str!("../readme.md")]
+
+mod lexer;
+mod parser;
+
+pub use crate::{
+    lexer::{Lexer, Token, TokenKind},
+    parser::{Parser, Precedence},
+};
@@ -0,0 +1,694 @@
+use crate::lexer::{Lexer, Token, TokenKind};
+use kql_ast::{
+    Attribute, AttributeArg, BinaryOpKind, Database, Decl, EnumDecl, Expr, ExprKind, FieldDecl, Ident, ImportDecl, LetDecl,
+    Literal, NamespaceDecl, RecordField, SeedDecl, SeedValue, StructDecl, Type, TypeAliasDecl, TypeArg, TypeKind, UnaryOpKind,
+    VariantDecl,
+};
+use kql_types::{KqlError, Result, Span};
+
+/// Binding power of infix operators, ordered from loosest to tightest.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Precedence {
+    /// Not an infix operator.
+    None,
+    /// `|>` pipeline chaining.
+    Pipeline,
+    /// Logical `||`.
+    Or,
+    /// Logical `&&`.
+    And,
+    /// `==` / `!=`.
+    Equality,
+    /// `<` / `<=` / `>` / `>=`.
+    Comparison,
+    /// `+` / `-`.
+    Term,
+    /// `*` / `/` / `%`.
+    Factor,
+    /// Prefix operators.
+    Unary,
+    /// Member access and calls.
+    Call,
+}
+
+fn get_precedence(kind: &TokenKind) -> Precedence {
+    match kind {
+        TokenKind::PipeGt => Precedence::Pipeline,
+        TokenKind::PipePipe => Precedence::Or,
+        TokenKind::AmpAmp => Precedence::And,
+        TokenKind::EqEq | TokenKind::NotEq => Precedence::Equality,
+        TokenKind::Lt | TokenKind::Le | TokenKind::Gt | TokenKind::Ge => Precedence::Comparison,
+        TokenKind::Plus | TokenKind::Minus => Precedence::Term,
+        TokenKind::Star | TokenKind::Slash | TokenKind::Percent => Precedence::Factor,
+        TokenKind::Dot => Precedence::Call,
+        _ => Precedence::None,
+    }
+}
+
+fn binary_op(kind: &TokenKind) -> Option<BinaryOpKind> {
+    let op = match kind {
+        TokenKind::Plus => BinaryOpKind::Add,
+        TokenKind::Minus => BinaryOpKind::Sub,
+        TokenKind::Star => BinaryOpKind::Mul,
+        TokenKind::Slash => BinaryOpKind::Div,
+        TokenKind::Percent => BinaryOpKind::Mod,
+        TokenKind::EqEq => BinaryOpKind::Eq,
+        TokenKind::NotEq => BinaryOpKind::NotEq,
+        TokenKind::Lt => BinaryOpKind::Lt,
+        TokenKind::Le => BinaryOpKind::Le,
+        TokenKind::Gt => BinaryOpKind::Gt,
+        TokenKind::Ge => BinaryOpKind::Ge,
+        TokenKind::AmpAmp => BinaryOpKind::And,
+        TokenKind::PipePipe => BinaryOpKind::Or,
+        _ => return None,
+    };
+    Some(op)
+}
+
+/// A recursive descent parser over the token stream of a single file.
+#[derive(Debug)]
+pub struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// In recovery mode, syntax errors are collected instead of aborting the
+    /// parse, and partially parsed declarations are kept.
+    recover: bool,
+    errors: Vec<KqlError>,
+}
+
+impl Parser {
+    /// Parse a complete KQL source file into a [Database].
+    pub fn parse(source: &str) -> Result<Database> {
+        let mut parser = Self::new(source);
+        let decls = parser.parse_decls(false)?;
+        Ok(Database { decls })
+    }
+
+    /// Parse like [Self::parse], but never bail: each syntax error is recorded
+    /// as a diagnostic, the parser skips ahead to the next plausible
+    /// declaration, and whatever was already understood is kept. Editor
+    /// features that need an AST mid-edit — completion, symbols, folding —
+    /// call this instead of [Self::parse].
+    pub fn parse_program_recover(source: &str) -> (Database, Vec<KqlError>) {
+        let mut parser = Self::new(source);
+        parser.recover = true;
+        let mut decls = Vec::new();
+        while *parser.peek() != TokenKind::Eof {
+            let before = parser.pos;
+            match parser.parse_decl() {
+                Ok(decl) => decls.push(decl),
+                Err(error) => {
+                    parser.errors.push(error);
+                    parser.synchronize_decl(before);
+                }
+            }
+            while parser.eat(TokenKind::Semicolon) {}
+        }
+        (Database { decls }, parser.errors)
+    }
+
+    fn new(source: &str) -> Self {
+        let mut lexer = Lexer::new(source);
+        let mut tokens = Vec::new();
+        loop {
+            let token = lexer.next_token();
+            let eof = token.kind == TokenKind::Eof;
+            if !token.kind.is_trivia() {
+                tokens.push(token);
+            }
+            if eof {
+                break;
+            }
+        }
+        Self { tokens, pos: 0, recover: false, errors: Vec::new() }
+    }
+
+    /// In recovery mode, record the error and carry on; otherwise fail.
+    fn recover_from(&mut self, error: KqlError) -> Result<()> {
+        if self.recover {
+            self.errors.push(error);
+            Ok(())
+        } else {
+            Err(error)
+        }
+    }
+
+    /// Skip ahead to the next token that can start a top-level declaration.
+    fn synchronize_decl(&mut self, before: usize) {
+        if self.pos == before {
+            self.advance();
+        }
+        loop {
+            match self.peek() {
+                TokenKind::Eof | TokenKind::At => break,
+                TokenKind::Ident(name)
+                    if matches!(name.as_str(), "struct" | "enum" | "type" | "let" | "seed" | "namespace" | "import") =>
+                {
+                    break;
+                }
+                _ => {
+                    self.advance();
+                }
+            }
+        }
+    }
+
+    /// Skip past a malformed struct field, stopping after the separating
+    /// comma or before the closing brace.
+    fn synchronize_field(&mut self) {
+        loop {
+            match self.peek() {
+                TokenKind::Eof | TokenKind::RBrace => break,
+                TokenKind::Comma => {
+                    self.advance();
+                    break;
+                }
+                _ => {
+                    self.advance();
+                }
+            }
+        }
+    }
+
+    fn peek(&self) -> &TokenKind {
+        &self.tokens[self.pos.min(self.tokens.len() - 1)].kind
+    }
+
+    fn peek_nth(&self, n: usize) -> &TokenKind {
+        &self.tokens[(self.pos + n).min(self.tokens.len() - 1)].kind
+    }
+
+    fn peek_span(&self) -> Span {
+        self.tokens[self.pos.min(self.tokens.len() - 1)].span
+    }
+
+    fn prev_end(&self) -> usize {
+        if self.pos == 0 { 0 } else { self.tokens[self.pos - 1].span.end }
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos.min(self.tokens.len() - 1)].clone();
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn eat(&mut self, kind: TokenKind) -> bool {
+        if *self.peek() == kind {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, kind: TokenKind, what: &str) -> Result<Token> {
+        if *self.peek() == kind {
+            Ok(self.advance())
+        } else {
+            Err(KqlError::syntax(format!("expected {}, found {:?}", what, self.peek()), self.peek_span()))
+        }
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), TokenKind::Ident(name) if name == keyword)
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<()> {
+        if self.at_keyword(keyword) {
+            self.advance();
+            Ok(())
+        } else {
+            Err(KqlError::syntax(format!("expected `{}`, found {:?}", keyword, self.peek()), self.peek_span()))
+        }
+    }
+
+    fn parse_ident(&mut self) -> Result<Ident> {
+        match self.peek().clone() {
+            // A quoted identifier carries its unquoted name; it is never
+            // treated as a keyword, so `` `type` `` works as a field name.
+            TokenKind::Ident(name) | TokenKind::QuotedIdent(name) => {
+                let span = self.peek_span();
+                self.advance();
+                Ok(Ident { name, span })
+            }
+            other => Err(KqlError::syntax(format!("expected identifier, found {:?}", other), self.peek_span())),
+        }
+    }
+
+    fn take_docs(&mut self) -> Vec<String> {
+        let mut docs = Vec::new();
+        while let TokenKind::DocComment(text) = self.peek().clone() {
+            docs.push(text);
+            self.advance();
+        }
+        docs
+    }
+
+    fn parse_decls(&mut self, nested: bool) -> Result<Vec<Decl>> {
+        let mut decls = Vec::new();
+        loop {
+            match self.peek() {
+                TokenKind::Eof => break,
+                TokenKind::RBrace if nested => break,
+                _ => {}
+            }
+            decls.push(self.parse_decl()?);
+            while self.eat(TokenKind::Semicolon) {}
+        }
+        Ok(decls)
+    }
+
+    fn parse_decl(&mut self) -> Result<Decl> {
+        let docs = self.take_docs();
+        let attributes = self.parse_attributes()?;
+        let start = self.peek_span().start;
+        if self.at_keyword("struct") {
+            self.advance();
+            Ok(Decl::Struct(self.parse_struct(docs, attributes, start)?))
+        } else if self.at_keyword("enum") {
+            self.advance();
+            Ok(Decl::Enum(self.parse_enum(docs, attributes, start)?))
+        } else if self.at_keyword("type") {
+            self.advance();
+            let name = self.parse_ident()?;
+            self.expect(TokenKind::Assign, "`=`")?;
+            let ty = self.parse_type()?;
+            Ok(Decl::TypeAlias(TypeAliasDecl { name, ty, docs, span: Span::new(start, self.prev_end()) }))
+        } else if self.at_keyword("let") {
+            self.advance();
+            let name = self.parse_ident()?;
+            self.expect(TokenKind::Assign, "`=`")?;
+            let value = self.parse_expression(Precedence::None)?;
+            Ok(Decl::Let(LetDecl { name, value, docs, span: Span::new(start, self.prev_end()) }))
+        } else if self.at_keyword("namespace") {
+            self.advance();
+            let name = self.parse_ident()?;
+            self.expect(TokenKind::LBrace, "`{`")?;
+            let decls = self.parse_decls(true)?;
+            self.expect(TokenKind::RBrace, "`}`")?;
+            Ok(Decl::Namespace(NamespaceDecl { name, decls, span: Span::new(start, self.prev_end()) }))
+        } else if self.at_keyword("seed") {
+            self.advance();
+            Ok(Decl::Seed(self.parse_seed(start)?))
+        } else if self.at_keyword("import") {
+            self.advance();
+            match self.peek().clone() {
+                TokenKind::Str(path) => {
+                    self.advance();
+                    let alias = if self.at_keyword("as") {
+                        self.advance();
+                        Some(self.parse_ident()?)
+                    } else {
+                        None
+                    };
+                    Ok(Decl::Import(ImportDecl { path, alias, span: Span::new(start, self.prev_end()) }))
+                }
+                other => Err(KqlError::syntax(format!("expected import path string, found {:?}", other), self.peek_span())),
+            }
+        } else {
+            Err(KqlError::syntax(format!("expected declaration, found {:?}", self.peek()), self.peek_span()))
+        }
+    }
+
+    fn parse_struct(&mut self, docs: Vec<String>, attributes: Vec<Attribute>, start: usize) -> Result<StructDecl> {
+        let name = self.parse_ident()?;
+        let mut generics = Vec::new();
+        if self.eat(TokenKind::Lt) {
+            while *self.peek() != TokenKind::Gt {
+                generics.push(self.parse_ident()?);
+                if !self.eat(TokenKind::Comma) {
+                    break;
+                }
+            }
+            self.expect(TokenKind::Gt, "`>`")?;
+        }
+        self.expect(TokenKind::LBrace, "`{`")?;
+        let mut fields = Vec::new();
+        while *self.peek() != TokenKind::RBrace {
+            if self.recover && *self.peek() == TokenKind::Eof {
+                break;
+            }
+            match self.parse_field() {
+                Ok(field) => {
+                    fields.push(field);
+                    self.eat(TokenKind::Comma);
+                }
+                Err(error) => {
+                    self.recover_from(error)?;
+                    self.synchronize_field();
+                }
+            }
+        }
+        if let Err(error) = self.expect(TokenKind::RBrace, "`}`") {
+            self.recover_from(error)?;
+        }
+        Ok(StructDecl { name, generics, attributes, fields, docs, span: Span::new(start, self.prev_end()) })
+    }
+
+    fn parse_field(&mut self) -> Result<FieldDecl> {
+        let docs = self.take_docs();
+        let name = self.parse_ident()?;
+        let start = name.span.start;
+        self.expect(TokenKind::Colon, "`:`")?;
+        let ty = self.parse_type()?;
+        let attributes = self.parse_attributes()?;
+        Ok(FieldDecl { name, ty, attributes, docs, span: Span::new(start, self.prev_end()) })
+    }
+
+    fn parse_seed(&mut self, start: usize) -> Result<SeedDecl> {
+        let target = self.parse_ident()?;
+        self.expect(TokenKind::LBrace, "`{`")?;
+        let mut values = Vec::new();
+        while *self.peek() != TokenKind::RBrace {
+            let name = self.parse_ident()?;
+            let vstart = name.span.start;
+            self.expect(TokenKind::Colon, "`:`")?;
+            let value = self.parse_expression(Precedence::None)?;
+            values.push(SeedValue { name, value, span: Span::new(vstart, self.prev_end()) });
+            self.eat(TokenKind::Comma);
+        }
+        self.expect(TokenKind::RBrace, "`}`")?;
+        Ok(SeedDecl { target, values, span: Span::new(start, self.prev_end()) })
+    }
+
+    fn parse_enum(&mut self, docs: Vec<String>, attributes: Vec<Attribute>, start: usize) -> Result<EnumDecl> {
+        let name = self.parse_ident()?;
+        self.expect(TokenKind::LBrace, "`{`")?;
+        let mut variants = Vec::new();
+        // Variants are separated by an optional comma, exactly like struct
+        // fields: newlines are already skipped as trivia, so a missing comma
+        // is fine and only malformed variants report an error.
+        while *self.peek() != TokenKind::RBrace {
+            let docs = self.take_docs();
+            let name = self.parse_ident()?;
+            let vstart = name.span.start;
+            let value = if self.eat(TokenKind::Assign) { Some(self.parse_expression(Precedence::None)?) } else { None };
+            variants.push(VariantDecl { name, value, docs, span: Span::new(vstart, self.prev_end()) });
+            self.eat(TokenKind::Comma);
+        }
+        self.expect(TokenKind::RBrace, "`}`")?;
+        Ok(EnumDecl { name, attributes, variants, docs, span: Span::new(start, self.prev_end()) })
+    }
+
+    fn parse_attributes(&mut self) -> Result<Vec<Attribute>> {
+        let mut attributes = Vec::new();
+        while *self.peek() == TokenKind::At {
+            let start = self.peek_span().start;
+            self.advance();
+            let name = self.parse_ident()?;
+            let mut args = Vec::new();
+            if self.eat(TokenKind::LParen) {
+                while *self.peek() != TokenKind::RParen {
+                    args.push(self.parse_attribute_arg()?);
+                    if !self.eat(TokenKind::Comma) {
+                        break;
+                    }
+                }
+                self.expect(TokenKind::RParen, "`)`")?;
+            }
+            attributes.push(Attribute { name, args, span: Span::new(start, self.prev_end()) });
+        }
+        Ok(attributes)
+    }
+
+    fn parse_attribute_arg(&mut self) -> Result<AttributeArg> {
+        // A named argument is `ident: value`; a lone `ident` is a positional expression.
+        if matches!(self.peek(), TokenKind::Ident(_)) && *self.peek_nth(1) == TokenKind::Colon {
+            let name = self.parse_ident()?;
+            self.advance();
+            let value = self.parse_expression(Precedence::None)?;
+            Ok(AttributeArg { name: Some(name), value })
+        } else {
+            let value = self.parse_expression(Precedence::None)?;
+            Ok(AttributeArg { name: None, value })
+        }
+    }
+
+    fn parse_type(&mut self) -> Result<Type> {
+        let start = self.peek_span().start;
+        let mut ty = match self.peek().clone() {
+            TokenKind::LBracket => {
+                self.advance();
+                let inner = self.parse_type()?;
+                self.expect(TokenKind::RBracket, "`]`")?;
+                Type { kind: TypeKind::List(Box::new(inner)), span: Span::new(start, self.prev_end()) }
+            }
+            TokenKind::LParen => {
+                self.advance();
+                let inner = self.parse_type()?;
+                if *self.peek() == TokenKind::Comma {
+                    let mut items = vec![inner];
+                    while self.eat(TokenKind::Comma) {
+                        if *self.peek() == TokenKind::RParen {
+                            break;
+                        }
+                        items.push(self.parse_type()?);
+                    }
+                    self.expect(TokenKind::RParen, "`)`")?;
+                    Type { kind: TypeKind::Tuple(items), span: Span::new(start, self.prev_end()) }
+                } else {
+                    // `(T)` is just `T` with grouping parentheses.
+                    self.expect(TokenKind::RParen, "`)`")?;
+                    inner
+                }
+            }
+            TokenKind::Ident(_) | TokenKind::QuotedIdent(_) => {
+                let mut path = vec![self.parse_ident()?];
+                while self.eat(TokenKind::ColonColon) {
+                    path.push(self.parse_ident()?);
+                }
+                let mut args = Vec::new();
+                if self.eat(TokenKind::Lt) {
+                    while *self.peek() != TokenKind::Gt {
+                        args.push(self.parse_type_arg()?);
+                        if !self.eat(TokenKind::Comma) {
+                            break;
+                        }
+                    }
+                    self.expect(TokenKind::Gt, "`>`")?;
+                }
+                Type { kind: TypeKind::Named { path, args }, span: Span::new(start, self.prev_end()) }
+            }
+            other => return Err(KqlError::syntax(format!("expected type, found {:?}", other), self.peek_span())),
+        };
+        while self.eat(TokenKind::Question) {
+            ty = Type { kind: TypeKind::Optional(Box::new(ty)), span: Span::new(start, self.prev_end()) };
+        }
+        Ok(ty)
+    }
+
+    fn parse_type_arg(&mut self) -> Result<TypeArg> {
+        if matches!(self.peek(), TokenKind::Ident(_)) && *self.peek_nth(1) == TokenKind::Colon {
+            let name = self.parse_ident()?;
+            self.advance();
+            let ty = self.parse_type()?;
+            Ok(TypeArg::Named { name, ty })
+        } else {
+            Ok(TypeArg::Type(self.parse_type()?))
+        }
+    }
+
+    /// Parse an expression with operators binding at least as tightly as `min`.
+    pub fn parse_expression(&mut self, min: Precedence) -> Result<Expr> {
+        let mut lhs = self.parse_prefix()?;
+        loop {
+            let precedence = get_precedence(self.peek());
+            if precedence == Precedence::None || precedence <= min {
+                break;
+            }
+            if *self.peek() == TokenKind::Dot {
+                self.advance();
+                lhs = self.parse_postfix_member(lhs)?;
+                continue;
+            }
+            // `a |> filter { ... }` is pure sugar for `a.filter { ... }`; the
+            // pipeline form desugars to the same member-call chain.
+            if *self.peek() == TokenKind::PipeGt {
+                self.advance();
+                lhs = self.parse_postfix_member(lhs)?;
+                continue;
+            }
+            let op = match binary_op(self.peek()) {
+                Some(op) => op,
+                None => break,
+            };
+            self.advance();
+            let rhs = self.parse_expression(precedence)?;
+            let span = lhs.span.merge(&rhs.span);
+            lhs = Expr { kind: ExprKind::Binary { op, lhs: Box::new(lhs), rhs: Box::new(rhs) }, span };
+        }
+        Ok(lhs)
+    }
+
+    fn parse_postfix_member(&mut self, base: Expr) -> Result<Expr> {
+        if *self.peek() == TokenKind::LBrace {
+            return self.parse_record_projection(base);
+        }
+        let name = self.parse_ident()?;
+        let start = base.span.start;
+        if self.eat(TokenKind::LParen) {
+            let mut args = Vec::new();
+            while *self.peek() != TokenKind::RParen {
+                args.push(self.parse_expression(Precedence::None)?);
+                if !self.eat(TokenKind::Comma) {
+                    break;
+                }
+            }
+            self.expect(TokenKind::RParen, "`)`")?;
+            let closure = self.parse_optional_closure()?;
+            let span = Span::new(start, self.prev_end());
+            Ok(Expr { kind: ExprKind::MethodCall { base: Box::new(base), method: name, args, closure }, span })
+        } else if let Some(closure) = self.parse_optional_closure()? {
+            let span = Span::new(start, self.prev_end());
+            Ok(Expr {
+                kind: ExprKind::MethodCall { base: Box::new(base), method: name, args: Vec::new(), closure: Some(closure) },
+                span,
+            })
+        } else {
+            let span = Span::new(start, self.prev_end());
+            Ok(Expr { kind: ExprKind::Member { base: Box::new(base), name }, span })
+        }
+    }
+
+    /// Parse the renamed-field projection `$.{ alias: expr, ... }`; the base
+    /// before the dot must be the `$` row variable.
+    fn parse_record_projection(&mut self, base: Expr) -> Result<Expr> {
+        if base.kind != ExprKind::Context {
+            return Err(KqlError::syntax("`.{ ... }` projections are only valid on `$`", base.span));
+        }
+        let start = base.span.start;
+        self.expect(TokenKind::LBrace, "`{`")?;
+        let mut fields = Vec::new();
+        while *self.peek() != TokenKind::RBrace {
+            let name = self.parse_ident()?;
+            self.expect(TokenKind::Colon, "`:`")?;
+            let value = self.parse_expression(Precedence::None)?;
+            fields.push(RecordField { name, value });
+            if !self.eat(TokenKind::Comma) {
+                break;
+            }
+        }
+        self.expect(TokenKind::RBrace, "`}`")?;
+        Ok(Expr { kind: ExprKind::Record(fields), span: Span::new(start, self.prev_end()) })
+    }
+
+    fn parse_optional_closure(&mut self) -> Result<Option<Box<Expr>>> {
+        if self.eat(TokenKind::LBrace) {
+            let body = self.parse_expression(Precedence::None)?;
+            self.expect(TokenKind::RBrace, "`}`")?;
+            Ok(Some(Box::new(body)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn parse_prefix(&mut self) -> Result<Expr> {
+        let span = self.peek_span();
+        match self.peek().clone() {
+            TokenKind::Int(value, suffix) => {
+                self.advance();
+                Ok(Expr { kind: ExprKind::Literal(Literal::Int(value, suffix)), span })
+            }
+            TokenKind::Float(value, suffix) => {
+                self.advance();
+                Ok(Expr { kind: ExprKind::Literal(Literal::Float(value, suffix)), span })
+            }
+            TokenKind::Str(value) => {
+                self.advance();
+                Ok(Expr { kind: ExprKind::Literal(Literal::String(value)), span })
+            }
+            TokenKind::Star => {
+                self.advance();
+                Ok(Expr { kind: ExprKind::Literal(Literal::Star), span })
+            }
+            TokenKind::Dollar => {
+                self.advance();
+                Ok(Expr { kind: ExprKind::Context, span })
+            }
+            TokenKind::Minus => {
+                self.advance();
+                let operand = self.parse_expression(Precedence::Unary)?;
+                let span = span.merge(&operand.span);
+                Ok(Expr { kind: ExprKind::Unary { op: UnaryOpKind::Neg, expr: Box::new(operand) }, span })
+            }
+            TokenKind::Bang => {
+                self.advance();
+                let operand = self.parse_expression(Precedence::Unary)?;
+                let span = span.merge(&operand.span);
+                Ok(Expr { kind: ExprKind::Unary { op: UnaryOpKind::Not, expr: Box::new(operand) }, span })
+            }
+            TokenKind::LParen => {
+                self.advance();
+                let inner = self.parse_expression(Precedence::None)?;
+                if *self.peek() == TokenKind::Comma {
+                    let mut items = vec![inner];
+                    while self.eat(TokenKind::Comma) {
+                        if *self.peek() == TokenKind::RParen {
+                            break;
+                        }
+                        items.push(self.parse_expression(Precedence::None)?);
+                    }
+                    self.expect(TokenKind::RParen, "`)`")?;
+                    Ok(Expr { kind: ExprKind::Tuple(items), span: Span::new(span.start, self.prev_end()) })
+                } else {
+                    self.expect(TokenKind::RParen, "`)`")?;
+                    Ok(inner)
+                }
+            }
+            TokenKind::LBracket => {
+                self.advance();
+                let mut items = Vec::new();
+                while *self.peek() != TokenKind::RBracket {
+                    items.push(self.parse_expression(Precedence::None)?);
+                    if !self.eat(TokenKind::Comma) {
+                        break;
+                    }
+                }
+                self.expect(TokenKind::RBracket, "`]`")?;
+                Ok(Expr { kind: ExprKind::List(items), span: Span::new(span.start, self.prev_end()) })
+            }
+            TokenKind::Ident(name) if name == "if" => {
+                self.advance();
+                let cond = self.parse_expression(Precedence::None)?;
+                self.expect_keyword("then")?;
+                let then_branch = self.parse_expression(Precedence::None)?;
+                self.expect_keyword("else")?;
+                let else_branch = self.parse_expression(Precedence::None)?;
+                let span = span.merge(&else_branch.span);
+                Ok(Expr {
+                    kind: ExprKind::If {
+                        cond: Box::new(cond),
+                        then_branch: Box::new(then_branch),
+                        else_branch: Box::new(else_branch),
+                    },
+                    span,
+                })
+            }
+            TokenKind::Ident(name) if name == "true" || name == "false" => {
+                self.advance();
+                Ok(Expr { kind: ExprKind::Literal(Literal::Bool(name == "true")), span })
+            }
+            TokenKind::Ident(_) => {
+                let ident = self.parse_ident()?;
+                if self.eat(TokenKind::LParen) {
+                    let mut args = Vec::new();
+                    while *self.peek() != TokenKind::RParen {
+                        args.push(self.parse_expression(Precedence::None)?);
+                        if !self.eat(TokenKind::Comma) {
+                            break;
+                        }
+                    }
+                    self.expect(TokenKind::RParen, "`)`")?;
+                    let span = Span::new(ident.span.start, self.prev_end());
+                    Ok(Expr { kind: ExprKind::Call { func: ident, args }, span })
+                } else {
+                    let span = ident.span;
+                    Ok(Expr { kind: ExprKind::Variable(ident), span })
+                }
+            }
+            other => Err(KqlError::syntax(format!("expected expression, found {:?}", other), span)),
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use kql_ast::Decl;
+use kql_parser::Parser;
+
+#[test]
+fn ready() {
+    println!("it works!")
+}
+
+#[test]
+fn parses_struct_and_enum() {
+    let source = r#"
+/// A person.
+@table("users")
+struct User {
+    id: Key<User, i64>,
+    role: Role,
+}
+
+enum Role {
+    Admin,
+    Member,
+}
+"#;
+    let database = Parser::parse(source).unwrap();
+    assert_eq!(database.decls.len(), 2);
+    let Decl::Struct(user) = &database.decls[0] else {
+        panic!("expected struct");
+    };
+    assert_eq!(user.name.name, "User");
+    assert_eq!(user.docs, vec!["A person.".to_string()]);
+    assert_eq!(user.attributes[0].name.name, "table");
+}
+
+#[test]
+fn parses_query_chain() {
+    let source = "let adults = User.filter { $.age >= 18 }.map { $.name }";
+    let database = Parser::parse(source).unwrap();
+    let Decl::Let(decl) = &database.decls[0] else {
+        panic!("expected let");
+    };
+    assert_eq!(decl.name.name, "adults");
+}
+
+#[test]
+fn parses_tuple_types() {
+    let database = Parser::parse("struct Point { coords: (i32, i32), label: (String) }").unwrap();
+    let Decl::Struct(point) = &database.decls[0] else {
+        panic!("expected struct");
+    };
+    let kql_ast::TypeKind::Tuple(items) = &point.fields[0].ty.kind else {
+        panic!("expected tuple type, got {:?}", point.fields[0].ty.kind);
+    };
+    assert_eq!(items.len(), 2);
+    // A parenthesized single type is grouping, not a one-element tuple.
+    assert!(matches!(&point.fields[1].ty.kind, kql_ast::TypeKind::Named { .. }));
+}
+
+#[test]
+fn parses_variants_with_and_without_commas() {
+    let comma_free = r#"
+enum Role {
+    Admin
+    Member
+    Guest
+}
+"#;
+    let database = Parser::parse(comma_free).unwrap();
+    let Decl::Enum(role) = &database.decls[0] else {
+        panic!("expected enum");
+    };
+    assert_eq!(role.variants.len(), 3);
+
+    let with_commas = "enum Role { Admin, Member, Guest, }";
+    let database = Parser::parse(with_commas).unwrap();
+    let Decl::Enum(role) = &database.decls[0] else {
+        panic!("expected enum");
+    };
+    assert_eq!(role.variants.len(), 3);
+}
+
+#[test]
+fn reports_syntax_errors_with_spans() {
+    let error = Parser::parse("struct {").unwrap_err();
+    assert!(error.span().is_some());
+}
+
+#[test]
+fn tokenizes_with_trivia_and_spans() {
+    use kql_parser::{Lexer, TokenKind};
+    let source = "let x = 1 // one\n";
+    let tokens = Lexer::tokenize_all(source);
+    let kinds: Vec<&TokenKind> = tokens.iter().map(|t| &t.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            &TokenKind::Ident("let".to_string()),
+            &TokenKind::Whitespace,
+            &TokenKind::Ident("x".to_string()),
+            &TokenKind::Whitespace,
+            &TokenKind::Assign,
+            &TokenKind::Whitespace,
+            &TokenKind::Int(1, None),
+            &TokenKind::Whitespace,
+            &TokenKind::LineComment("one".to_string()),
+            &TokenKind::Whitespace,
+        ]
+    );
+    // Spans tile the source exactly.
+    assert_eq!(tokens[0].span.start, 0);
+    assert_eq!(tokens.last().unwrap().span.end, source.len());
+    for pair in tokens.windows(2) {
+        assert_eq!(pair[0].span.end, pair[1].span.start);
+    }
+}
+
+#[test]
+fn logical_operators_bind_looser_than_comparisons() {
+    use kql_ast::{BinaryOpKind, ExprKind};
+    let database = Parser::parse("let both = User.filter { $.a == 1 && $.b == 2 || $.c > 3 }").unwrap();
+    let Decl::Let(decl) = &database.decls[0] else {
+        panic!("expected let");
+    };
+    let ExprKind::MethodCall { closure: Some(body), .. } = &decl.value.kind else {
+        panic!("expected filter call");
+    };
+    // `||` is the loosest, then `&&`; the comparisons group underneath.
+    let ExprKind::Binary { op: BinaryOpKind::Or, lhs, rhs } = &body.kind else {
+        panic!("expected `||` at the top: {:?}", body.kind);
+    };
+    let ExprKind::Binary { op: BinaryOpKind::And, lhs: first, rhs: second, .. } = &lhs.kind else {
+        panic!("expected `&&` under `||`: {:?}", lhs.kind);
+    };
+    assert!(matches!(first.kind, ExprKind::Binary { op: BinaryOpKind::Eq, .. }));
+    assert!(matches!(second.kind, ExprKind::Binary { op: BinaryOpKind::Eq, .. }));
+    assert!(matches!(rhs.kind, ExprKind::Binary { op: BinaryOpKind::Gt, .. }));
+}
+
+#[test]
+fn parses_prefix_negation_and_angle_not_equal() {
+    use kql_ast::{BinaryOpKind, ExprKind, UnaryOpKind};
+    let database = Parser::parse("let stale = User.filter { !$.active }").unwrap();
+    let Decl::Let(decl) = &database.decls[0] else {
+        panic!("expected let");
+    };
+    let ExprKind::MethodCall { closure: Some(body), .. } = &decl.value.kind else {
+        panic!("expected filter call");
+    };
+    assert!(matches!(body.kind, ExprKind::Unary { op: UnaryOpKind::Not, .. }), "{:?}", body.kind);
+
+    // `<>` is the SQL spelling of `!=`; both lex to the same token.
+    let database = Parser::parse("let other = User.filter { $.a <> $.b }").unwrap();
+    let Decl::Let(decl) = &database.decls[0] else {
+        panic!("expected let");
+    };
+    let ExprKind::MethodCall { closure: Some(body), .. } = &decl.value.kind else {
+        panic!("expected filter call");
+    };
+    assert!(matches!(body.kind, ExprKind::Binary { op: BinaryOpKind::NotEq, .. }), "{:?}", body.kind);
+}
+
+#[test]
+fn lexes_numeric_literal_suffixes() {
+    use kql_parser::{Lexer, TokenKind};
+    for suffix in ["i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64"] {
+        let tokens = Lexer::tokenize_all(&format!("10{suffix}"));
+        assert_eq!(tokens[0].kind, TokenKind::Int(10, Some(suffix.to_string())), "{suffix}");
+        assert_eq!(tokens.len(), 1, "{suffix}");
+    }
+    for suffix in ["f32", "f64", "d128"] {
+        let tokens = Lexer::tokenize_all(&format!("2.5{suffix}"));
+        assert_eq!(tokens[0].kind, TokenKind::Float(2.5, Some(suffix.to_string())), "{suffix}");
+        assert_eq!(tokens.len(), 1, "{suffix}");
+    }
+    // An unsuffixed literal still lexes as before.
+    assert_eq!(Lexer::tokenize_all("10")[0].kind, TokenKind::Int(10, None));
+}
+
+#[test]
+fn parses_conditional_expressions() {
+    use kql_ast::{BinaryOpKind, ExprKind, Literal};
+    let source = "struct User { age: i64 }\nlet flagged = User.filter { (if $.age >= 18 then 1 else 0) == 1 }";
+    let db = Parser::parse(source).unwrap();
+    let Decl::Let(item) = &db.decls[1] else { panic!("expected a let") };
+    let ExprKind::MethodCall { closure: Some(closure), .. } = &item.value.kind else { panic!("expected a filter call") };
+    let ExprKind::Binary { lhs, .. } = &closure.kind else { panic!("expected a comparison") };
+    let ExprKind::If { cond, then_branch, else_branch } = &lhs.kind else { panic!("expected an if, got {:?}", lhs.kind) };
+    assert!(matches!(cond.kind, ExprKind::Binary { op: BinaryOpKind::Ge, .. }));
+    assert!(matches!(then_branch.kind, ExprKind::Literal(Literal::Int(1, None))));
+    assert!(matches!(else_branch.kind, ExprKind::Literal(Literal::Int(0, None))));
+}
+
+#[test]
+fn parses_empty_and_trivial_sources() {
+    for source in ["", "   \n\t\n", "// just a comment\n", "/* block\ncomment */\n"] {
+        let db = Parser::parse(source).unwrap();
+        assert!(db.decls.is_empty(), "{source:?} produced {:?}", db.decls);
+    }
+}
+
+#[test]
+fn parses_backtick_quoted_identifiers() {
+    let source = "struct Item {\n    id: Key<Item, i64>,\n    `order`: i32,\n}\n";
+    let db = Parser::parse(source).unwrap();
+    let Decl::Struct(item) = &db.decls[0] else { panic!("expected a struct") };
+    assert_eq!(item.fields[1].name.name, "order");
+}
+
+#[test]
+fn visitor_walks_every_expression() {
+    use kql_ast::visit::{Visitor, walk_expr};
+    use kql_ast::{Expr, ExprKind};
+
+    struct VariableCounter(usize);
+    impl Visitor for VariableCounter {
+        fn visit_expr(&mut self, expr: &Expr) {
+            if matches!(expr.kind, ExprKind::Variable(_)) {
+                self.0 += 1;
+            }
+            walk_expr(self, expr);
+        }
+    }
+
+    let source = "let adults = User.filter { $.age >= limit && $.active == yes }\n";
+    let db = Parser::parse(source).unwrap();
+    let mut counter = VariableCounter(0);
+    counter.visit_database(&db);
+    // `User`, `limit` and `yes`.
+    assert_eq!(counter.0, 3);
+}
+
+#[test]
+fn recovering_parse_keeps_partial_declarations() {
+    let (db, errors) = Parser::parse_program_recover("struct User {\n    id: i64,\n");
+    assert_eq!(db.decls.len(), 1, "{db:?}");
+    let kql_ast::Decl::Struct(user) = &db.decls[0] else { panic!("{:?}", db.decls[0]) };
+    assert_eq!(user.name.name, "User");
+    assert_eq!(user.fields.len(), 1);
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert!(errors[0].message().contains("expected `}`"), "{errors:?}");
+    // A broken declaration does not take the following ones down with it.
+    let (db, errors) = Parser::parse_program_recover("struct { }\n\nstruct Post { id: i64 }\n");
+    assert_eq!(db.decls.len(), 1, "{db:?}");
+    assert!(!errors.is_empty());
+}
+
+#[test]
+fn line_index_locates_tokens_without_rescanning() {
+    use kql_parser::{Lexer, TokenKind};
+    use kql_types::LineIndex;
+    let source = "struct User {\n    id: i64,\n    name: String,\n}\n";
+    let index = LineIndex::new(source);
+    let tokens = Lexer::tokenize_all(source);
+    let find = |text: &str| tokens.iter().find(|t| matches!(&t.kind, TokenKind::Ident(n) if n == text)).unwrap();
+    // `name` and `String` sit on the third line (zero-based line 2).
+    assert_eq!(index.line_col(find("name").span.start), (2, 4));
+    assert_eq!(index.line_col(find("String").span.start), (2, 10));
+    assert_eq!(index.line_col(find("id").span.start), (1, 4));
+    // An offset past the end clamps to the last position.
+    assert_eq!(index.line_col(source.len()), (4, 0));
+}
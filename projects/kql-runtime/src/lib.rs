@@ -0,0 +1,109 @@
+#![deny(missing_debug_implementations)]
+#![warn(rustdoc::missing_crate_level_docs)]
+#![doc = include_str!("../readme.md")]
+
+mod query;
+
+pub mod prelude;
+
+pub use crate::query::{QueryBuilder, SqlValue};
+use sqlx::{FromRow, SqlitePool, sqlite::SqliteRow};
+use std::marker::PhantomData;
+
+/// The SQL backend a pool talks to.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum KqlDialect {
+    /// PostgreSQL.
+    Postgres,
+    /// MySQL 8+.
+    MySql,
+    /// SQLite 3.
+    #[default]
+    Sqlite,
+}
+
+/// A connection pool tagged with the dialect it speaks.
+#[derive(Debug, Clone)]
+pub struct KqlPool {
+    pool: SqlitePool,
+    dialect: KqlDialect,
+}
+
+impl KqlPool {
+    /// Connect to a database URL. Only SQLite URLs are supported for now.
+    pub async fn connect(url: &str) -> sqlx::Result<Self> {
+        let pool = SqlitePool::connect(url).await?;
+        Ok(Self { pool, dialect: KqlDialect::Sqlite })
+    }
+
+    /// The dialect this pool speaks.
+    pub fn dialect(&self) -> KqlDialect {
+        self.dialect
+    }
+
+    /// The underlying sqlx pool.
+    pub fn inner(&self) -> &SqlitePool {
+        &self.pool
+    }
+}
+
+/// Implemented by generated row types so the repository and query builder
+/// know which table and columns they map to.
+pub trait KqlEntity: for<'r> FromRow<'r, SqliteRow> + Send + Unpin {
+    /// The physical table name.
+    const TABLE: &'static str;
+    /// The column names, in declaration order.
+    const COLUMNS: &'static [&'static str];
+    /// The primary key column name.
+    const PRIMARY_KEY: &'static str;
+    /// Singular relations that can be eager-loaded with [QueryBuilder::with].
+    const RELATIONS: &'static [KqlRelation] = &[];
+}
+
+/// Metadata for a singular relation declared on an entity, mirroring the
+/// compiler's MIR `Relation`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KqlRelation {
+    /// The relation name, as written in the schema.
+    pub name: &'static str,
+    /// The foreign key column on the owning table.
+    pub fk_column: &'static str,
+    /// The physical name of the related table.
+    pub to_table: &'static str,
+    /// The primary key column of the related table.
+    pub to_primary_key: &'static str,
+}
+
+/// A typed repository over one entity's table.
+#[derive(Debug)]
+pub struct KqlRepository<T> {
+    pool: KqlPool,
+    _marker: PhantomData<T>,
+}
+
+impl<T: KqlEntity> KqlRepository<T> {
+    /// Create a repository over a pool.
+    pub fn new(pool: KqlPool) -> Self {
+        Self { pool, _marker: PhantomData }
+    }
+
+    /// Start a query over the entity's table.
+    pub fn query(&self) -> QueryBuilder<T> {
+        QueryBuilder::new()
+    }
+
+    /// Fetch every row of the table.
+    pub async fn all(&self) -> sqlx::Result<Vec<T>> {
+        self.query().all(&self.pool).await
+    }
+
+    /// Fetch one row by primary key.
+    pub async fn find_by_id(&self, id: impl Into<SqlValue>) -> sqlx::Result<Option<T>> {
+        self.query().filter(T::PRIMARY_KEY, "=", id).first(&self.pool).await
+    }
+
+    /// The pool this repository executes on.
+    pub fn pool(&self) -> &KqlPool {
+        &self.pool
+    }
+}
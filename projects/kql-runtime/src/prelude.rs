@@ -0,0 +1,5 @@
+//! Everything needed to use generated entity code in one import:
+//! `use kql_runtime::prelude::*;`.
+
+pub use crate::{KqlDialect, KqlEntity, KqlPool, KqlRelation, KqlRepository, QueryBuilder, SqlValue};
+pub use sqlx::{FromRow, Row};
@@ -0,0 +1,380 @@
+use crate::{KqlEntity, KqlPool, KqlRelation};
+use sqlx::{FromRow, Row, sqlite::SqliteArguments, sqlite::SqliteRow};
+use std::{collections::HashMap, marker::PhantomData};
+
+/// A dynamically typed bind parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+    /// An integer value.
+    Int(i64),
+    /// A floating point value.
+    Float(f64),
+    /// A text value.
+    Text(String),
+    /// A boolean value.
+    Bool(bool),
+    /// NULL.
+    Null,
+}
+
+impl From<i64> for SqlValue {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+impl From<i32> for SqlValue {
+    fn from(value: i32) -> Self {
+        Self::Int(value as i64)
+    }
+}
+impl From<f64> for SqlValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+impl From<&str> for SqlValue {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_string())
+    }
+}
+impl From<String> for SqlValue {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+impl From<bool> for SqlValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+/// A small, chainable query builder over one entity's table.
+#[derive(Debug)]
+pub struct QueryBuilder<T> {
+    table: &'static str,
+    select: Vec<String>,
+    conditions: Vec<(String, SqlValue)>,
+    group_by: Vec<String>,
+    order_by: Vec<(String, bool)>,
+    after: Vec<SqlValue>,
+    with: Vec<String>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: KqlEntity> Default for QueryBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: KqlEntity> QueryBuilder<T> {
+    /// Create a builder over the entity's table.
+    pub fn new() -> Self {
+        Self {
+            table: T::TABLE,
+            select: Vec::new(),
+            conditions: Vec::new(),
+            group_by: Vec::new(),
+            order_by: Vec::new(),
+            after: Vec::new(),
+            with: Vec::new(),
+            limit: None,
+            offset: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Add a `column <op> ?` condition, AND-ed with the existing ones.
+    pub fn filter(mut self, column: &str, op: &str, value: impl Into<SqlValue>) -> Self {
+        self.conditions.push((format!("{} {} ?", column, op), value.into()));
+        self
+    }
+
+    /// Replace the `*` projection with an explicit select expression. Each
+    /// call appends one, so `select_raw("status")` then
+    /// `select_raw("COUNT(*) AS n")` projects both. The expression is rendered
+    /// verbatim; pair a reshaped projection with [Self::all_as].
+    pub fn select_raw(mut self, expr: &str) -> Self {
+        self.select.push(expr.to_string());
+        self
+    }
+
+    /// Add GROUP BY keys.
+    pub fn group_by(mut self, columns: &[&str]) -> Self {
+        self.group_by.extend(columns.iter().map(|c| c.to_string()));
+        self
+    }
+
+    /// Add an ORDER BY key.
+    pub fn order_by(mut self, column: &str, desc: bool) -> Self {
+        self.order_by.push((column.to_string(), desc));
+        self
+    }
+
+    /// Keyset pagination: only return rows strictly after `cursor`, which
+    /// holds one value per `order_by` key, in the same order. Ignored until
+    /// at least one `order_by` key is set.
+    pub fn after(mut self, cursor: Vec<SqlValue>) -> Self {
+        self.after = cursor;
+        self
+    }
+
+    /// Set the LIMIT.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Set the OFFSET.
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Record a relation declared in [KqlEntity::RELATIONS] to eager-load.
+    /// Unknown names are ignored, matching the compiler's `generate_select`.
+    pub fn with(mut self, relation: &str) -> Self {
+        self.with.push(relation.to_string());
+        self
+    }
+
+    /// The recorded relations that resolve against [KqlEntity::RELATIONS].
+    fn resolved_relations(&self) -> Vec<&'static KqlRelation> {
+        self.with.iter().filter_map(|name| T::RELATIONS.iter().find(|r| r.name == *name)).collect()
+    }
+
+    /// Render the SELECT this builder describes. Eager-loaded relations join
+    /// the same way `generate_select` renders them in compiled queries.
+    pub fn build_sql(&self) -> String {
+        self.render(self.limit)
+    }
+
+    /// The SELECT [Self::first] executes: the same statement as
+    /// [Self::build_sql] with the limit forced to a single row.
+    pub fn first_sql(&self) -> String {
+        self.render(Some(1))
+    }
+
+    fn render(&self, limit: Option<u64>) -> String {
+        let relations = self.resolved_relations();
+        let mut sql = if !self.select.is_empty() {
+            format!("SELECT {} FROM {}", self.select.join(", "), self.table)
+        } else if relations.is_empty() {
+            format!("SELECT * FROM {}", self.table)
+        } else {
+            format!("SELECT {}.* FROM {}", self.table, self.table)
+        };
+        for relation in relations {
+            sql.push_str(&format!(
+                " LEFT OUTER JOIN {} ON {}.{} = {}.{}",
+                relation.to_table, self.table, relation.fk_column, relation.to_table, relation.to_primary_key
+            ));
+        }
+        let mut predicates: Vec<String> = self.conditions.iter().map(|(c, _)| c.clone()).collect();
+        if let Some(predicate) = self.keyset_predicate() {
+            predicates.push(predicate);
+        }
+        if !predicates.is_empty() {
+            sql.push_str(&format!(" WHERE {}", predicates.join(" AND ")));
+        }
+        if !self.group_by.is_empty() {
+            sql.push_str(&format!(" GROUP BY {}", self.group_by.join(", ")));
+        }
+        if !self.order_by.is_empty() {
+            let keys = self
+                .order_by
+                .iter()
+                .map(|(column, desc)| if *desc { format!("{} DESC", column) } else { column.clone() })
+                .collect::<Vec<_>>()
+                .join(", ");
+            sql.push_str(&format!(" ORDER BY {}", keys));
+        }
+        if let Some(limit) = limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+        if let Some(offset) = self.offset {
+            sql.push_str(&format!(" OFFSET {}", offset));
+        }
+        sql
+    }
+
+    /// The cursor predicate, when one applies: a row-value comparison when
+    /// every key sorts the same way (`>` ascending, `<` descending), or the
+    /// expanded lexicographic form when the directions are mixed, since a
+    /// single row-value comparison cannot express that ordering.
+    fn keyset_predicate(&self) -> Option<String> {
+        if self.after.is_empty() || self.order_by.is_empty() {
+            return None;
+        }
+        if let Some(op) = self.uniform_keyset_op() {
+            let columns: Vec<&str> = self.order_by.iter().map(|(column, _)| column.as_str()).collect();
+            return Some(match columns.as_slice() {
+                [single] => format!("{} {} ?", single, op),
+                _ => format!("({}) {} ({})", columns.join(", "), op, vec!["?"; columns.len()].join(", ")),
+            });
+        }
+        // `a ASC, b DESC` expands to `a > ? OR (a = ? AND b < ?)`.
+        let disjuncts: Vec<String> = (0..self.order_by.len())
+            .map(|i| {
+                let mut parts: Vec<String> = self.order_by[..i].iter().map(|(column, _)| format!("{} = ?", column)).collect();
+                let (column, desc) = &self.order_by[i];
+                parts.push(format!("{} {} ?", column, if *desc { "<" } else { ">" }));
+                format!("({})", parts.join(" AND "))
+            })
+            .collect();
+        Some(format!("({})", disjuncts.join(" OR ")))
+    }
+
+    /// The shared comparator when every `order_by` key sorts the same way,
+    /// or `None` for mixed directions.
+    fn uniform_keyset_op(&self) -> Option<&'static str> {
+        if self.order_by.iter().all(|(_, desc)| !desc) {
+            Some(">")
+        } else if self.order_by.iter().all(|(_, desc)| *desc) {
+            Some("<")
+        } else {
+            None
+        }
+    }
+
+    /// The cursor values in placeholder order: once each for the row-value
+    /// forms, and once per disjunct prefix for the expanded mixed-direction
+    /// form.
+    fn keyset_bind_values(&self) -> Vec<&SqlValue> {
+        if self.order_by.is_empty() || self.uniform_keyset_op().is_some() {
+            return self.after.iter().collect();
+        }
+        let mut values = Vec::new();
+        for i in 0..self.order_by.len() {
+            if let Some(prefix) = self.after.get(..=i) {
+                values.extend(prefix);
+            }
+        }
+        values
+    }
+
+    /// Execute and fetch all matching rows.
+    pub async fn all(&self, pool: &KqlPool) -> sqlx::Result<Vec<T>> {
+        let sql = self.build_sql();
+        let mut query = sqlx::query_as::<_, T>(&sql);
+        for (_, value) in &self.conditions {
+            query = bind_value(query, value);
+        }
+        for value in self.keyset_bind_values() {
+            query = bind_value(query, value);
+        }
+        query.fetch_all(pool.inner()).await
+    }
+
+    /// Execute and fetch all rows into `R` instead of the entity type, for
+    /// projections built with [Self::select_raw] whose shape no longer
+    /// matches the entity — grouped aggregates, in particular.
+    pub async fn all_as<R>(&self, pool: &KqlPool) -> sqlx::Result<Vec<R>>
+    where
+        R: for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+    {
+        let sql = self.build_sql();
+        let mut query = sqlx::query_as::<_, R>(&sql);
+        for (_, value) in &self.conditions {
+            query = bind_value(query, value);
+        }
+        for value in self.keyset_bind_values() {
+            query = bind_value(query, value);
+        }
+        query.fetch_all(pool.inner()).await
+    }
+
+    /// Execute and fetch the first row, if any, applying the accumulated
+    /// filters, ordering, and offset.
+    pub async fn first(&self, pool: &KqlPool) -> sqlx::Result<Option<T>> {
+        let sql = self.first_sql();
+        let mut query = sqlx::query_as::<_, T>(&sql);
+        for (_, value) in &self.conditions {
+            query = bind_value(query, value);
+        }
+        for value in self.keyset_bind_values() {
+            query = bind_value(query, value);
+        }
+        query.fetch_optional(pool.inner()).await
+    }
+
+    /// Execute and fetch all matching rows, hydrating the first relation
+    /// recorded with [Self::with] into `R`. Related rows are fetched in one
+    /// batched lookup keyed by integer primary key; a NULL or dangling
+    /// foreign key hydrates as `None`.
+    pub async fn all_with<R: KqlEntity>(&self, pool: &KqlPool) -> sqlx::Result<Vec<(T, Option<R>)>> {
+        let relation = self.resolved_relations().into_iter().next();
+        let sql = self.build_sql();
+        let mut query = sqlx::query(&sql);
+        for (_, value) in &self.conditions {
+            query = bind_raw(query, value);
+        }
+        for value in self.keyset_bind_values() {
+            query = bind_raw(query, value);
+        }
+        let mut parents = Vec::new();
+        for row in query.fetch_all(pool.inner()).await? {
+            let fk = match relation {
+                Some(relation) => row.try_get::<Option<i64>, _>(relation.fk_column)?,
+                None => None,
+            };
+            parents.push((T::from_row(&row)?, fk));
+        }
+        let Some(relation) = relation else {
+            return Ok(parents.into_iter().map(|(parent, _)| (parent, None)).collect());
+        };
+        let mut keys: Vec<i64> = parents.iter().filter_map(|(_, fk)| *fk).collect();
+        keys.sort_unstable();
+        keys.dedup();
+        let mut related: HashMap<i64, SqliteRow> = HashMap::new();
+        if !keys.is_empty() {
+            let placeholders = vec!["?"; keys.len()].join(", ");
+            let sql = format!("SELECT * FROM {} WHERE {} IN ({})", relation.to_table, relation.to_primary_key, placeholders);
+            let mut query = sqlx::query(&sql);
+            for key in &keys {
+                query = query.bind(*key);
+            }
+            for row in query.fetch_all(pool.inner()).await? {
+                related.insert(row.try_get(relation.to_primary_key)?, row);
+            }
+        }
+        let mut out = Vec::with_capacity(parents.len());
+        for (parent, fk) in parents {
+            // Rows stay raw so one related row can hydrate many parents.
+            let row = match fk.and_then(|key| related.get(&key)) {
+                Some(row) => Some(R::from_row(row)?),
+                None => None,
+            };
+            out.push((parent, row));
+        }
+        Ok(out)
+    }
+}
+
+fn bind_raw<'q>(
+    query: sqlx::query::Query<'q, sqlx::Sqlite, SqliteArguments<'q>>,
+    value: &SqlValue,
+) -> sqlx::query::Query<'q, sqlx::Sqlite, SqliteArguments<'q>> {
+    match value {
+        SqlValue::Int(v) => query.bind(*v),
+        SqlValue::Float(v) => query.bind(*v),
+        SqlValue::Text(v) => query.bind(v.clone()),
+        SqlValue::Bool(v) => query.bind(*v),
+        SqlValue::Null => query.bind(Option::<i64>::None),
+    }
+}
+
+fn bind_value<'q, T>(
+    query: sqlx::query::QueryAs<'q, sqlx::Sqlite, T, SqliteArguments<'q>>,
+    value: &SqlValue,
+) -> sqlx::query::QueryAs<'q, sqlx::Sqlite, T, SqliteArguments<'q>> {
+    match value {
+        SqlValue::Int(v) => query.bind(*v),
+        SqlValue::Float(v) => query.bind(*v),
+        SqlValue::Text(v) => query.bind(v.clone()),
+        SqlValue::Bool(v) => query.bind(*v),
+        SqlValue::Null => query.bind(Option::<i64>::None),
+    }
+}
@@ -0,0 +1,185 @@
+use kql_runtime::{KqlEntity, KqlRelation, QueryBuilder};
+use sqlx::FromRow;
+
+#[test]
+fn ready() {
+    println!("it works!")
+}
+
+#[derive(Debug, FromRow)]
+struct User {
+    #[allow(dead_code)]
+    id: i64,
+    #[allow(dead_code)]
+    name: String,
+}
+
+impl KqlEntity for User {
+    const TABLE: &'static str = "users";
+    const COLUMNS: &'static [&'static str] = &["id", "name"];
+    const PRIMARY_KEY: &'static str = "id";
+}
+
+#[test]
+fn builds_plain_select() {
+    let sql = QueryBuilder::<User>::new().build_sql();
+    assert_eq!(sql, "SELECT * FROM users");
+}
+
+#[test]
+fn builds_filtered_sorted_select() {
+    let sql = QueryBuilder::<User>::new().filter("id", ">=", 10i64).order_by("name", true).limit(5).offset(2).build_sql();
+    assert_eq!(sql, "SELECT * FROM users WHERE id >= ? ORDER BY name DESC LIMIT 5 OFFSET 2");
+}
+
+#[test]
+fn builds_keyset_paginated_select() {
+    let sql = QueryBuilder::<User>::new().order_by("id", false).after(vec![10i64.into()]).limit(5).build_sql();
+    assert_eq!(sql, "SELECT * FROM users WHERE id > ? ORDER BY id LIMIT 5");
+    let sql = QueryBuilder::<User>::new()
+        .order_by("name", false)
+        .order_by("id", false)
+        .after(vec!["bob".into(), 10i64.into()])
+        .build_sql();
+    assert_eq!(sql, "SELECT * FROM users WHERE (name, id) > (?, ?) ORDER BY name, id");
+}
+
+#[test]
+fn builds_grouped_aggregate_select() {
+    let sql = QueryBuilder::<User>::new()
+        .select_raw("name")
+        .select_raw("COUNT(*) AS n")
+        .group_by(&["name"])
+        .order_by("name", false)
+        .build_sql();
+    assert_eq!(sql, "SELECT name, COUNT(*) AS n FROM users GROUP BY name ORDER BY name");
+}
+
+#[tokio::test]
+async fn groups_rows_into_a_provided_row_type() {
+    #[derive(Debug, FromRow)]
+    struct NameCount {
+        name: String,
+        n: i64,
+    }
+    let pool = kql_runtime::KqlPool::connect("sqlite::memory:").await.unwrap();
+    sqlx::query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL)").execute(pool.inner()).await.unwrap();
+    sqlx::query("INSERT INTO users (name) VALUES ('alice'), ('bob'), ('bob')").execute(pool.inner()).await.unwrap();
+    let rows = QueryBuilder::<User>::new()
+        .select_raw("name")
+        .select_raw("COUNT(*) AS n")
+        .group_by(&["name"])
+        .order_by("name", false)
+        .all_as::<NameCount>(&pool)
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!((rows[0].name.as_str(), rows[0].n), ("alice", 1));
+    assert_eq!((rows[1].name.as_str(), rows[1].n), ("bob", 2));
+}
+
+#[test]
+fn first_keeps_filters_and_ordering() {
+    let builder = QueryBuilder::<User>::new().filter("id", ">=", 10i64).order_by("name", true).offset(2);
+    assert_eq!(builder.first_sql(), "SELECT * FROM users WHERE id >= ? ORDER BY name DESC LIMIT 1 OFFSET 2");
+}
+
+mod prelude_only {
+    // Uses nothing but the prelude, the way generated code does.
+    use kql_runtime::prelude::*;
+
+    #[derive(Debug, FromRow)]
+    struct Account {
+        #[allow(dead_code)]
+        id: i64,
+    }
+
+    impl KqlEntity for Account {
+        const TABLE: &'static str = "accounts";
+        const COLUMNS: &'static [&'static str] = &["id"];
+        const PRIMARY_KEY: &'static str = "id";
+    }
+
+    #[test]
+    fn prelude_covers_generated_code() {
+        let sql = QueryBuilder::<Account>::new().filter("id", "=", SqlValue::Int(1)).build_sql();
+        assert_eq!(sql, "SELECT * FROM accounts WHERE id = ?");
+        assert_eq!(KqlDialect::default(), KqlDialect::Sqlite);
+    }
+}
+
+#[tokio::test]
+async fn fetches_rows_from_sqlite() {
+    let pool = kql_runtime::KqlPool::connect("sqlite::memory:").await.unwrap();
+    sqlx::query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL)").execute(pool.inner()).await.unwrap();
+    sqlx::query("INSERT INTO users (name) VALUES ('alice'), ('bob')").execute(pool.inner()).await.unwrap();
+    let rows = QueryBuilder::<User>::new().filter("name", "=", "bob").all(&pool).await.unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].name, "bob");
+    let first = QueryBuilder::<User>::new().order_by("name", true).first(&pool).await.unwrap().unwrap();
+    assert_eq!(first.name, "bob");
+}
+
+#[derive(Debug, FromRow)]
+struct Post {
+    #[allow(dead_code)]
+    id: i64,
+    #[allow(dead_code)]
+    title: String,
+    #[allow(dead_code)]
+    author_id: Option<i64>,
+}
+
+impl KqlEntity for Post {
+    const TABLE: &'static str = "posts";
+    const COLUMNS: &'static [&'static str] = &["id", "title", "author_id"];
+    const PRIMARY_KEY: &'static str = "id";
+    const RELATIONS: &'static [KqlRelation] =
+        &[KqlRelation { name: "author", fk_column: "author_id", to_table: "users", to_primary_key: "id" }];
+}
+
+#[test]
+fn builds_joined_select_for_with() {
+    let sql = QueryBuilder::<Post>::new().with("author").build_sql();
+    assert_eq!(sql, "SELECT posts.* FROM posts LEFT OUTER JOIN users ON posts.author_id = users.id");
+}
+
+#[tokio::test]
+async fn eager_loads_a_singular_relation() {
+    let pool = kql_runtime::KqlPool::connect("sqlite::memory:").await.unwrap();
+    sqlx::query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL)").execute(pool.inner()).await.unwrap();
+    sqlx::query("CREATE TABLE posts (id INTEGER PRIMARY KEY, title TEXT NOT NULL, author_id INTEGER)")
+        .execute(pool.inner())
+        .await
+        .unwrap();
+    sqlx::query("INSERT INTO users (id, name) VALUES (1, 'alice')").execute(pool.inner()).await.unwrap();
+    sqlx::query("INSERT INTO posts (title, author_id) VALUES ('hello', 1), ('again', 1), ('orphan', NULL)")
+        .execute(pool.inner())
+        .await
+        .unwrap();
+    let rows = QueryBuilder::<Post>::new().with("author").all_with::<User>(&pool).await.unwrap();
+    assert_eq!(rows.len(), 3);
+    // One author row hydrates both of alice's posts.
+    assert_eq!(rows[0].1.as_ref().map(|u| u.name.as_str()), Some("alice"));
+    assert_eq!(rows[1].1.as_ref().map(|u| u.name.as_str()), Some("alice"));
+    assert!(rows[2].1.is_none(), "{:?}", rows[2]);
+}
+
+#[test]
+fn builds_descending_keyset_select() {
+    let sql = QueryBuilder::<User>::new().order_by("id", true).after(vec![10i64.into()]).limit(5).build_sql();
+    assert_eq!(sql, "SELECT * FROM users WHERE id < ? ORDER BY id DESC LIMIT 5");
+    let sql = QueryBuilder::<User>::new()
+        .order_by("name", true)
+        .order_by("id", true)
+        .after(vec!["bob".into(), 10i64.into()])
+        .build_sql();
+    assert_eq!(sql, "SELECT * FROM users WHERE (name, id) < (?, ?) ORDER BY name DESC, id DESC");
+    // Mixed directions have no row-value form and expand lexicographically.
+    let sql = QueryBuilder::<User>::new()
+        .order_by("name", false)
+        .order_by("id", true)
+        .after(vec!["bob".into(), 10i64.into()])
+        .build_sql();
+    assert_eq!(sql, "SELECT * FROM users WHERE ((name > ?) OR (name = ? AND id < ?)) ORDER BY name, id DESC");
+}
@@ -0,0 +1,299 @@
+#![deny(missing_debug_implementations)]
+#![warn(rustdoc::missing_crate_level_docs)]
+#![doc = include_str!("../readme.md")]
+
+use indexmap::IndexMap;
+use kql_types::{KqlError, Result, Span};
+use sqlparser::{
+    ast::{ColumnDef, ColumnOption, CreateTable, DataType, ObjectName, Statement, TableConstraint},
+    dialect::{GenericDialect, MySqlDialect, PostgreSqlDialect, SQLiteDialect},
+    parser::Parser,
+};
+
+/// The SQL flavour [Transpiler::transpile] parses its input as.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputDialect {
+    /// A permissive grammar accepting most common DDL.
+    #[default]
+    Generic,
+    /// PostgreSQL.
+    Postgres,
+    /// MySQL.
+    MySql,
+    /// SQLite.
+    Sqlite,
+}
+
+/// Converts SQL DDL into KQL schema source.
+#[derive(Debug, Default)]
+pub struct Transpiler {
+    dialect: InputDialect,
+}
+
+impl Transpiler {
+    /// Create a transpiler with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Choose the SQL grammar the input is parsed with, when the generic one
+    /// is too permissive or rejects dialect-specific syntax.
+    pub fn with_input_dialect(mut self, dialect: InputDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Transpile a SQL script into KQL source. Only `CREATE TABLE` statements
+    /// are translated; everything else is skipped.
+    pub fn transpile(&self, sql: &str) -> Result<String> {
+        let dialect: Box<dyn sqlparser::dialect::Dialect> = match self.dialect {
+            InputDialect::Generic => Box::new(GenericDialect {}),
+            InputDialect::Postgres => Box::new(PostgreSqlDialect {}),
+            InputDialect::MySql => Box::new(MySqlDialect {}),
+            InputDialect::Sqlite => Box::new(SQLiteDialect {}),
+        };
+        let statements = Parser::parse_sql(&*dialect, sql)
+            .map_err(|e| KqlError::syntax(format!("failed to parse SQL: {}", e), Span::default()))?;
+        let comments = scan_comments(sql);
+        // Standalone `CREATE INDEX` statements become `@index` attributes on
+        // the matching struct, so collect them before translating tables.
+        let mut indexes: IndexMap<(Option<String>, String), Vec<String>> = IndexMap::new();
+        for statement in &statements {
+            if let Statement::CreateIndex(create) = statement {
+                indexes.entry(split_name(&create.table_name)).or_default().push(index_attribute(create));
+            }
+        }
+        // Group tables by schema so each schema becomes a namespace block.
+        let mut schemas: IndexMap<Option<String>, Vec<String>> = IndexMap::new();
+        for statement in &statements {
+            if let Statement::CreateTable(create) = statement {
+                let (schema, table) = split_name(&create.name);
+                let attributes = indexes.get(&(schema.clone(), table.clone())).map(Vec::as_slice).unwrap_or(&[]);
+                let source = self.transpile_table(schema.as_deref(), &table, create, &comments, attributes);
+                schemas.entry(schema).or_default().push(source);
+            }
+        }
+        let mut out = String::new();
+        for (schema, tables) in schemas {
+            match schema {
+                Some(schema) => {
+                    out.push_str(&format!("namespace {} {{\n", to_pascal_case(&schema)));
+                    for table in tables {
+                        for line in table.lines() {
+                            if line.is_empty() {
+                                out.push('\n');
+                            } else {
+                                out.push_str(&format!("    {}\n", line));
+                            }
+                        }
+                    }
+                    out.push_str("}\n\n");
+                }
+                None => {
+                    for table in tables {
+                        out.push_str(&table);
+                        out.push('\n');
+                    }
+                }
+            }
+        }
+        Ok(out.trim_end().to_string() + "\n")
+    }
+
+    fn transpile_table(
+        &self,
+        schema: Option<&str>,
+        table: &str,
+        create: &CreateTable,
+        comments: &CommentMap,
+        attributes: &[String],
+    ) -> String {
+        let struct_name = to_pascal_case(table);
+        let mut primary_keys: Vec<String> = Vec::new();
+        let mut foreign_keys: IndexMap<String, (Option<String>, String)> = IndexMap::new();
+        for constraint in &create.constraints {
+            if let TableConstraint::PrimaryKey { columns, .. } = constraint {
+                primary_keys.extend(columns.iter().map(|c| c.to_string()));
+            }
+            if let TableConstraint::ForeignKey { columns, foreign_table, .. } = constraint {
+                if let [column] = columns.as_slice() {
+                    foreign_keys.insert(column.value.clone(), split_name(foreign_table));
+                }
+            }
+        }
+        for column in &create.columns {
+            if column.options.iter().any(|o| matches!(o.option, ColumnOption::Unique { is_primary: true, .. })) {
+                primary_keys.push(column.name.value.clone());
+            }
+        }
+        let mut out = format!("@table(\"{}\")\n", table);
+        for attribute in attributes {
+            out.push_str(attribute);
+            out.push('\n');
+        }
+        out.push_str(&format!("struct {} {{\n", struct_name));
+        for column in &create.columns {
+            if let Some(lines) = comments.get(&(table.to_ascii_lowercase(), column.name.value.to_ascii_lowercase())) {
+                for line in lines {
+                    out.push_str(&format!("    /// {}\n", line));
+                }
+            }
+            out.push_str(&format!(
+                "    {},\n",
+                self.transpile_column(schema, &struct_name, column, &primary_keys, &foreign_keys)
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn transpile_column(
+        &self,
+        schema: Option<&str>,
+        struct_name: &str,
+        column: &ColumnDef,
+        primary_keys: &[String],
+        foreign_keys: &IndexMap<String, (Option<String>, String)>,
+    ) -> String {
+        let name = column.name.value.clone();
+        let base = kql_type(&column.data_type);
+        let not_null = column.options.iter().any(|o| matches!(o.option, ColumnOption::NotNull))
+            || primary_keys.contains(&name)
+            || column.options.iter().any(|o| matches!(o.option, ColumnOption::Unique { is_primary: true, .. }));
+        let inline_fk = column.options.iter().find_map(|o| match &o.option {
+            ColumnOption::ForeignKey { foreign_table, .. } => Some(split_name(foreign_table)),
+            _ => None,
+        });
+        if let Some((fk_schema, fk_table)) = inline_fk.or_else(|| foreign_keys.get(&name).cloned()) {
+            // A referencing column becomes a `ForeignKey` field; the `_id`
+            // suffix comes back when KQL generates the column.
+            let target = match &fk_schema {
+                Some(s) if Some(s.as_str()) != schema => format!("{}::{}", to_pascal_case(s), to_pascal_case(&fk_table)),
+                _ => to_pascal_case(&fk_table),
+            };
+            let field = name.strip_suffix("_id").unwrap_or(&name);
+            return format!("{}: ForeignKey<{}>", field, target);
+        }
+        let mut ty = if primary_keys.contains(&name) && primary_keys.len() == 1 {
+            format!("Key<{}, {}>", struct_name, base)
+        } else {
+            base
+        };
+        if !not_null {
+            ty.push('?');
+        }
+        let mut attributes = Vec::new();
+        if is_serial(&column.data_type) {
+            attributes.push("@auto_increment".to_string());
+        }
+        for option in &column.options {
+            match &option.option {
+                ColumnOption::Unique { is_primary: false, .. } => attributes.push("@unique".to_string()),
+                ColumnOption::Default(expr) => attributes.push(format!("@default({})", expr)),
+                _ => {}
+            }
+        }
+        let attributes = if attributes.is_empty() { String::new() } else { format!(" {}", attributes.join(" ")) };
+        format!("{}: {}{}", name, ty, attributes)
+    }
+}
+
+/// Render a standalone `CREATE INDEX` as a KQL `@index` attribute.
+fn index_attribute(create: &sqlparser::ast::CreateIndex) -> String {
+    let columns: Vec<String> = create
+        .columns
+        .iter()
+        .map(|column| match column.asc {
+            Some(false) => format!("{}.desc()", column.expr),
+            Some(true) => format!("{}.asc()", column.expr),
+            None => column.expr.to_string(),
+        })
+        .collect();
+    let unique = if create.unique { ", unique: true" } else { "" };
+    format!("@index(columns: [{}]{})", columns.join(", "), unique)
+}
+
+/// `-- comment` lines keyed by the lowercased `(table, column)` they sit above.
+type CommentMap = IndexMap<(String, String), Vec<String>>;
+
+/// Pre-scan the raw SQL for `--` comments, since sqlparser discards trivia.
+/// Comments directly above a column definition attach to that column.
+fn scan_comments(sql: &str) -> CommentMap {
+    let mut comments = CommentMap::new();
+    let mut pending: Vec<String> = Vec::new();
+    let mut table = String::new();
+    for raw in sql.lines() {
+        let line = raw.trim();
+        if line.is_empty() {
+            pending.clear();
+            continue;
+        }
+        if let Some(text) = line.strip_prefix("--") {
+            pending.push(text.trim().to_string());
+            continue;
+        }
+        if let Some(rest) = line.to_ascii_lowercase().strip_prefix("create table") {
+            let name = rest.trim().split(|c: char| c == '(' || c.is_whitespace()).next().unwrap_or("");
+            table = name.rsplit('.').next().unwrap_or("").to_string();
+            pending.clear();
+            continue;
+        }
+        let first = line.split(|c: char| !(c.is_alphanumeric() || c == '_')).next().unwrap_or("");
+        if !pending.is_empty() && !first.is_empty() && !table.is_empty() {
+            comments.insert((table.clone(), first.to_ascii_lowercase()), std::mem::take(&mut pending));
+        }
+        pending.clear();
+    }
+    comments
+}
+
+fn split_name(name: &ObjectName) -> (Option<String>, String) {
+    let parts: Vec<String> = name.0.iter().map(|p| p.value.clone()).collect();
+    match parts.as_slice() {
+        [table] => (None, table.clone()),
+        [schema, table] => (Some(schema.clone()), table.clone()),
+        _ => (None, name.to_string()),
+    }
+}
+
+fn is_serial(data_type: &DataType) -> bool {
+    matches!(data_type, DataType::Custom(name, _) if name.to_string().to_ascii_lowercase().contains("serial"))
+}
+
+fn kql_type(data_type: &DataType) -> String {
+    match data_type {
+        DataType::TinyInt(_) => "i8".to_string(),
+        DataType::SmallInt(_) => "i16".to_string(),
+        DataType::Int(_) | DataType::Integer(_) => "i32".to_string(),
+        DataType::BigInt(_) => "i64".to_string(),
+        DataType::UnsignedTinyInt(_) => "u8".to_string(),
+        DataType::UnsignedSmallInt(_) => "u16".to_string(),
+        DataType::UnsignedInt(_) | DataType::UnsignedInteger(_) => "u32".to_string(),
+        DataType::UnsignedBigInt(_) => "u64".to_string(),
+        DataType::Real | DataType::Float(_) => "f32".to_string(),
+        DataType::Double | DataType::DoublePrecision => "f64".to_string(),
+        DataType::Decimal(_) | DataType::Numeric(_) => "d128".to_string(),
+        DataType::Boolean | DataType::Bool => "bool".to_string(),
+        DataType::Date => "Date".to_string(),
+        DataType::Time(_, _) => "Time".to_string(),
+        DataType::Timestamp(_, _) | DataType::Datetime(_) => "DateTime".to_string(),
+        DataType::Uuid => "Uuid".to_string(),
+        DataType::JSON | DataType::JSONB => "Json".to_string(),
+        DataType::Custom(name, _) if name.to_string().eq_ignore_ascii_case("serial") => "i32".to_string(),
+        DataType::Custom(name, _) if name.to_string().eq_ignore_ascii_case("bigserial") => "i64".to_string(),
+        _ => "String".to_string(),
+    }
+}
+
+fn to_pascal_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
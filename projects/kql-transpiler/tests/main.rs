@@ -0,0 +1,74 @@
+use kql_transpiler::Transpiler;
+
+#[test]
+fn ready() {
+    println!("it works!")
+}
+
+#[test]
+fn transpiles_create_table() {
+    let sql = r#"
+CREATE TABLE users (
+    id BIGSERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    age INTEGER
+);
+"#;
+    let kql = Transpiler::new().transpile(sql).unwrap();
+    assert!(kql.contains("struct Users"), "{kql}");
+    assert!(kql.contains("id: Key<Users, i64>"), "{kql}");
+    assert!(kql.contains("age: i32?"), "{kql}");
+}
+
+#[test]
+fn attaches_standalone_indexes_to_structs() {
+    let sql = r#"
+CREATE TABLE users (
+    id BIGSERIAL PRIMARY KEY,
+    email TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL
+);
+CREATE UNIQUE INDEX users_email_idx ON users (email);
+CREATE INDEX users_created_idx ON users (created_at DESC);
+"#;
+    let kql = Transpiler::new().transpile(sql).unwrap();
+    assert!(kql.contains("@index(columns: [email], unique: true)"), "{kql}");
+    assert!(kql.contains("@index(columns: [created_at.desc()])"), "{kql}");
+}
+
+#[test]
+fn carries_column_comments_into_doc_comments() {
+    let sql = r#"
+CREATE TABLE users (
+    id BIGSERIAL PRIMARY KEY,
+    -- Display name, shown in the UI.
+    name TEXT NOT NULL
+);
+"#;
+    let kql = Transpiler::new().transpile(sql).unwrap();
+    assert!(kql.contains("    /// Display name, shown in the UI.\n    name: String"), "{kql}");
+}
+
+#[test]
+fn translates_cross_schema_foreign_keys() {
+    let sql = r#"
+CREATE TABLE app.users (
+    id BIGSERIAL PRIMARY KEY
+);
+CREATE TABLE billing.invoices (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES app.users(id),
+    parent_id BIGINT REFERENCES billing.invoices(id)
+);
+"#;
+    let kql = Transpiler::new().transpile(sql).unwrap();
+    assert!(kql.contains("namespace Billing {"), "{kql}");
+    assert!(kql.contains("user: ForeignKey<App::Users>"), "{kql}");
+    // Same-schema references need no qualification.
+    assert!(kql.contains("parent: ForeignKey<Invoices>"), "{kql}");
+}
+
+#[test]
+fn rejects_invalid_sql() {
+    assert!(Transpiler::new().transpile("CREATE ELEPHANT").is_err());
+}
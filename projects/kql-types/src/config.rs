@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+/// Project configuration, deserialized from `kql.toml` at the project root.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KqlConfig {
+    /// Path to the root schema file, relative to `kql.toml`.
+    pub schema: Option<String>,
+    /// Default SQL dialect used when the CLI is invoked without `--dialect`.
+    pub dialect: Option<String>,
+    /// Code generation settings.
+    pub codegen: CodegenConfig,
+    /// Migration settings.
+    pub migrations: MigrationsConfig,
+    /// Relation settings.
+    pub relations: RelationsConfig,
+}
+
+/// Settings for the `kql generate` command.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CodegenConfig {
+    /// Target language of the generated client code.
+    pub language: Option<String>,
+    /// Directory the generated code is written to.
+    pub output: Option<String>,
+}
+
+/// Defaults applied to every relation in the schema.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RelationsConfig {
+    /// Referential action for foreign keys without their own `on_delete`,
+    /// as an action keyword like `cascade`.
+    pub default_on_delete: Option<String>,
+    /// Referential action for foreign keys without their own `on_update`.
+    pub default_on_update: Option<String>,
+}
+
+/// Settings for the `kql migrate` command.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MigrationsConfig {
+    /// Directory migration scripts are written to.
+    pub directory: Option<String>,
+}
@@ -0,0 +1,92 @@
+use crate::Span;
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+/// The result type shared by every stage of the KQL toolchain.
+pub type Result<T, E = KqlError> = std::result::Result<T, E>;
+
+/// An error produced while compiling a KQL schema.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KqlError {
+    /// The source text could not be tokenized or parsed.
+    SyntaxError {
+        /// Human readable description of the problem.
+        message: String,
+        /// Location of the offending source text.
+        span: Span,
+    },
+    /// The source parsed but is not a meaningful schema.
+    SemanticError {
+        /// Human readable description of the problem.
+        message: String,
+        /// Location of the offending source text.
+        span: Span,
+    },
+    /// A file could not be read or written.
+    IoError {
+        /// Human readable description of the problem.
+        message: String,
+    },
+}
+
+impl KqlError {
+    /// Create a syntax error at the given span.
+    pub fn syntax(message: impl Into<String>, span: Span) -> Self {
+        Self::SyntaxError { message: message.into(), span }
+    }
+    /// Create a semantic error at the given span.
+    pub fn semantic(message: impl Into<String>, span: Span) -> Self {
+        Self::SemanticError { message: message.into(), span }
+    }
+    /// The span this error points at, if it has one.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Self::SyntaxError { span, .. } | Self::SemanticError { span, .. } => Some(*span),
+            Self::IoError { .. } => None,
+        }
+    }
+    /// The error message without location information.
+    pub fn message(&self) -> &str {
+        match self {
+            Self::SyntaxError { message, .. } | Self::SemanticError { message, .. } | Self::IoError { message } => message,
+        }
+    }
+    /// Convert this error into a structured diagnostic.
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic { severity: Severity::Error, message: self.message().to_string(), span: self.span().unwrap_or_default() }
+    }
+}
+
+impl Error for KqlError {}
+
+impl Display for KqlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SyntaxError { message, span } => write!(f, "syntax error at {}..{}: {}", span.start, span.end, message),
+            Self::SemanticError { message, span } => write!(f, "semantic error at {}..{}: {}", span.start, span.end, message),
+            Self::IoError { message } => write!(f, "io error: {}", message),
+        }
+    }
+}
+
+/// How serious a [Diagnostic] is.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// A hint or style suggestion that never blocks compilation.
+    Warning,
+    /// A hard error; no artifacts are produced.
+    Error,
+}
+
+/// A structured, renderer-agnostic diagnostic used by the CLI and the LSP.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// Whether this diagnostic blocks compilation.
+    pub severity: Severity,
+    /// Human readable description of the problem.
+    pub message: String,
+    /// Location of the offending source text.
+    pub span: Span,
+}
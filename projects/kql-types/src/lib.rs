@@ -0,0 +1,13 @@
+#![deny(missing_debug_implementations)]
+#![warn(rustdoc::missing_crate_level_docs)]
+#![doc = include_str!("../readme.md")]
+
+mod config;
+mod errors;
+mod span;
+
+pub use crate::{
+    config::{CodegenConfig, KqlConfig, MigrationsConfig, RelationsConfig},
+    errors::{Diagnostic, KqlError, Result, Severity},
+    span::{LineIndex, Span},
+};
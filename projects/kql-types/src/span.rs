@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+/// A half-open byte range `[start, end)` into a source file.
+///
+/// Spans are produced by the lexer and flow unchanged through the AST, HIR and
+/// diagnostics, so that every error can point back at the offending source.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Span {
+    /// Byte offset of the first character covered by this span.
+    pub start: usize,
+    /// Byte offset one past the last character covered by this span.
+    pub end: usize,
+}
+
+impl Span {
+    /// Create a new span from raw byte offsets.
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// The smallest span covering both `self` and `other`, including any gap
+    /// between them.
+    pub fn merge(&self, other: &Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+
+    /// Whether the byte offset `pos` falls inside this span.
+    pub fn contains(&self, pos: usize) -> bool {
+        self.start <= pos && pos < self.end
+    }
+
+    /// The number of bytes this span covers.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Whether this span covers no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// Byte offsets of every line start in a source file, built once so that
+/// repeated offset-to-position lookups cost a binary search plus a scan of
+/// one line, instead of rescanning the whole prefix per lookup.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    source: &'a str,
+    /// Byte offset each line starts at; the first entry is always 0.
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    /// Index `source` in a single pass.
+    pub fn new(source: &'a str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(source.char_indices().filter(|&(_, c)| c == '\n').map(|(index, _)| index + 1));
+        Self { source, line_starts }
+    }
+
+    /// Zero-based line number containing the byte offset.
+    pub fn line(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= offset) - 1
+    }
+
+    /// Zero-based `(line, column)` of a byte offset. The column counts
+    /// characters from the line start, matching what editors display.
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let line = self.line(offset);
+        let start = self.line_starts[line];
+        let column = self.source[start..offset.min(self.source.len())].chars().count();
+        (line, column)
+    }
+}
@@ -0,0 +1,43 @@
+use kql_types::{KqlConfig, KqlError, Span};
+
+#[test]
+fn ready() {
+    println!("it works!")
+}
+
+#[test]
+fn config_round_trips() {
+    let text = r#"
+schema = "schema.kql"
+dialect = "postgres"
+
+[codegen]
+language = "rust"
+"#;
+    let config: KqlConfig = toml::from_str(text).unwrap();
+    assert_eq!(config.schema.as_deref(), Some("schema.kql"));
+    assert_eq!(config.codegen.language.as_deref(), Some("rust"));
+    let back: KqlConfig = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+    assert_eq!(back, config);
+}
+
+#[test]
+fn errors_carry_spans() {
+    let error = KqlError::syntax("unexpected token", Span::new(3, 7));
+    assert_eq!(error.span(), Some(Span::new(3, 7)));
+    assert_eq!(error.message(), "unexpected token");
+    assert_eq!(KqlError::IoError { message: "gone".to_string() }.span(), None);
+}
+
+#[test]
+fn spans_merge_and_measure() {
+    // Overlapping, disjoint (covering the gap) and contained spans.
+    assert_eq!(Span::new(2, 6).merge(&Span::new(4, 9)), Span::new(2, 9));
+    assert_eq!(Span::new(0, 3).merge(&Span::new(7, 10)), Span::new(0, 10));
+    assert_eq!(Span::new(1, 9).merge(&Span::new(3, 5)), Span::new(1, 9));
+    assert_eq!(Span::new(3, 5).merge(&Span::new(1, 9)), Span::new(1, 9));
+    assert!(Span::new(2, 6).contains(2));
+    assert!(!Span::new(2, 6).contains(6));
+    assert_eq!(Span::new(2, 6).len(), 4);
+    assert!(Span::new(4, 4).is_empty());
+}
@@ -0,0 +1,28 @@
+#![deny(missing_debug_implementations)]
+#![warn(rustdoc::missing_crate_level_docs)]
+#![doc = include_str!("../readme.md")]
+
+use kql_analyzer::{Pipeline, lir::Dialect};
+
+/// Compile a KQL source string to SQL for the given dialect name.
+///
+/// Errors are flattened into plain strings so the result can cross a WASI
+/// boundary without a shared error type.
+pub fn compile_to_sql(source: &str, dialect: &str) -> Result<String, String> {
+    let dialect: Dialect = dialect.parse().map_err(|e| format!("{e}"))?;
+    let pipeline = Pipeline::new();
+    let hir = pipeline.compile(source).map_err(join_errors)?;
+    let mir = pipeline.to_mir(hir).map_err(|e| e.to_string())?;
+    let statements = pipeline.to_sql(&mir, dialect).map_err(|e| e.to_string())?;
+    Ok(statements.iter().map(|s| format!("{s};")).collect::<Vec<_>>().join("\n\n"))
+}
+
+/// Validate a KQL source string, returning all error messages joined by
+/// newlines if any.
+pub fn check(source: &str) -> Result<(), String> {
+    Pipeline::new().compile(source).map(|_| ()).map_err(join_errors)
+}
+
+fn join_errors<E: std::fmt::Display>(errors: Vec<E>) -> String {
+    errors.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("\n")
+}
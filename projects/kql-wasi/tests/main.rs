@@ -0,0 +1,16 @@
+#[test]
+fn ready() {
+    println!("it works!")
+}
+
+#[test]
+fn compiles_source_to_sql() {
+    let sql = kql_wasi::compile_to_sql("struct User { id: Key<User, i64> }", "sqlite").unwrap();
+    assert!(sql.contains("CREATE TABLE user"), "{sql}");
+}
+
+#[test]
+fn reports_errors_as_strings() {
+    assert!(kql_wasi::compile_to_sql("struct {", "postgres").is_err());
+    assert!(kql_wasi::check("struct User { id: i64 }").is_ok());
+}